@@ -0,0 +1,229 @@
+//! Python bindings, built into the `igc_parser` Python module with maturin
+
+use std::fmt::Display;
+
+use igc_parser::error::IGCError;
+use igc_parser::igc_file::IGCFile;
+use igc_parser::metadata::FlightMetadata;
+use igc_parser::track::columns::FixColumns;
+use numpy::IntoPyArray;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+mod records;
+
+use records::*;
+
+/// A record that failed to parse
+#[pyclass(name = "ParseError", module = "igc_parser", frozen, get_all)]
+#[derive(Clone)]
+struct PyParseError {
+    /// The letter of the record, e.g. `B`
+    record: char,
+    /// Line of the file counting from 1
+    line: Option<usize>,
+    /// What failed, e.g. `fix` or `coordinate`
+    kind: &'static str,
+    message: String,
+}
+
+#[pymethods]
+impl PyParseError {
+    fn __repr__(&self) -> String {
+        format!("ParseError(record={:?}, line={:?}, kind={:?}, message={:?})", self.record, self.line, self.kind, self.message)
+    }
+}
+
+/// A parsed IGC file, the records that parsed are in one list per kind and the others in `errors`
+#[pyclass(name = "IGCFile", module = "igc_parser", frozen)]
+struct PyIGCFile(IGCFile);
+
+/// The records that parsed, wrapped for Python
+fn valid<T: Clone, W>(results: &[Result<T, IGCError>], wrap: fn(T) -> W) -> Vec<W> {
+    results.iter().filter_map(|result| result.as_ref().ok()).map(|record| wrap(record.clone())).collect()
+}
+
+#[pymethods]
+impl PyIGCFile {
+    /// Parses the text of an IGC file
+    #[staticmethod]
+    fn parse(py: Python<'_>, content: &str) -> PyResult<Self> {
+        py.detach(|| IGCFile::parse(content)).map(Self).map_err(value_error)
+    }
+
+    /// Reads and parses the IGC file at `path`, invalid UTF-8 is replaced
+    #[staticmethod]
+    fn read(py: Python<'_>, path: std::path::PathBuf) -> PyResult<Self> {
+        let bytes = std::fs::read(&path).map_err(|error| PyOSError::new_err(format!("{}: {error}", path.display())))?;
+        py.detach(|| IGCFile::parse(&String::from_utf8_lossy(&bytes))).map(Self).map_err(value_error)
+    }
+
+    #[getter]
+    fn fixes(&self) -> Vec<PyFix> {
+        valid(self.0.get_fixes(), PyFix)
+    }
+
+    #[getter]
+    fn headers(&self) -> Vec<PyHeader> {
+        valid(self.0.get_file_header_vec(), PyHeader)
+    }
+
+    #[getter]
+    fn events(&self) -> Vec<PyEvent> {
+        valid(self.0.get_events(), PyEvent)
+    }
+
+    #[getter]
+    fn comments(&self) -> Vec<PyComment> {
+        valid(self.0.get_comment_vec(), PyComment)
+    }
+
+    #[getter]
+    fn flight_recorder_ids(&self) -> Vec<PyFlightRecorderId> {
+        valid(self.0.get_fr_ids(), PyFlightRecorderId)
+    }
+
+    /// The C records as `TaskPoint` and `Declaration`
+    #[getter]
+    fn task(&self, py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+        self.0.get_task_info().iter().filter_map(|result| result.as_ref().ok()).map(|info| task_info(py, info)).collect()
+    }
+
+    #[getter]
+    fn satellites(&self) -> Vec<PySatellites> {
+        valid(self.0.get_satellite_vec(), PySatellites)
+    }
+
+    #[getter]
+    fn security(&self) -> Vec<PySecurity> {
+        valid(self.0.get_security_vec(), PySecurity)
+    }
+
+    #[getter]
+    fn differential_gps(&self) -> Vec<PyDifferentialGps> {
+        valid(self.0.get_differential_gps_records(), PyDifferentialGps)
+    }
+
+    #[getter]
+    fn data_fixes(&self) -> Vec<PyDataFix> {
+        valid(self.0.get_data_fix_vec(), PyDataFix)
+    }
+
+    #[getter]
+    fn i_extensions(&self) -> Vec<PyExtensions> {
+        valid(self.0.get_i_extension_vec(), PyExtensions)
+    }
+
+    #[getter]
+    fn j_extensions(&self) -> Vec<PyExtensions> {
+        valid(self.0.get_j_extension_vec(), PyExtensions)
+    }
+
+    /// Every record that failed to parse, in the order of the file
+    #[getter]
+    fn errors(&self) -> Vec<PyParseError> {
+        let file = &self.0;
+        let mut errors = Vec::new();
+        collect_errors(file, 'A', file.get_fr_ids(), &mut errors);
+        collect_errors(file, 'B', file.get_fixes(), &mut errors);
+        collect_errors(file, 'C', file.get_task_info(), &mut errors);
+        collect_errors(file, 'D', file.get_differential_gps_records(), &mut errors);
+        collect_errors(file, 'E', file.get_events(), &mut errors);
+        collect_errors(file, 'F', file.get_satellite_vec(), &mut errors);
+        collect_errors(file, 'G', file.get_security_vec(), &mut errors);
+        collect_errors(file, 'H', file.get_file_header_vec(), &mut errors);
+        collect_errors(file, 'I', file.get_i_extension_vec(), &mut errors);
+        collect_errors(file, 'J', file.get_j_extension_vec(), &mut errors);
+        collect_errors(file, 'K', file.get_data_fix_vec(), &mut errors);
+        collect_errors(file, 'L', file.get_comment_vec(), &mut errors);
+        errors.sort_by_key(|error| error.line.unwrap_or(usize::MAX));
+        errors
+    }
+
+    /// Pilot, glider, logger and date from the headers and the A record, `None` for what the file does not contain
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        metadata(py, &FlightMetadata::from_igc_file(&self.0))
+    }
+
+    /// The valid fixes as NumPy arrays, see `fix_arrays`
+    fn fix_arrays<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        arrays(py, FixColumns::from_igc_file(&self.0))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("IGCFile(fixes={}, errors={})", self.0.get_fixes().len(), self.errors().len())
+    }
+}
+
+fn collect_errors<T>(file: &IGCFile, letter: char, results: &[Result<T, IGCError>], errors: &mut Vec<PyParseError>) {
+    for (index, result) in results.iter().enumerate() {
+        let Err(error) = result else { continue };
+        let line = file.error_line(letter, index);
+        errors.push(PyParseError { record: letter, line, kind: error.kind(), message: error.message().to_string() });
+    }
+}
+
+fn value_error(error: impl Display) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn metadata<'py>(py: Python<'py>, metadata: &FlightMetadata) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("date", metadata.date.as_ref().map(|value| date(py, value)).transpose()?)?;
+    for (key, value) in metadata.text_fields() {
+        dict.set_item(key, value)?;
+    }
+    dict.set_item("fix_accuracy", metadata.fix_accuracy)?;
+    Ok(dict)
+}
+
+/// One array per column, the vectors are handed to NumPy without copying
+fn arrays(py: Python<'_>, columns: FixColumns) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("timestamp", columns.timestamps.into_pyarray(py))?;
+    dict.set_item("latitude", columns.latitudes.into_pyarray(py))?;
+    dict.set_item("longitude", columns.longitudes.into_pyarray(py))?;
+    dict.set_item("pressure_altitude", columns.pressure_altitudes.into_pyarray(py))?;
+    dict.set_item("gps_altitude", columns.gps_altitudes.into_pyarray(py))?;
+    dict.set_item("gps_valid", columns.gps_valid.into_pyarray(py))?;
+    for column in columns.extensions {
+        dict.set_item(format!("{}_valid", column.code), column.valid.into_pyarray(py))?;
+        dict.set_item(column.code.as_ref(), column.values.into_pyarray(py))?;
+    }
+    Ok(dict)
+}
+
+/// Parses the text of an IGC file
+#[pyfunction]
+fn parse(py: Python<'_>, content: &str) -> PyResult<PyIGCFile> {
+    PyIGCFile::parse(py, content)
+}
+
+/// Reads and parses the IGC file at `path`
+#[pyfunction]
+fn read(py: Python<'_>, path: std::path::PathBuf) -> PyResult<PyIGCFile> {
+    PyIGCFile::read(py, path)
+}
+
+/// The valid fixes of the text of an IGC file as a dict of NumPy arrays, read without creating a record per fix
+///
+/// `timestamp` is in Unix seconds when the file has a date and seconds from midnight otherwise, `gps_altitude` is zero
+/// where `gps_valid` is false and every extension of the I record has an `int32` array, e.g. `ENL`, with a `ENL_valid` mask
+#[pyfunction]
+fn fix_arrays<'py>(py: Python<'py>, content: &str) -> PyResult<Bound<'py, PyDict>> {
+    arrays(py, py.detach(|| FixColumns::parse(content)))
+}
+
+/// Fast IGC flight recorder file parsing with per-record errors
+#[pymodule]
+#[pyo3(name = "igc_parser")]
+fn igc_parser_py(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(parse, module)?)?;
+    module.add_function(wrap_pyfunction!(read, module)?)?;
+    module.add_function(wrap_pyfunction!(fix_arrays, module)?)?;
+    module.add_class::<PyIGCFile>()?;
+    module.add_class::<PyParseError>()?;
+    records::register(module)
+}
@@ -0,0 +1,298 @@
+use igc_parser::records::comment::Comment;
+use igc_parser::records::data_fix::DataFix;
+use igc_parser::records::diff_gps::{DiffGPS, DiffGPSQualifier};
+use igc_parser::records::event::Event;
+use igc_parser::records::extension::{Extension, ExtensionType};
+use igc_parser::records::file_header::FileHeaderRecord;
+use igc_parser::records::fix::Fix;
+use igc_parser::records::flight_recorder_id::FlightRecorderID;
+use igc_parser::records::satellite::Satellite;
+use igc_parser::records::security::Security;
+use igc_parser::records::task_info::{DeclarationTime, TaskInfo, TaskPoint};
+use igc_parser::records::util::{Date, Time};
+use pyo3::prelude::*;
+use pyo3::types::{PyDate, PyTime, PyTzInfo};
+
+/// `datetime.time` in UTC
+pub(crate) fn time<'py>(py: Python<'py>, time: &Time) -> PyResult<Bound<'py, PyTime>> {
+    PyTime::new(py, time.h, time.m, time.s, 0, Some(&PyTzInfo::utc(py)?.to_owned()))
+}
+
+pub(crate) fn date<'py>(py: Python<'py>, date: &Date) -> PyResult<Bound<'py, PyDate>> {
+    PyDate::new(py, date.full_year() as i32, date.m, date.d)
+}
+
+/// A frozen Python class wrapping a record, with the IGC line as `line` and in the `repr`
+macro_rules! record_class {
+    ($class:ident, $name:literal, $record:ty, { $($methods:tt)* }) => {
+        #[pyclass(name = $name, module = "igc_parser", frozen)]
+        #[derive(Clone)]
+        pub struct $class(pub(crate) $record);
+
+        #[pymethods]
+        impl $class {
+            /// The record as a line of an IGC file
+            #[getter]
+            fn line(&self) -> String {
+                self.0.to_string()
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}({:?})", $name, self.0.to_string())
+            }
+
+            $($methods)*
+        }
+    };
+}
+
+record_class!(PyFix, "Fix", Fix, {
+    /// UTC time of the fix
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTime>> {
+        time(py, &self.0.timestamp)
+    }
+
+    #[getter]
+    fn seconds_since_midnight(&self) -> u32 {
+        self.0.timestamp.seconds_since_midnight()
+    }
+
+    /// Signed decimal degrees
+    #[getter]
+    fn latitude(&self) -> f64 {
+        self.0.coordinates.latitude.to_decimal_degrees()
+    }
+
+    /// Signed decimal degrees
+    #[getter]
+    fn longitude(&self) -> f64 {
+        self.0.coordinates.longitude.to_decimal_degrees()
+    }
+
+    /// Meters
+    #[getter]
+    fn pressure_altitude(&self) -> i16 {
+        self.0.pressure_alt
+    }
+
+    /// Meters, `None` when the GPS altitude is not valid
+    #[getter]
+    fn gps_altitude(&self) -> Option<i16> {
+        self.0.gps_alt
+    }
+
+    /// The extension data after the GPS altitude, decoded with the I record
+    #[getter]
+    fn extension(&self) -> &str {
+        &self.0.extension
+    }
+});
+
+record_class!(PyHeader, "Header", FileHeaderRecord, {
+    /// `F` for the flight recorder, `O` for an official observer and `P` for the pilot
+    #[getter]
+    fn source(&self) -> char {
+        self.0.source.letter()
+    }
+
+    /// The three letter code, e.g. `PLT`
+    #[getter]
+    fn code(&self) -> &str {
+        self.0.header.code()
+    }
+
+    #[getter]
+    fn long_name(&self) -> Option<&str> {
+        self.0.long_name.as_deref()
+    }
+
+    /// The value as written after the long name
+    #[getter]
+    fn value(&self) -> &str {
+        &self.0.raw_value
+    }
+});
+
+record_class!(PyEvent, "Event", Event, {
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTime>> {
+        time(py, &self.0.time)
+    }
+
+    /// The three letter code, e.g. `PEV`
+    #[getter]
+    fn code(&self) -> &str {
+        &self.0.event_type
+    }
+
+    #[getter]
+    fn extension(&self) -> &str {
+        &self.0.extension
+    }
+});
+
+record_class!(PyComment, "Comment", Comment, {
+    #[getter]
+    fn text(&self) -> &str {
+        &self.0.content
+    }
+});
+
+record_class!(PyFlightRecorderId, "FlightRecorderId", FlightRecorderID, {
+    /// The manufacturer code, e.g. `FLA`
+    #[getter]
+    fn manufacturer(&self) -> &str {
+        &self.0.manufacturer
+    }
+
+    #[getter]
+    fn serial(&self) -> &str {
+        &self.0.id
+    }
+
+    #[getter]
+    fn extension(&self) -> &str {
+        &self.0.extension
+    }
+});
+
+record_class!(PyTaskPoint, "TaskPoint", TaskPoint, {
+    #[getter]
+    fn latitude(&self) -> f64 {
+        self.0.coordinate.latitude.to_decimal_degrees()
+    }
+
+    #[getter]
+    fn longitude(&self) -> f64 {
+        self.0.coordinate.longitude.to_decimal_degrees()
+    }
+
+    #[getter]
+    fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+});
+
+record_class!(PyDeclaration, "Declaration", DeclarationTime, {
+    /// UTC date of the declaration
+    #[getter]
+    fn date<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDate>> {
+        date(py, &self.0.date)
+    }
+
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTime>> {
+        time(py, &self.0.time)
+    }
+
+    /// The intended date of the flight, `None` when not given
+    #[getter]
+    fn flight_date<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDate>>> {
+        self.0.flight_date().map(|flight_date| date(py, &flight_date)).transpose()
+    }
+
+    #[getter]
+    fn task_number(&self) -> Option<u16> {
+        self.0.task_number()
+    }
+
+    #[getter]
+    fn turn_points(&self) -> Option<u8> {
+        self.0.turn_points()
+    }
+
+    #[getter]
+    fn description(&self) -> &str {
+        self.0.description()
+    }
+});
+
+record_class!(PySatellites, "Satellites", Satellite, {
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTime>> {
+        time(py, &self.0.time)
+    }
+
+    /// The IDs of the satellites in use, e.g. `["G04", "G12"]`
+    #[getter]
+    fn ids(&self) -> Vec<&str> {
+        self.0.satellite_ids.iter().map(|id| id.as_ref()).collect()
+    }
+});
+
+record_class!(PySecurity, "Security", Security, {
+    #[getter]
+    fn code(&self) -> &str {
+        &self.0.security_code
+    }
+});
+
+record_class!(PyDifferentialGps, "DifferentialGps", DiffGPS, {
+    /// `GPS` or `DGPS`
+    #[getter]
+    fn qualifier(&self) -> &str {
+        match self.0.qualifier {
+            DiffGPSQualifier::GPS => "GPS",
+            DiffGPSQualifier::DGPS => "DGPS",
+        }
+    }
+
+    #[getter]
+    fn station_id(&self) -> u16 {
+        self.0.dgps_station_id
+    }
+});
+
+record_class!(PyDataFix, "DataFix", DataFix, {
+    #[getter]
+    fn time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyTime>> {
+        time(py, &self.0.time)
+    }
+
+    #[getter]
+    fn content(&self) -> &str {
+        &self.0.content
+    }
+});
+
+record_class!(PyExtensions, "Extensions", Extension, {
+    /// `I` for extensions of the B records and `J` for the K records
+    #[getter]
+    fn letter(&self) -> char {
+        match self.0.extension_type {
+            ExtensionType::I => 'I',
+            ExtensionType::J => 'J',
+        }
+    }
+
+    /// Start byte, end byte and code of each extension, e.g. `[(36, 38, "FXA")]`
+    #[getter]
+    fn extensions(&self) -> Vec<(u8, u8, &str)> {
+        self.0.extensions.iter().map(|(start, end, code)| (*start, *end, code.as_ref())).collect()
+    }
+});
+
+/// A task point or a declaration
+pub(crate) fn task_info(py: Python<'_>, task_info: &TaskInfo) -> PyResult<Py<PyAny>> {
+    Ok(match task_info {
+        TaskInfo::TaskPoint(point) => Py::new(py, PyTaskPoint(point.clone()))?.into_any(),
+        TaskInfo::DeclarationTime(declaration) => Py::new(py, PyDeclaration(declaration.clone()))?.into_any(),
+    })
+}
+
+pub(crate) fn register(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyFix>()?;
+    module.add_class::<PyHeader>()?;
+    module.add_class::<PyEvent>()?;
+    module.add_class::<PyComment>()?;
+    module.add_class::<PyFlightRecorderId>()?;
+    module.add_class::<PyTaskPoint>()?;
+    module.add_class::<PyDeclaration>()?;
+    module.add_class::<PySatellites>()?;
+    module.add_class::<PySecurity>()?;
+    module.add_class::<PyDifferentialGps>()?;
+    module.add_class::<PyDataFix>()?;
+    module.add_class::<PyExtensions>()?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+from pathlib import Path
+
+import igc_parser
+import pytest
+
+EXAMPLE = Path(__file__).parents[2] / "examples" / "example.igc"
+
+
+@pytest.fixture(scope="module")
+def flight():
+    return igc_parser.read(EXAMPLE)
+
+
+def test_records(flight):
+    assert len(flight.fixes) == 5825
+    fix = flight.fixes[0]
+    assert fix.line == "B1238095600728N00901298EA-00230008000111"
+    assert fix.time.hour == 12 and fix.time.minute == 38
+    assert fix.latitude == pytest.approx(56.0121333)
+    assert fix.gps_altitude == 80
+    assert flight.i_extensions[0].extensions == [(36, 38, "FXA"), (39, 40, "SIU")]
+
+
+def test_metadata(flight):
+    metadata = flight.metadata
+    assert metadata["registration"] == "OY-EXD"
+    assert metadata["pilot"] == "LASSE EDSLEV"
+    assert metadata["date"].isoformat() == "2020-08-06"
+
+
+def test_errors():
+    flight = igc_parser.parse("HFDTEDATE:060820\nB1602405407121N00249342WA0028000421\nBROKEN\n")
+    assert len(flight.fixes) == 1
+    [error] = flight.errors
+    assert (error.record, error.line, error.kind) == ("B", 3, "fix")
+
+
+def test_missing_file():
+    with pytest.raises(OSError):
+        igc_parser.read("does-not-exist.igc")
+
+
+def test_fix_arrays(flight):
+    np = pytest.importorskip("numpy")
+    arrays = flight.fix_arrays()
+    assert len(arrays["latitude"]) == 5825
+    assert arrays["pressure_altitude"].dtype == np.int32
+    assert {"FXA", "FXA_valid", "SIU", "SIU_valid"} <= arrays.keys()
+    assert np.array_equal(arrays["latitude"], igc_parser.fix_arrays(EXAMPLE.read_text())["latitude"])
@@ -0,0 +1,433 @@
+use crate::analysis::altitude::{pressure_to_qnh_altitude, Calibration};
+use crate::igc_file::IGCFile;
+use crate::records::fix::Fix;
+use crate::records::util::{Coordinate, EARTH_RADIUS};
+use crate::StrWrapper;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Parser for the OpenAir airspace format
+pub mod openair;
+
+/// Meters in a foot
+pub const FEET: f64 = 0.3048;
+/// Meters in a nautical mile
+pub const NAUTICAL_MILE: f64 = 1852.;
+
+/// Airspace class or type from the `AC` command
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AirspaceClass {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    /// `R`
+    Restricted,
+    /// `Q`
+    Danger,
+    /// `P`
+    Prohibited,
+    /// `CTR`
+    ControlZone,
+    /// `GP`
+    GliderProhibited,
+    /// `TMZ`
+    TransponderMandatoryZone,
+    /// `RMZ`
+    RadioMandatoryZone,
+    /// `W`
+    WaveWindow,
+    Other(StrWrapper),
+}
+
+impl AirspaceClass {
+    pub fn parse(class: &str) -> Self {
+        match class.trim().to_ascii_uppercase().as_str() {
+            "A" => AirspaceClass::A,
+            "B" => AirspaceClass::B,
+            "C" => AirspaceClass::C,
+            "D" => AirspaceClass::D,
+            "E" => AirspaceClass::E,
+            "F" => AirspaceClass::F,
+            "G" => AirspaceClass::G,
+            "R" => AirspaceClass::Restricted,
+            "Q" => AirspaceClass::Danger,
+            "P" => AirspaceClass::Prohibited,
+            "CTR" => AirspaceClass::ControlZone,
+            "GP" => AirspaceClass::GliderProhibited,
+            "TMZ" => AirspaceClass::TransponderMandatoryZone,
+            "RMZ" => AirspaceClass::RadioMandatoryZone,
+            "W" => AirspaceClass::WaveWindow,
+            _ => AirspaceClass::Other(class.trim().into()),
+        }
+    }
+}
+
+/// Lower or upper limit of an airspace, altitudes are in meters
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AltitudeLimit {
+    Surface,
+    /// Hundreds of feet of pressure altitude on the standard setting
+    FlightLevel(u16),
+    Amsl(f64),
+    Agl(f64),
+    Unlimited,
+}
+
+/// Horizontal shape of an airspace, arcs are stored as polygon points one degree apart
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Polygon(Vec<Coordinate>),
+    /// Radius in meters
+    Circle { center: Coordinate, radius: f64 },
+}
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Airspace {
+    pub class: AirspaceClass,
+    pub name: StrWrapper,
+    pub lower: AltitudeLimit,
+    pub upper: AltitudeLimit,
+    pub geometry: Geometry,
+}
+
+impl Airspace {
+    /// Distance in meters from the coordinate to the nearest boundary, `None` if the coordinate is outside
+    pub fn horizontal_penetration(&self, coordinate: &Coordinate) -> Option<f64> {
+        match &self.geometry {
+            Geometry::Circle { center, radius } => Some(radius - center.distance_to(coordinate)).filter(|depth| *depth >= 0.),
+            Geometry::Polygon(points) => {
+                let points = points.iter().map(|point| project(coordinate, point)).collect::<Vec<_>>();
+                let mut inside = false;
+                let mut depth = f64::INFINITY;
+                for (i, a) in points.iter().enumerate() {
+                    let b = &points[(i + 1) % points.len()];
+                    if (a.1 > 0.) != (b.1 > 0.) && a.0 - a.1 * (b.0 - a.0) / (b.1 - a.1) > 0. {
+                        inside = !inside;
+                    }
+                    depth = depth.min(distance_to_segment(a, b));
+                }
+                inside.then_some(depth)
+            }
+        }
+    }
+
+    /// Meters the fix is above the lower limit and below the upper limit, whichever is less, negative if outside vertically
+    ///
+    /// A fix is taken to be outside when a limit is a flight level and its pressure altitude is unknown
+    pub fn vertical_penetration(&self, fix: &Fix, altitude_source: &AltitudeSource, ground: Option<f64>) -> f64 {
+        let above_lower = match margin(&self.lower, fix, altitude_source, ground) {
+            Limit::Below => f64::INFINITY,
+            Limit::Above | Limit::Unknown => f64::NEG_INFINITY,
+            Limit::Margin(margin) => margin,
+        };
+        let below_upper = match margin(&self.upper, fix, altitude_source, ground) {
+            Limit::Below | Limit::Unknown => f64::NEG_INFINITY,
+            Limit::Above => f64::INFINITY,
+            Limit::Margin(margin) => -margin,
+        };
+        above_lower.min(below_upper)
+    }
+
+    /// Bounding box as (south, west, north, east) in decimal degrees, crossing the antimeridian is not supported
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        let points = match &self.geometry {
+            Geometry::Circle { center, radius } => [0., 90., 180., 270.].iter().map(|bearing| center.destination(*bearing, radius * 1.01)).collect(),
+            Geometry::Polygon(points) => points.clone(),
+        };
+        points.iter().map(Coordinate::to_decimal_degrees).fold(
+            (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            |(south, west, north, east), (latitude, longitude)| (south.min(latitude), west.min(longitude), north.max(latitude), east.max(longitude)),
+        )
+    }
+}
+
+/// Position of `point` in meters east and north of `origin` on a local flat projection
+fn project(origin: &Coordinate, point: &Coordinate) -> (f64, f64) {
+    let ((origin_latitude, origin_longitude), (latitude, longitude)) = (origin.to_decimal_degrees(), point.to_decimal_degrees());
+    let longitude_difference = (longitude - origin_longitude + 540.).rem_euclid(360.) - 180.;
+    let scale = EARTH_RADIUS * std::f64::consts::PI / 180.;
+    (longitude_difference * origin_latitude.to_radians().cos() * scale, (latitude - origin_latitude) * scale)
+}
+
+/// Distance from the origin to the segment from `a` to `b`
+fn distance_to_segment(a: &(f64, f64), b: &(f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0. { 0. } else { (-(a.0 * dx + a.1 * dy) / length).clamp(0., 1.) };
+    (a.0 + t * dx).hypot(a.1 + t * dy)
+}
+
+/// Altitude used to check limits given above mean sea level or ground, flight levels always use the pressure altitude
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AltitudeSource {
+    /// The pressure altitude as recorded, relative to the standard setting of 1013.25 hPa
+    Pressure,
+    /// The pressure altitude corrected to the given QNH in hPa
+    Qnh(f64),
+    /// The GPS altitude, falling back to the pressure altitude when the fix has no valid GPS altitude
+    Gps,
+    /// The QNH altitude of a calibration, which honours the altitude reference headers of the file
+    Calibrated(Calibration),
+}
+
+impl AltitudeSource {
+    /// Altitude above mean sea level of the fix in meters
+    pub fn altitude(&self, fix: &Fix) -> f64 {
+        match self {
+            AltitudeSource::Pressure => fix.pressure_alt as f64,
+            AltitudeSource::Qnh(qnh) => pressure_to_qnh_altitude(fix.pressure_alt as f64, *qnh),
+            AltitudeSource::Gps => fix.gps_alt.unwrap_or(fix.pressure_alt) as f64,
+            AltitudeSource::Calibrated(calibration) => calibration.qnh_altitude(fix),
+        }
+    }
+
+    /// Pressure altitude of the fix relative to the standard setting, used for flight levels
+    ///
+    /// `None` for a calibration of `MSL` pressure altitudes without the recorder QNH
+    pub fn pressure_altitude(&self, fix: &Fix) -> Option<f64> {
+        match self {
+            AltitudeSource::Calibrated(calibration) => calibration.pressure_altitude(fix),
+            _ => Some(fix.pressure_alt as f64),
+        }
+    }
+}
+
+enum Limit {
+    /// The limit is below every altitude, e.g. the surface
+    Below,
+    /// The limit is above every altitude, e.g. unlimited
+    Above,
+    /// Meters the fix is above the limit
+    Margin(f64),
+    /// A flight level when the pressure altitude is unknown
+    Unknown,
+}
+
+fn margin(limit: &AltitudeLimit, fix: &Fix, altitude_source: &AltitudeSource, ground: Option<f64>) -> Limit {
+    match limit {
+        AltitudeLimit::Surface => Limit::Below,
+        AltitudeLimit::Unlimited => Limit::Above,
+        AltitudeLimit::FlightLevel(level) => match altitude_source.pressure_altitude(fix) {
+            Some(pressure_altitude) => Limit::Margin(pressure_altitude - *level as f64 * 100. * FEET),
+            None => Limit::Unknown,
+        },
+        AltitudeLimit::Amsl(altitude) => Limit::Margin(altitude_source.altitude(fix) - altitude),
+        AltitudeLimit::Agl(height) => Limit::Margin(altitude_source.altitude(fix) - ground.unwrap_or(0.) - height),
+    }
+}
+
+/// Ground elevation for airspace limits given above ground level
+pub trait Terrain {
+    /// Elevation in meters above mean sea level, `None` if unknown
+    fn elevation(&self, coordinate: &Coordinate) -> Option<f64>;
+}
+
+/// A continuous period inside an airspace
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incursion {
+    /// Index of the airspace in the checked slice
+    pub airspace: usize,
+    pub class: AirspaceClass,
+    pub name: StrWrapper,
+    /// Index of the first fix inside the airspace
+    pub entry_index: usize,
+    /// Index of the last fix inside the airspace
+    pub exit_index: usize,
+    pub entry: Fix,
+    pub exit: Fix,
+    /// Deepest distance in meters from the lateral boundary
+    pub max_horizontal_penetration: f64,
+    /// Deepest distance in meters from the nearest vertical limit
+    pub max_vertical_penetration: f64,
+}
+
+/// Checks fixes against airspaces
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::airspace::{openair, AirspaceChecker, AltitudeSource};
+/// let airspaces = openair::parse("AC R\nAN DANGER\nAL SFC\nAH FL100\nV X=51:07:00 N 001:49:00 W\nDC 20")
+///     .into_iter()
+///     .filter_map(|airspace| airspace.ok())
+///     .collect::<Vec<_>>();
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let incursions = AirspaceChecker::new(AltitudeSource::Qnh(1020.)).check_file(&airspaces, &IGCFile::parse(&file).unwrap());
+/// for incursion in incursions {
+///     println!("{} from {:?} to {:?}", incursion.name, incursion.entry.timestamp, incursion.exit.timestamp);
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AirspaceChecker {
+    pub altitude_source: AltitudeSource,
+}
+
+impl Default for AirspaceChecker {
+    fn default() -> Self {
+        Self::new(AltitudeSource::Pressure)
+    }
+}
+
+impl AirspaceChecker {
+    pub fn new(altitude_source: AltitudeSource) -> Self {
+        Self { altitude_source }
+    }
+
+    /// Checks the fixes of a parsed file, fixes that failed to parse are ignored
+    pub fn check_file(&self, airspaces: &[Airspace], igc_file: &IGCFile) -> Vec<Incursion> {
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        self.check(airspaces, &fixes)
+    }
+
+    /// Incursions ordered by airspace and then by time, limits above ground level assume the ground is at sea level
+    pub fn check(&self, airspaces: &[Airspace], fixes: &[Fix]) -> Vec<Incursion> {
+        self.check_with_terrain(airspaces, fixes, None)
+    }
+
+    /// Incursions ordered by airspace and then by time, using `terrain` for limits above ground level
+    pub fn check_with_terrain(&self, airspaces: &[Airspace], fixes: &[Fix], terrain: Option<&dyn Terrain>) -> Vec<Incursion> {
+        let positions = fixes.iter().map(|fix| fix.coordinates.to_decimal_degrees()).collect::<Vec<_>>();
+        let mut incursions = Vec::new();
+        for (index, airspace) in airspaces.iter().enumerate() {
+            let (south, west, north, east) = airspace.bounds();
+            let uses_ground = matches!(airspace.lower, AltitudeLimit::Agl(_)) || matches!(airspace.upper, AltitudeLimit::Agl(_));
+            let mut current: Option<Incursion> = None;
+            for (i, fix) in fixes.iter().enumerate() {
+                let (latitude, longitude) = positions[i];
+                let in_bounds = (south..=north).contains(&latitude) && (west..=east).contains(&longitude);
+                let penetration = in_bounds.then(|| airspace.horizontal_penetration(&fix.coordinates)).flatten().and_then(|horizontal| {
+                    let ground = if uses_ground { terrain.and_then(|terrain| terrain.elevation(&fix.coordinates)) } else { None };
+                    let vertical = airspace.vertical_penetration(fix, &self.altitude_source, ground);
+                    (vertical >= 0.).then_some((horizontal, vertical))
+                });
+                match (penetration, current.as_mut()) {
+                    (Some((horizontal, vertical)), Some(incursion)) => {
+                        incursion.exit_index = i;
+                        incursion.exit = fix.clone();
+                        incursion.max_horizontal_penetration = incursion.max_horizontal_penetration.max(horizontal);
+                        incursion.max_vertical_penetration = incursion.max_vertical_penetration.max(vertical);
+                    }
+                    (Some((horizontal, vertical)), None) => current = Some(Incursion {
+                        airspace: index,
+                        class: airspace.class.clone(),
+                        name: airspace.name.clone(),
+                        entry_index: i,
+                        exit_index: i,
+                        entry: fix.clone(),
+                        exit: fix.clone(),
+                        max_horizontal_penetration: horizontal,
+                        max_vertical_penetration: vertical,
+                    }),
+                    (None, _) => incursions.extend(current.take()),
+                }
+            }
+            incursions.extend(current);
+        }
+        incursions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::altitude::STANDARD_PRESSURE;
+    use crate::records::file_header::PressureAltitudeReference;
+
+    fn square(lower: AltitudeLimit, upper: AltitudeLimit) -> Airspace {
+        Airspace {
+            class: AirspaceClass::C,
+            name: "SQUARE".into(),
+            lower,
+            upper,
+            geometry: Geometry::Polygon(vec![
+                Coordinate::from_decimal_degrees(50., 5.),
+                Coordinate::from_decimal_degrees(51., 5.),
+                Coordinate::from_decimal_degrees(51., 6.),
+                Coordinate::from_decimal_degrees(50., 6.),
+            ]),
+        }
+    }
+
+    #[test]
+    fn horizontal_penetration() {
+        let airspace = square(AltitudeLimit::Surface, AltitudeLimit::Unlimited);
+        let depth = airspace.horizontal_penetration(&Coordinate::from_decimal_degrees(50.1, 5.5)).unwrap();
+        assert!((depth - 11_120.).abs() < 20., "{depth}");
+        assert!(airspace.horizontal_penetration(&Coordinate::from_decimal_degrees(49.9, 5.5)).is_none());
+        assert!(airspace.horizontal_penetration(&Coordinate::from_decimal_degrees(50.5, 6.1)).is_none());
+
+        let circle = Airspace { geometry: Geometry::Circle { center: Coordinate::from_decimal_degrees(50., 5.), radius: 1000. }, ..airspace };
+        assert!((circle.horizontal_penetration(&Coordinate::from_decimal_degrees(50., 5.)).unwrap() - 1000.).abs() < 1e-9);
+        assert!(circle.horizontal_penetration(&Coordinate::from_decimal_degrees(50.01, 5.)).is_none());
+    }
+
+    #[test]
+    fn finds_incursions() {
+        let airspaces = [square(AltitudeLimit::Amsl(1000.), AltitudeLimit::FlightLevel(65))];
+        let fixes = [
+            Fix::at(43200, 49.9, 5.5, 1500),
+            Fix::at(43210, 50.05, 5.5, 1500),
+            Fix::at(43220, 50.5, 5.5, 1800),
+            Fix::at(43230, 50.5, 5.5, 900),
+            Fix::at(43240, 50.5, 5.5, 1200),
+            Fix::at(43250, 51.1, 5.5, 1200),
+        ].map(|fix| Fix { gps_alt: Some(fix.pressure_alt + 150), ..fix });
+        let incursions = AirspaceChecker::default().check(&airspaces, &fixes);
+        assert_eq!(incursions.len(), 2);
+        assert_eq!((incursions[0].entry_index, incursions[0].exit_index), (1, 2));
+        assert_eq!(incursions[0].entry, fixes[1]);
+        assert_eq!(incursions[0].exit, fixes[2]);
+        assert!((incursions[0].max_horizontal_penetration - 35_365.).abs() < 100.);
+        assert!((incursions[0].max_vertical_penetration - (1981.2 - 1500.)).abs() < 1e-9);
+        assert_eq!((incursions[1].entry_index, incursions[1].exit_index), (4, 4));
+        assert_eq!(incursions[1].name.as_ref(), "SQUARE");
+
+        // with a low QNH the fixes at 900m and 1200m pressure altitude are below 1000m AMSL
+        let incursions = AirspaceChecker::new(AltitudeSource::Qnh(980.)).check(&airspaces, &fixes);
+        assert_eq!(incursions.len(), 1);
+        assert_eq!((incursions[0].entry_index, incursions[0].exit_index), (1, 2));
+
+        let incursions = AirspaceChecker::new(AltitudeSource::Gps).check(&airspaces, &fixes);
+        assert_eq!(incursions.len(), 1);
+        assert_eq!((incursions[0].entry_index, incursions[0].exit_index), (1, 4));
+
+        let incursions = AirspaceChecker::new(AltitudeSource::Calibrated(Calibration::new(980.))).check(&airspaces, &fixes);
+        assert_eq!(incursions.len(), 1);
+        assert_eq!((incursions[0].entry_index, incursions[0].exit_index), (1, 2));
+
+        // the upper limit is a flight level, unknown for MSL pressure altitudes without the recorder QNH
+        let msl = Calibration { pressure_reference: PressureAltitudeReference::MSL, ..Calibration::new(980.) };
+        assert!(AirspaceChecker::new(AltitudeSource::Calibrated(msl.clone())).check(&airspaces, &fixes).is_empty());
+        let msl = Calibration { recorder_qnh: Some(STANDARD_PRESSURE), ..msl };
+        assert_eq!(AirspaceChecker::new(AltitudeSource::Calibrated(msl)).check(&airspaces, &fixes).len(), 2);
+    }
+
+    struct Plateau;
+
+    impl Terrain for Plateau {
+        fn elevation(&self, _coordinate: &Coordinate) -> Option<f64> {
+            Some(500.)
+        }
+    }
+
+    #[test]
+    fn above_ground_limits() {
+        let airspaces = [square(AltitudeLimit::Surface, AltitudeLimit::Agl(1000.))];
+        let fixes = [Fix::at(43200, 50.5, 5.5, 1200)];
+        assert!(AirspaceChecker::default().check(&airspaces, &fixes).is_empty());
+        let incursions = AirspaceChecker::default().check_with_terrain(&airspaces, &fixes, Some(&Plateau));
+        assert_eq!(incursions.len(), 1);
+        assert!((incursions[0].max_vertical_penetration - 300.).abs() < 1e-9);
+    }
+}
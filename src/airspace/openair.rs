@@ -0,0 +1,304 @@
+use crate::airspace::{Airspace, AirspaceClass, AltitudeLimit, Geometry, FEET, NAUTICAL_MILE};
+use crate::error::IGCError::AirspaceInitError;
+use crate::records::util::Coordinate;
+use crate::{Result, StrWrapper};
+
+/// Parses an OpenAir airspace file, each airspace is parsed independently so one invalid airspace does not stop the others
+///
+/// Supported commands are `AC`, `AN`, `AL`, `AH`, `DP`, `DC`, `DA`, `DB` and the `X` and `D` variables, other commands are ignored
+/// # examples
+/// ```rust
+/// use igc_parser::airspace::{openair, AltitudeLimit, Geometry};
+/// let content = "AC D\nAN CTR EXAMPLE\nAL SFC\nAH 2500ft AMSL\nV X=52:00:00 N 005:00:00 E\nDC 5";
+/// let airspaces = openair::parse(content);
+/// let airspace = airspaces[0].as_ref().unwrap();
+/// assert_eq!(airspace.name.as_ref(), "CTR EXAMPLE");
+/// assert_eq!(airspace.lower, AltitudeLimit::Surface);
+/// assert!(matches!(airspace.geometry, Geometry::Circle { .. }));
+/// ```
+pub fn parse(content: &str) -> Vec<Result<Airspace>> {
+    let mut airspaces = Vec::new();
+    let mut builder: Option<Builder> = None;
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('*') { continue }
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command.to_ascii_uppercase(), argument.trim()),
+            None => (line.to_ascii_uppercase(), ""),
+        };
+        if command == "AC" {
+            airspaces.extend(builder.take().map(Builder::build));
+            builder = Some(Builder::new(number + 1, argument));
+            continue
+        }
+        let Some(current) = builder.as_mut() else { continue };
+        if current.error.is_some() { continue }
+        if let Err(error) = current.apply(&command, argument) {
+            current.error = Some(AirspaceInitError(format!("line {}: {error}", number + 1)));
+        }
+    }
+    airspaces.extend(builder.map(Builder::build));
+    airspaces
+}
+
+struct Builder {
+    line: usize,
+    class: AirspaceClass,
+    name: Option<String>,
+    lower: Option<AltitudeLimit>,
+    upper: Option<AltitudeLimit>,
+    center: Option<Coordinate>,
+    clockwise: bool,
+    points: Vec<Coordinate>,
+    circle: Option<(Coordinate, f64)>,
+    error: Option<crate::error::IGCError>,
+}
+
+impl Builder {
+    fn new(line: usize, class: &str) -> Self {
+        Self {
+            line,
+            class: AirspaceClass::parse(class),
+            name: None,
+            lower: None,
+            upper: None,
+            center: None,
+            clockwise: true,
+            points: Vec::new(),
+            circle: None,
+            error: None,
+        }
+    }
+
+    fn apply(&mut self, command: &str, argument: &str) -> core::result::Result<(), String> {
+        match command {
+            "AN" => self.name = Some(argument.to_string()),
+            "AL" => self.lower = Some(parse_altitude(argument)?),
+            "AH" => self.upper = Some(parse_altitude(argument)?),
+            "V" => match argument.split_once('=').map(|(variable, value)| (variable.trim().to_ascii_uppercase(), value.trim())) {
+                Some((variable, value)) if variable == "X" => self.center = Some(parse_coordinate(value)?),
+                Some((variable, value)) if variable == "D" => self.clockwise = match value {
+                    "+" => true,
+                    "-" => false,
+                    _ => return Err(format!("'{value}' is not a valid arc direction")),
+                },
+                _ => {}
+            },
+            "DP" => self.points.push(parse_coordinate(argument)?),
+            "DC" => self.circle = Some((self.center()?, parse_number(argument)? * NAUTICAL_MILE)),
+            "DA" => {
+                let values = argument.split(',').map(parse_number).collect::<core::result::Result<Vec<_>, _>>()?;
+                let [radius, start, end] = values[..] else { return Err(format!("'{argument}' is not a radius, start and end angle")) };
+                let center = self.center()?;
+                self.arc(&center, radius * NAUTICAL_MILE, start, end);
+            }
+            "DB" => {
+                let Some((from, to)) = argument.split_once(',') else { return Err(format!("'{argument}' is not two coordinates")) };
+                let (center, from, to) = (self.center()?, parse_coordinate(from)?, parse_coordinate(to)?);
+                self.arc(&center, center.distance_to(&from), center.bearing_to(&from), center.bearing_to(&to));
+                self.points.push(to);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn center(&self) -> core::result::Result<Coordinate, String> {
+        self.center.clone().ok_or_else(|| "arc or circle without a center set by 'V X='".to_string())
+    }
+
+    /// Adds points every degree along an arc, including the start but not the end
+    fn arc(&mut self, center: &Coordinate, radius: f64, start: f64, end: f64) {
+        let sweep = if self.clockwise { (end - start).rem_euclid(360.) } else { -(start - end).rem_euclid(360.) };
+        let steps = sweep.abs().ceil().max(1.) as usize;
+        for step in 0..steps {
+            self.points.push(center.destination(start + sweep * step as f64 / steps as f64, radius));
+        }
+    }
+
+    fn build(self) -> Result<Airspace> {
+        if let Some(error) = self.error { return Err(error) }
+        let Self { line, class, name, lower, upper, points, circle, .. } = self;
+        let name = name.unwrap_or_default();
+        let (Some(lower), Some(upper)) = (lower, upper) else {
+            return Err(AirspaceInitError(format!("airspace '{name}' at line {line} is missing its lower or upper limit")))
+        };
+        let geometry = match circle {
+            Some((center, radius)) if points.is_empty() => Geometry::Circle { center, radius },
+            _ if points.len() >= 3 => Geometry::Polygon(points),
+            _ => return Err(AirspaceInitError(format!("airspace '{name}' at line {line} has no circle or polygon with at least three points"))),
+        };
+        Ok(Airspace { class, name: StrWrapper::from(name), lower, upper, geometry })
+    }
+}
+
+fn parse_number(value: &str) -> core::result::Result<f64, String> {
+    value.trim().parse::<f64>().map_err(|_| format!("'{}' is not a number", value.trim()))
+}
+
+/// Parses limits such as `SFC`, `GND`, `FL65`, `3500ft AMSL`, `3500 MSL`, `1000ft AGL`, `1500m` and `UNL`
+fn parse_altitude(value: &str) -> core::result::Result<AltitudeLimit, String> {
+    let upper = value.trim().to_ascii_uppercase().replace(' ', "");
+    if upper == "SFC" || upper == "GND" { return Ok(AltitudeLimit::Surface) }
+    if upper.starts_with("UNL") { return Ok(AltitudeLimit::Unlimited) }
+    if let Some(level) = upper.strip_prefix("FL") {
+        return level.parse::<u16>().map(AltitudeLimit::FlightLevel).map_err(|_| format!("'{value}' is not a valid flight level"))
+    }
+
+    let split = upper.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(upper.len());
+    let (number, unit) = upper.split_at(split);
+    let number = number.parse::<f64>().map_err(|_| format!("'{value}' is not a valid altitude"))?;
+    let (meters, reference) = match unit.strip_prefix("FT").or_else(|| unit.strip_prefix('F')) {
+        Some(reference) => (number * FEET, reference),
+        None => match unit.strip_prefix('M').filter(|_| !unit.starts_with("MSL")) {
+            Some(reference) => (number, reference),
+            None => (number * FEET, unit),
+        },
+    };
+    match reference {
+        "STD" => Ok(AltitudeLimit::FlightLevel((number / 100.).round() as u16)),
+        "AGL" | "GND" | "SFC" | "ASFC" if number == 0. => Ok(AltitudeLimit::Surface),
+        "AGL" | "GND" | "SFC" | "ASFC" => Ok(AltitudeLimit::Agl(meters)),
+        "" if number == 0. => Ok(AltitudeLimit::Surface),
+        "" | "AMSL" | "MSL" | "ALT" => Ok(AltitudeLimit::Amsl(meters)),
+        _ => Err(format!("'{value}' has an unknown altitude reference")),
+    }
+}
+
+/// Parses coordinates such as `53:24:00 N 010:20:00 E` or `53:24.5N 10:20.3E`
+fn parse_coordinate(value: &str) -> core::result::Result<Coordinate, String> {
+    let upper = value.trim().to_ascii_uppercase();
+    let Some(latitude_end) = upper.find(['N', 'S']) else { return Err(format!("'{value}' has no latitude hemisphere")) };
+    let Some(longitude_end) = upper[latitude_end + 1..].find(['E', 'W']).map(|i| i + latitude_end + 1) else {
+        return Err(format!("'{value}' has no longitude hemisphere"))
+    };
+    let latitude = parse_degrees(&upper[..latitude_end]).filter(|degrees| *degrees <= 90.);
+    let longitude = parse_degrees(&upper[latitude_end + 1..longitude_end]).filter(|degrees| *degrees <= 180.);
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => {
+            let latitude = if &upper[latitude_end..=latitude_end] == "N" { latitude } else { -latitude };
+            let longitude = if &upper[longitude_end..=longitude_end] == "E" { longitude } else { -longitude };
+            Ok(Coordinate::from_decimal_degrees(latitude, longitude))
+        }
+        _ => Err(format!("'{value}' is not a valid coordinate")),
+    }
+}
+
+/// Degrees from `D`, `D:M` or `D:M:S` where the last part may have decimals
+fn parse_degrees(value: &str) -> Option<f64> {
+    let parts = value.trim().split(':').map(|part| part.trim().parse::<f64>().ok()).collect::<Option<Vec<_>>>()?;
+    if parts.is_empty() || parts.len() > 3 || parts[1..].iter().any(|part| *part >= 60.) { return None }
+    Some(parts.iter().zip([1., 60., 3600.]).map(|(part, divisor)| part / divisor).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+* example airspace file
+AC R
+AN EHR 1 ROTTERDAM
+AL GND
+AH FL 95
+DP 52:00:00 N 004:00:00 E
+DP 52:10:00 N 004:00:00 E
+DP 52:10:00 N 004:20:00 E
+DP 52:00:00 N 004:20:00 E
+
+AC CTR
+AN LOGGING CTR
+AL SFC
+AH 3000ft AMSL
+V X=51:30:00 N 005:00:00 E
+DC 8
+
+AC C
+AN TMA WITH ARCS
+AL 1500ft MSL
+AH 4500ft MSL
+V X=51:00:00 N 006:00:00 E
+V D=-
+DA 10,180,0
+DB 51:10:00 N 006:00:00 E, 50:50:00 N 006:00:00 E
+
+AC Q
+AN BROKEN
+AL SFC
+AH 2000ft AMSL
+DP 50:00:00 N 00X:00:00 E
+
+AC GP
+AN AFTER BROKEN
+AL 1000 AGL
+AH UNL
+V X=50:00:00 N 007:00:00 E
+DC 1.5
+";
+
+    #[test]
+    fn parses_example_file() {
+        let airspaces = parse(EXAMPLE);
+        assert_eq!(airspaces.len(), 5);
+
+        let restricted = airspaces[0].as_ref().unwrap();
+        assert_eq!(restricted.class, AirspaceClass::Restricted);
+        assert_eq!(restricted.name.as_ref(), "EHR 1 ROTTERDAM");
+        assert_eq!((restricted.lower.clone(), restricted.upper.clone()), (AltitudeLimit::Surface, AltitudeLimit::FlightLevel(95)));
+        assert!(matches!(&restricted.geometry, Geometry::Polygon(points) if points.len() == 4));
+
+        let ctr = airspaces[1].as_ref().unwrap();
+        assert_eq!(ctr.class, AirspaceClass::ControlZone);
+        match &ctr.geometry {
+            Geometry::Circle { center, radius } => {
+                assert_eq!(center, &Coordinate::from_decimal_degrees(51.5, 5.));
+                assert!((radius - 8. * NAUTICAL_MILE).abs() < 1e-9);
+            }
+            geometry => panic!("{geometry:?}"),
+        }
+
+        let tma = airspaces[2].as_ref().unwrap();
+        let Geometry::Polygon(points) = &tma.geometry else { panic!() };
+        // counter clockwise from south to north through the east, then back to the south through the west including the end point
+        assert_eq!(points.len(), 361);
+        let center = Coordinate::from_decimal_degrees(51., 6.);
+        assert!((center.bearing_to(&points[90]) - 90.).abs() < 0.5);
+        assert!((center.distance_to(&points[90]) - 10. * NAUTICAL_MILE).abs() < 10.);
+        assert!((center.bearing_to(&points[270]) - 270.).abs() < 0.5);
+
+        assert!(airspaces[3].as_ref().unwrap_err().to_string().contains("line 31"));
+
+        let glider_prohibited = airspaces[4].as_ref().unwrap();
+        assert_eq!(glider_prohibited.class, AirspaceClass::GliderProhibited);
+        assert_eq!(glider_prohibited.upper, AltitudeLimit::Unlimited);
+        assert!(matches!(glider_prohibited.lower, AltitudeLimit::Agl(meters) if (meters - 304.8).abs() < 1e-9));
+    }
+
+    #[test]
+    fn altitudes() {
+        assert_eq!(parse_altitude("SFC"), Ok(AltitudeLimit::Surface));
+        assert_eq!(parse_altitude("0"), Ok(AltitudeLimit::Surface));
+        assert_eq!(parse_altitude("FL65"), Ok(AltitudeLimit::FlightLevel(65)));
+        assert_eq!(parse_altitude("fl 100"), Ok(AltitudeLimit::FlightLevel(100)));
+        assert_eq!(parse_altitude("6500ft STD"), Ok(AltitudeLimit::FlightLevel(65)));
+        assert_eq!(parse_altitude("1000ft"), Ok(AltitudeLimit::Amsl(304.8)));
+        assert_eq!(parse_altitude("1000 MSL"), Ok(AltitudeLimit::Amsl(304.8)));
+        assert_eq!(parse_altitude("1000m AMSL"), Ok(AltitudeLimit::Amsl(1000.)));
+        assert_eq!(parse_altitude("500 m"), Ok(AltitudeLimit::Amsl(500.)));
+        assert_eq!(parse_altitude("1000 ft AGL"), Ok(AltitudeLimit::Agl(304.8)));
+        assert_eq!(parse_altitude("0 AGL"), Ok(AltitudeLimit::Surface));
+        assert_eq!(parse_altitude("UNLIMITED"), Ok(AltitudeLimit::Unlimited));
+        assert!(parse_altitude("FLX").is_err());
+        assert!(parse_altitude("1000 QFE").is_err());
+        assert!(parse_altitude("HIGH").is_err());
+    }
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(parse_coordinate("52:30:00 N 004:15:00 E"), Ok(Coordinate::from_decimal_degrees(52.5, 4.25)));
+        assert_eq!(parse_coordinate("52:30.0S 4:15.0W"), Ok(Coordinate::from_decimal_degrees(-52.5, -4.25)));
+        assert!(parse_coordinate("52:30:00 004:15:00 E").is_err());
+        assert!(parse_coordinate("52:60:00 N 004:15:00 E").is_err());
+        assert!(parse_coordinate("95:00:00 N 004:15:00 E").is_err());
+    }
+}
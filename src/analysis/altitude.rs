@@ -0,0 +1,382 @@
+use crate::airspace::FEET;
+use crate::analysis::{ground_speed, seconds_since, GROUND_SPEED};
+use crate::igc_file::IGCFile;
+use crate::records::file_header::{FileHeader, GPSAltitudeReference, PressureAltitudeReference};
+use crate::records::fix::Fix;
+use crate::records::util::Time;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Pressure in hPa at sea level in the standard atmosphere
+pub const STANDARD_PRESSURE: f64 = 1013.25;
+/// Exponent and height scale of the barometric formula for the standard atmosphere troposphere
+const EXPONENT: f64 = 0.190263;
+const SCALE_HEIGHT: f64 = 44330.77;
+
+/// Static pressure in hPa at a pressure altitude in meters
+pub fn pressure_at(pressure_altitude: f64) -> f64 {
+    STANDARD_PRESSURE * (1. - pressure_altitude / SCALE_HEIGHT).powf(EXPONENT.recip())
+}
+
+/// Altitude in meters an altimeter set to `setting` hPa shows at a static pressure in hPa
+pub fn altitude_at(pressure: f64, setting: f64) -> f64 {
+    SCALE_HEIGHT * (1. - (pressure / setting).powf(EXPONENT))
+}
+
+/// Pressure altitude converted to the altitude shown with the altimeter set to `qnh` hPa
+/// # examples
+/// ```rust
+/// use igc_parser::analysis::altitude::{pressure_to_qnh_altitude, qnh_to_pressure_altitude};
+/// let altitude = pressure_to_qnh_altitude(1000., 1023.);
+/// assert!((altitude - 1079.).abs() < 1.);
+/// assert!((qnh_to_pressure_altitude(altitude, 1023.) - 1000.).abs() < 1e-6);
+/// ```
+pub fn pressure_to_qnh_altitude(pressure_altitude: f64, qnh: f64) -> f64 {
+    altitude_at(pressure_at(pressure_altitude), qnh)
+}
+
+/// Altitude shown with the altimeter set to `qnh` hPa converted to pressure altitude
+pub fn qnh_to_pressure_altitude(altitude: f64, qnh: f64) -> f64 {
+    let pressure = qnh * (1. - altitude / SCALE_HEIGHT).powf(EXPONENT.recip());
+    altitude_at(pressure, STANDARD_PRESSURE)
+}
+
+/// Flight level, hundreds of feet of pressure altitude
+pub fn flight_level(pressure_altitude: f64) -> f64 {
+    pressure_altitude / FEET / 100.
+}
+
+/// QNH in hPa for a static pressure in hPa measured at `elevation` meters
+pub fn qnh_from_pressure(pressure: f64, elevation: f64) -> f64 {
+    pressure / (1. - elevation / SCALE_HEIGHT).powf(EXPONENT.recip())
+}
+
+/// QFE in hPa, the pressure at a field `elevation` meters above sea level for a given QNH
+pub fn qfe_from_qnh(qnh: f64, elevation: f64) -> f64 {
+    qnh * (1. - elevation / SCALE_HEIGHT).powf(EXPONENT.recip())
+}
+
+/// Where the elevation of the field used for a QNH estimate came from
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ElevationSource {
+    /// Given by the caller
+    Field,
+    /// Mean GPS altitude of the on-ground fixes
+    Gps,
+}
+
+/// QNH estimated from the fixes before take off, or after landing if the recording starts in the air
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct QnhEstimate {
+    pub qnh: f64,
+    pub qfe: f64,
+    pub field_elevation: f64,
+    pub elevation_source: ElevationSource,
+    /// Number of on-ground fixes used
+    pub samples: usize,
+}
+
+/// Number of fixes before the first one moving faster than `GROUND_SPEED`, in either direction of time
+fn leading_ground_fixes<'a>(fixes: impl Iterator<Item = &'a Fix> + Clone) -> usize {
+    let mut count = 1;
+    for (from, to) in fixes.clone().zip(fixes.skip(1)) {
+        if ground_speed(from, to) > GROUND_SPEED { break }
+        count += 1;
+    }
+    count
+}
+
+/// Estimates QNH from the on-ground fixes, using `field_elevation` if known and the GPS altitude otherwise
+///
+/// At least three on-ground fixes are needed, and valid GPS altitudes when no field elevation is given.
+/// The GPS altitudes are taken to be above the geoid, see `Calibration::from_igc_file` for files with `HFALGALTGPS:ELL`
+pub fn estimate_qnh(fixes: &[Fix], field_elevation: Option<f64>) -> Option<QnhEstimate> {
+    estimate_qnh_with(fixes, field_elevation, |fix| fix.gps_alt.map(f64::from))
+}
+
+/// `estimate_qnh` with the altitude above mean sea level of the on-ground fixes given by `gps_altitude`
+fn estimate_qnh_with(fixes: &[Fix], field_elevation: Option<f64>, gps_altitude: impl Fn(&Fix) -> Option<f64>) -> Option<QnhEstimate> {
+    if fixes.len() < 3 { return None }
+    let leading = leading_ground_fixes(fixes.iter());
+    let trailing = leading_ground_fixes(fixes.iter().rev());
+    let ground = if leading >= 3 { &fixes[..leading] } else if trailing >= 3 { &fixes[fixes.len() - trailing..] } else { return None };
+
+    let (field_elevation, elevation_source) = match field_elevation {
+        Some(elevation) => (elevation, ElevationSource::Field),
+        None => {
+            let gps = ground.iter().filter_map(gps_altitude).collect::<Vec<_>>();
+            if gps.is_empty() { return None }
+            (gps.iter().sum::<f64>() / gps.len() as f64, ElevationSource::Gps)
+        }
+    };
+    let pressure_altitude = ground.iter().map(|fix| fix.pressure_alt as f64).sum::<f64>() / ground.len() as f64;
+    let qnh = qnh_from_pressure(pressure_at(pressure_altitude), field_elevation);
+    Some(QnhEstimate { qnh, qfe: qfe_from_qnh(qnh, field_elevation), field_elevation, elevation_source, samples: ground.len() })
+}
+
+/// What is known about a flight apart from its file, for `Calibration::from_igc_file`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CalibrationInput {
+    /// Elevation of the field in meters, the GPS altitude on the ground is used when `None`
+    pub field_elevation: Option<f64>,
+    /// Height of the geoid above the WGS84 ellipsoid in meters, needed for files with `HFALGALTGPS:ELL`
+    pub geoid_separation: Option<f64>,
+    /// QNH in hPa the recorder was set to, needed for files with `HFALPALTPRESSURE:MSL`
+    pub recorder_qnh: Option<f64>,
+}
+
+/// Conversions of the altitudes of fixes honouring the altitude references of the file
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::altitude::{Calibration, CalibrationInput};
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let calibration = Calibration::from_igc_file(&file, &CalibrationInput { field_elevation: Some(140.), ..CalibrationInput::default() });
+/// if let Some(Ok(fix)) = file.get_fixes().first() {
+///     println!("{:.0}m QNH, FL{:.0}", calibration.qnh_altitude(fix), calibration.flight_level(fix).unwrap());
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    /// QNH in hPa, used for the altitude above mean sea level of pressure altitudes referenced to `ISA`
+    pub qnh: f64,
+    /// QNH in hPa the recorder was set to for pressure altitudes referenced to `MSL`, without it their flight level is unknown
+    pub recorder_qnh: Option<f64>,
+    /// Elevation of the field in meters for heights above the field
+    pub field_elevation: Option<f64>,
+    pub pressure_reference: PressureAltitudeReference,
+    pub gps_reference: GPSAltitudeReference,
+    /// Height of the geoid above the WGS84 ellipsoid in meters, subtracted from GPS altitudes referenced to the ellipsoid
+    pub geoid_separation: f64,
+}
+
+impl Calibration {
+    /// Calibration for ISA pressure altitudes and geoid GPS altitudes
+    pub fn new(qnh: f64) -> Self {
+        Self {
+            qnh,
+            recorder_qnh: None,
+            field_elevation: None,
+            pressure_reference: PressureAltitudeReference::ISA,
+            gps_reference: GPSAltitudeReference::Geoid,
+            geoid_separation: 0.,
+        }
+    }
+
+    /// Calibration from the `HFALG` and `HFALP` headers and a QNH estimated from the on-ground fixes
+    ///
+    /// Pressure altitudes referenced to `MSL` are already corrected, so no QNH is estimated and `qnh` is the recorder setting if given.
+    /// GPS altitudes referenced to the ellipsoid are only used for the estimate when the geoid separation is given.
+    /// The standard setting is used when no QNH can be estimated
+    pub fn from_igc_file(igc_file: &IGCFile, input: &CalibrationInput) -> Self {
+        let mut calibration = Self::new(STANDARD_PRESSURE);
+        calibration.recorder_qnh = input.recorder_qnh;
+        calibration.geoid_separation = input.geoid_separation.unwrap_or(0.);
+        for header in igc_file.get_file_header_vec().iter().filter_map(|header| header.as_ref().ok()) {
+            match header.header {
+                FileHeader::PressureAltitudeReference(reference) => calibration.pressure_reference = reference,
+                FileHeader::GPSAltitudeReference(reference) => calibration.gps_reference = reference,
+                _ => {}
+            }
+        }
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let gps_known = calibration.gps_reference == GPSAltitudeReference::Geoid || input.geoid_separation.is_some();
+        let estimate = match calibration.pressure_reference {
+            PressureAltitudeReference::ISA => estimate_qnh_with(&fixes, input.field_elevation, |fix| calibration.gps_altitude(fix).filter(|_| gps_known)),
+            PressureAltitudeReference::MSL => None,
+        };
+        calibration.field_elevation = estimate.as_ref().map(|estimate| estimate.field_elevation).or(input.field_elevation);
+        calibration.qnh = match calibration.pressure_reference {
+            PressureAltitudeReference::ISA => estimate.map_or(STANDARD_PRESSURE, |estimate| estimate.qnh),
+            PressureAltitudeReference::MSL => input.recorder_qnh.unwrap_or(STANDARD_PRESSURE),
+        };
+        calibration
+    }
+
+    /// Pressure altitude of the fix relative to the standard setting, `None` for `MSL` altitudes without the recorder QNH
+    pub fn pressure_altitude(&self, fix: &Fix) -> Option<f64> {
+        match self.pressure_reference {
+            PressureAltitudeReference::ISA => Some(fix.pressure_alt as f64),
+            PressureAltitudeReference::MSL => Some(qnh_to_pressure_altitude(fix.pressure_alt as f64, self.recorder_qnh?)),
+        }
+    }
+
+    /// Altitude of the fix above mean sea level from the pressure altitude
+    pub fn qnh_altitude(&self, fix: &Fix) -> f64 {
+        match self.pressure_reference {
+            PressureAltitudeReference::ISA => pressure_to_qnh_altitude(fix.pressure_alt as f64, self.qnh),
+            PressureAltitudeReference::MSL => fix.pressure_alt as f64,
+        }
+    }
+
+    /// Flight level of the fix, `None` for `MSL` altitudes without the recorder QNH
+    pub fn flight_level(&self, fix: &Fix) -> Option<f64> {
+        self.pressure_altitude(fix).map(flight_level)
+    }
+
+    /// Height of the fix above the field from the pressure altitude, `None` if the field elevation is unknown
+    pub fn height_above_field(&self, fix: &Fix) -> Option<f64> {
+        Some(self.qnh_altitude(fix) - self.field_elevation?)
+    }
+
+    /// GPS altitude of the fix above mean sea level, `None` if the fix has no valid GPS altitude
+    pub fn gps_altitude(&self, fix: &Fix) -> Option<f64> {
+        let altitude = fix.gps_alt? as f64;
+        Some(match self.gps_reference {
+            GPSAltitudeReference::Geoid => altitude,
+            GPSAltitudeReference::Ellipsoid => altitude - self.geoid_separation,
+        })
+    }
+
+    /// Compares the GPS altitude with the QNH altitude over the flight
+    ///
+    /// A steady trend in the difference points to a drifting pressure sensor or a QNH change during the flight
+    pub fn drift(&self, fixes: &[Fix]) -> Option<AltitudeDrift> {
+        let start = &fixes.first()?.timestamp;
+        let samples = fixes
+            .iter()
+            .filter_map(|fix| Some((seconds_since(start, &fix.timestamp) as f64, self.gps_altitude(fix)? - self.qnh_altitude(fix))))
+            .collect::<Vec<_>>();
+        if samples.len() < 2 { return None }
+
+        let n = samples.len() as f64;
+        let mean_time = samples.iter().map(|(time, _)| time).sum::<f64>() / n;
+        let mean_offset = samples.iter().map(|(_, offset)| offset).sum::<f64>() / n;
+        let covariance = samples.iter().map(|(time, offset)| (time - mean_time) * (offset - mean_offset)).sum::<f64>();
+        let variance = samples.iter().map(|(time, _)| (time - mean_time).powi(2)).sum::<f64>();
+        let slope = if variance > 0. { covariance / variance } else { 0. };
+        let fitted = |time: f64| mean_offset + slope * (time - mean_time);
+        let max_deviation = samples.iter().map(|(time, offset)| (offset - fitted(*time)).abs()).fold(0., f64::max);
+        let (first, last) = (samples[0].0, samples[samples.len() - 1].0);
+        Some(AltitudeDrift {
+            start: start.clone(),
+            mean_offset,
+            start_offset: fitted(first),
+            end_offset: fitted(last),
+            drift_per_hour: slope * 3600.,
+            max_deviation,
+            samples: samples.len(),
+        })
+    }
+}
+
+/// Trend of the GPS altitude minus the QNH altitude, fitted by least squares
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AltitudeDrift {
+    pub start: Time,
+    pub mean_offset: f64,
+    /// Fitted offset in meters at the first and last fix with a valid GPS altitude
+    pub start_offset: f64,
+    pub end_offset: f64,
+    pub drift_per_hour: f64,
+    /// Largest distance in meters of a sample from the fitted trend
+    pub max_deviation: f64,
+    pub samples: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert!((pressure_at(0.) - STANDARD_PRESSURE).abs() < 1e-9);
+        assert!((pressure_at(5574.) - 500.).abs() < 0.5);
+        assert!((pressure_to_qnh_altitude(1000., STANDARD_PRESSURE) - 1000.).abs() < 1e-6);
+        assert!((pressure_to_qnh_altitude(0., 1023.25) - 83.).abs() < 1.);
+        assert!((pressure_to_qnh_altitude(1000., 1003.25) - 918.).abs() < 1.);
+        assert!((flight_level(1981.2) - 65.).abs() < 1e-9);
+        let qnh = qnh_from_pressure(pressure_at(100.), 200.);
+        assert!((pressure_to_qnh_altitude(100., qnh) - 200.).abs() < 1e-6);
+        assert!((qfe_from_qnh(qnh, 200.) - pressure_at(100.)).abs() < 1e-9);
+    }
+
+    /// Ten stationary fixes at a field, then climbing away towards the east
+    fn flight(pressure_offset: i16) -> Vec<Fix> {
+        let mut fixes = (0..10).map(|t| Fix { gps_alt: Some(200), ..Fix::at(43200 + t * 4, 50., 5., 100 + pressure_offset) }).collect::<Vec<_>>();
+        fixes.extend((1..20).map(|t| Fix {
+            gps_alt: Some(200 + t as i16 * 10),
+            ..Fix::at(43240 + t * 4, 50., 5. + t as f64 * 0.002, 100 + pressure_offset + t as i16 * 10)
+        }));
+        fixes
+    }
+
+    #[test]
+    fn qnh_from_ground_fixes() {
+        let estimate = estimate_qnh(&flight(0), None).unwrap();
+        assert_eq!((estimate.samples, estimate.elevation_source), (10, ElevationSource::Gps));
+        assert_eq!(estimate.field_elevation, 200.);
+        assert!((pressure_to_qnh_altitude(100., estimate.qnh) - 200.).abs() < 1e-6);
+        assert!((estimate.qnh - 1025.3).abs() < 0.1, "{}", estimate.qnh);
+
+        let estimate = estimate_qnh(&flight(0), Some(150.)).unwrap();
+        assert_eq!(estimate.elevation_source, ElevationSource::Field);
+        assert!((pressure_to_qnh_altitude(100., estimate.qnh) - 150.).abs() < 1e-6);
+
+        assert!(estimate_qnh(&flight(0)[8..], None).is_none());
+    }
+
+    #[test]
+    fn calibration_honours_headers() {
+        let content = |pressure_reference: &str| {
+            let mut lines = vec!["AXXXABC".to_string(), "HFALGALTGPS:ELL".to_string(), format!("HFALPALTPRESSURE:{pressure_reference}")];
+            lines.extend(flight(0).iter().map(Fix::to_string));
+            IGCFile::parse(&lines.join("\n")).unwrap()
+        };
+
+        let field = CalibrationInput { field_elevation: Some(200.), ..CalibrationInput::default() };
+        let calibration = Calibration::from_igc_file(&content("ISA"), &field);
+        assert_eq!(calibration.gps_reference, GPSAltitudeReference::Ellipsoid);
+        assert!((calibration.qnh - 1025.3).abs() < 0.1);
+        let fix = Fix { gps_alt: Some(1150), ..Fix::at(43200, 50., 5., 1000) };
+        assert_eq!(calibration.pressure_altitude(&fix), Some(1000.));
+        assert!((calibration.qnh_altitude(&fix) - 1098.).abs() < 1.);
+        assert!((calibration.height_above_field(&fix).unwrap() - 898.).abs() < 1.);
+        assert_eq!(calibration.gps_altitude(&fix), Some(1150.));
+        let calibration = Calibration { geoid_separation: 47., ..calibration };
+        assert_eq!(calibration.gps_altitude(&fix), Some(1103.));
+
+        let calibration = Calibration::from_igc_file(&content("MSL"), &field);
+        assert_eq!(calibration.pressure_reference, PressureAltitudeReference::MSL);
+        assert_eq!(calibration.qnh_altitude(&fix), 1000.);
+        assert_eq!(calibration.flight_level(&fix), None);
+        let calibration = Calibration::from_igc_file(&content("MSL"), &CalibrationInput { recorder_qnh: Some(STANDARD_PRESSURE), ..field });
+        assert!((calibration.flight_level(&fix).unwrap() - 32.8).abs() < 0.1);
+        let calibration = Calibration::from_igc_file(&content("MSL"), &CalibrationInput { recorder_qnh: Some(1030.), ..field });
+        assert!(calibration.flight_level(&fix).unwrap() < 32.8);
+    }
+
+    #[test]
+    fn calibration_estimates_from_ellipsoid_gps_altitudes() {
+        // the GPS altitudes of `flight` are 47m above the geoid when referenced to the ellipsoid, the field is at 200m
+        let mut lines = vec!["AXXXABC".to_string(), "HFALGALTGPS:ELL".to_string(), "HFALPALTPRESSURE:ISA".to_string()];
+        lines.extend(flight(0).into_iter().map(|fix| Fix { gps_alt: fix.gps_alt.map(|altitude| altitude + 47), ..fix }.to_string()));
+        let igc_file = IGCFile::parse(&lines.join("\n")).unwrap();
+
+        let calibration = Calibration::from_igc_file(&igc_file, &CalibrationInput { geoid_separation: Some(47.), ..CalibrationInput::default() });
+        assert_eq!(calibration.field_elevation, Some(200.));
+        assert!((calibration.qnh - 1025.3).abs() < 0.1, "{}", calibration.qnh);
+
+        // without the separation the ellipsoid heights are not used and no QNH can be estimated
+        let calibration = Calibration::from_igc_file(&igc_file, &CalibrationInput::default());
+        assert_eq!((calibration.field_elevation, calibration.qnh), (None, STANDARD_PRESSURE));
+    }
+
+    #[test]
+    fn drift() {
+        // the pressure altitude falls behind the GPS altitude by 1m every 10 seconds
+        let fixes = (0..100).map(|t| Fix { gps_alt: Some(1000), ..Fix::at(43200 + t * 10, 50., 5., 1000 - t as i16) }).collect::<Vec<_>>();
+        let drift = Calibration::new(STANDARD_PRESSURE).drift(&fixes).unwrap();
+        assert!((drift.drift_per_hour - 360.).abs() < 1e-6);
+        assert!(drift.start_offset.abs() < 1e-6);
+        assert!((drift.end_offset - 99.).abs() < 1e-6);
+        assert!(drift.max_deviation < 1e-6);
+        assert_eq!(drift.samples, 100);
+    }
+}
@@ -0,0 +1,440 @@
+use crate::analysis::{ground_speed, seconds_since, GROUND_SPEED};
+use crate::igc_file::IGCFile;
+use crate::metadata::FlightMetadata;
+use crate::records::fix::Fix;
+use crate::records::task_info::{TaskInfo, TaskPoint};
+use crate::records::util::{Date, Time};
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Badge {
+    Silver,
+    Gold,
+    Diamond,
+}
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BadgeLeg {
+    /// Meters of distance after the 1% rule
+    Distance,
+    /// Seconds from takeoff to landing
+    Duration,
+    /// Meters climbed from a low point to a later high point
+    HeightGain,
+    /// Meters of a completed declared task
+    Goal,
+}
+
+/// A fix used as evidence for a claim
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CitedFix {
+    /// Index in the evaluated fixes
+    pub index: usize,
+    pub time: Time,
+    pub pressure_alt: i16,
+}
+
+impl CitedFix {
+    fn new(fixes: &[Fix], index: usize) -> Self {
+        Self { index, time: fixes[index].timestamp.clone(), pressure_alt: fixes[index].pressure_alt }
+    }
+}
+
+/// Pass or fail of one badge leg
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgeCheck {
+    pub badge: Badge,
+    pub leg: BadgeLeg,
+    pub required: f64,
+    pub achieved: f64,
+    pub passed: bool,
+    pub fixes: Vec<CitedFix>,
+}
+
+/// Largest climb from a low point to a later high point
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeightGain {
+    pub low: CitedFix,
+    pub high: CitedFix,
+    pub gain: f64,
+}
+
+/// A distance between a start and a finish fix with the 1% rule applied
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceClaim {
+    pub start: CitedFix,
+    pub finish: CitedFix,
+    /// Meters along the course
+    pub distance: f64,
+    /// Pressure altitude lost from the start to the finish
+    pub height_loss: f64,
+    /// The distance reduced by 100 times the height loss exceeding 1% of the distance
+    pub claimed_distance: f64,
+}
+
+impl DistanceClaim {
+    fn new(fixes: &[Fix], start: usize, finish: usize, distance: f64) -> Self {
+        let height_loss = fixes[start].pressure_alt as f64 - fixes[finish].pressure_alt as f64;
+        let claimed_distance = (distance - 100. * (height_loss - distance / 100.).max(0.)).max(0.);
+        Self { start: CitedFix::new(fixes, start), finish: CitedFix::new(fixes, finish), distance, height_loss, claimed_distance }
+    }
+}
+
+/// The task declared in the C records, takeoff and landing points are not used for scoring
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub date: Date,
+    pub time: Time,
+    pub start: TaskPoint,
+    pub turn_points: Vec<TaskPoint>,
+    pub finish: TaskPoint,
+}
+
+impl Declaration {
+    /// The declaration from C records ordered as in the file, the declaration time followed by the takeoff, start, turn points, finish and landing
+    pub fn from_task_info(task_info: &[TaskInfo]) -> Option<Self> {
+        let (date, time) = task_info.iter().find_map(|info| match info {
+            TaskInfo::DeclarationTime(declaration) => Some((declaration.date.clone(), declaration.time.clone())),
+            _ => None,
+        })?;
+        let points = task_info
+            .iter()
+            .filter_map(|info| match info {
+                TaskInfo::TaskPoint(point) => Some(point.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if points.len() < 4 { return None }
+        let (start, finish) = (points[1].clone(), points[points.len() - 2].clone());
+        Some(Self { date, time, start, turn_points: points[2..points.len() - 2].to_vec(), finish })
+    }
+
+    /// Length in meters of the declared course through the start, turn points and finish
+    pub fn distance(&self) -> f64 {
+        self.points().zip(self.points().skip(1)).map(|(from, to)| from.coordinate.distance_to(&to.coordinate)).sum()
+    }
+
+    fn points(&self) -> impl Iterator<Item = &TaskPoint> + Clone {
+        std::iter::once(&self.start).chain(self.turn_points.iter()).chain(std::iter::once(&self.finish))
+    }
+}
+
+/// Result of flying the declared task
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclaredTaskResult {
+    /// The first fix in the observation zone of each point in order, `None` from the first point not reached
+    pub points: Vec<Option<CitedFix>>,
+    pub completed: bool,
+    /// The declaration is assumed to be made on the day of the flight if the date of the flight is unknown
+    pub declared_before_takeoff: bool,
+    /// Present if the task was completed
+    pub distance: Option<DistanceClaim>,
+}
+
+/// Full evaluation of a flight
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgeReport {
+    pub takeoff: Option<CitedFix>,
+    pub landing: Option<CitedFix>,
+    pub duration: u32,
+    pub height_gain: Option<HeightGain>,
+    /// Straight distance from the takeoff to the landing
+    pub free_distance: Option<DistanceClaim>,
+    pub declared_task: Option<DeclaredTaskResult>,
+    pub checks: Vec<BadgeCheck>,
+}
+
+impl BadgeReport {
+    pub fn passed(&self, badge: Badge, leg: BadgeLeg) -> bool {
+        self.checks.iter().any(|check| check.badge == badge && check.leg == leg && check.passed)
+    }
+}
+
+/// Badge requirements and observation zones
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::badges::{Badge, BadgeLeg, BadgeRules};
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let report = BadgeRules::default().evaluate_file(&file);
+/// for check in report.checks.iter().filter(|check| check.passed) {
+///     println!("{:?} {:?}: {:.0}", check.badge, check.leg, check.achieved);
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgeRules {
+    /// Meters for the silver, gold and diamond distance
+    pub distance: [f64; 3],
+    /// Seconds for the silver and gold duration
+    pub duration: u32,
+    /// Meters for the silver, gold and diamond height gain
+    pub height_gain: [f64; 3],
+    /// Meters of a declared task for the diamond goal
+    pub goal: f64,
+    /// Radius in meters of the cylinders around the start and finish points
+    pub start_finish_radius: f64,
+    /// Radius in meters of the cylinders around the turn points
+    pub turn_point_radius: f64,
+}
+
+impl Default for BadgeRules {
+    fn default() -> Self {
+        Self {
+            distance: [50_000., 300_000., 500_000.],
+            duration: 5 * 3600,
+            height_gain: [1000., 3000., 5000.],
+            goal: 300_000.,
+            start_finish_radius: 1000.,
+            turn_point_radius: 500.,
+        }
+    }
+}
+
+/// Index of the first and last fix of the flight from the ground speed
+pub fn takeoff_and_landing(fixes: &[Fix]) -> Option<(usize, usize)> {
+    let moving = |i: usize| ground_speed(&fixes[i], &fixes[i + 1]) > GROUND_SPEED;
+    let takeoff = (0..fixes.len().saturating_sub(1)).find(|&i| moving(i))?;
+    let landing = (0..fixes.len() - 1).rev().find(|&i| moving(i))? + 1;
+    Some((takeoff, landing))
+}
+
+/// Largest climb in pressure altitude from a low point to a later high point
+pub fn height_gain(fixes: &[Fix]) -> Option<HeightGain> {
+    let mut low = 0;
+    let mut best: Option<(usize, usize)> = None;
+    let gain = |low: usize, high: usize| fixes[high].pressure_alt as i32 - fixes[low].pressure_alt as i32;
+    for (i, fix) in fixes.iter().enumerate() {
+        if fix.pressure_alt < fixes[low].pressure_alt { low = i }
+        if best.is_none_or(|(best_low, best_high)| gain(low, i) > gain(best_low, best_high)) {
+            best = Some((low, i));
+        }
+    }
+    let (low, high) = best?;
+    Some(HeightGain {
+        low: CitedFix::new(fixes, low),
+        high: CitedFix::new(fixes, high),
+        gain: fixes[high].pressure_alt as f64 - fixes[low].pressure_alt as f64,
+    })
+}
+
+impl BadgeRules {
+    /// Evaluates the fixes and declaration of a parsed file, records that failed to parse are ignored
+    pub fn evaluate_file(&self, igc_file: &IGCFile) -> BadgeReport {
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let task_info = igc_file.get_task_info().iter().filter_map(|info| info.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let declaration = Declaration::from_task_info(&task_info);
+        self.evaluate(&fixes, declaration.as_ref(), FlightMetadata::from_igc_file(igc_file).date.as_ref())
+    }
+
+    /// Evaluates the badge legs, `flight_date` is used to check that the declaration was made before takeoff
+    pub fn evaluate(&self, fixes: &[Fix], declaration: Option<&Declaration>, flight_date: Option<&Date>) -> BadgeReport {
+        let Some((takeoff, landing)) = takeoff_and_landing(fixes) else {
+            return BadgeReport { takeoff: None, landing: None, duration: 0, height_gain: None, free_distance: None, declared_task: None, checks: Vec::new() }
+        };
+        let flight = &fixes[takeoff..=landing];
+        let duration = seconds_since(&fixes[takeoff].timestamp, &fixes[landing].timestamp);
+        let height_gain = height_gain(flight).map(|gain| HeightGain {
+            low: CitedFix::new(fixes, gain.low.index + takeoff),
+            high: CitedFix::new(fixes, gain.high.index + takeoff),
+            gain: gain.gain,
+        });
+        let free_distance = DistanceClaim::new(fixes, takeoff, landing, fixes[takeoff].coordinates.distance_to(&fixes[landing].coordinates));
+        let declared_task = declaration.map(|declaration| self.declared_task(fixes, takeoff, landing, declaration, flight_date));
+
+        let mut checks = Vec::new();
+        let task_distance = declared_task.as_ref().and_then(|task| task.distance.as_ref());
+        let distance = match task_distance {
+            Some(task) if task.claimed_distance > free_distance.claimed_distance => task,
+            _ => &free_distance,
+        };
+        for (badge, required) in [Badge::Silver, Badge::Gold, Badge::Diamond].into_iter().zip(self.distance) {
+            checks.push(check(badge, BadgeLeg::Distance, required, distance.claimed_distance, vec![distance.start.clone(), distance.finish.clone()]));
+        }
+        for badge in [Badge::Silver, Badge::Gold] {
+            let fixes = vec![CitedFix::new(fixes, takeoff), CitedFix::new(fixes, landing)];
+            checks.push(check(badge, BadgeLeg::Duration, self.duration as f64, duration as f64, fixes));
+        }
+        if let Some(height_gain) = &height_gain {
+            for (badge, required) in [Badge::Silver, Badge::Gold, Badge::Diamond].into_iter().zip(self.height_gain) {
+                checks.push(check(badge, BadgeLeg::HeightGain, required, height_gain.gain, vec![height_gain.low.clone(), height_gain.high.clone()]));
+            }
+        }
+        if let Some(task) = &declared_task {
+            let achieved = task.distance.as_ref().filter(|_| task.declared_before_takeoff).map_or(0., |distance| distance.claimed_distance);
+            checks.push(check(Badge::Diamond, BadgeLeg::Goal, self.goal, achieved, task.points.iter().flatten().cloned().collect()));
+        }
+
+        BadgeReport {
+            takeoff: Some(CitedFix::new(fixes, takeoff)),
+            landing: Some(CitedFix::new(fixes, landing)),
+            duration,
+            height_gain,
+            free_distance: Some(free_distance),
+            declared_task,
+            checks,
+        }
+    }
+
+    fn declared_task(&self, fixes: &[Fix], takeoff: usize, landing: usize, declaration: &Declaration, flight_date: Option<&Date>) -> DeclaredTaskResult {
+        let point_count = declaration.turn_points.len() + 2;
+        let mut points = Vec::with_capacity(point_count);
+        let mut next = takeoff;
+        for (i, point) in declaration.points().enumerate() {
+            let radius = if i == 0 || i == point_count - 1 { self.start_finish_radius } else { self.turn_point_radius };
+            let reached = (next..=landing).find(|&index| fixes[index].coordinates.distance_to(&point.coordinate) <= radius);
+            points.push(reached.map(|index| CitedFix::new(fixes, index)));
+            match reached {
+                Some(index) => next = index,
+                None => break,
+            }
+        }
+        points.resize(point_count, None);
+
+        let completed = points.iter().all(Option::is_some);
+        let distance = match (&points[0], &points[point_count - 1]) {
+            (Some(start), Some(finish)) if completed => Some(DistanceClaim::new(fixes, start.index, finish.index, declaration.distance())),
+            _ => None,
+        };
+        let declared = declaration.date.days_since_epoch();
+        let declared_before_takeoff = match flight_date {
+            Some(date) if date.days_since_epoch() != declared => declared < date.days_since_epoch(),
+            _ => declaration.time.seconds_since_midnight() < fixes[takeoff].timestamp.seconds_since_midnight(),
+        };
+        DeclaredTaskResult { points, completed, declared_before_takeoff, distance }
+    }
+}
+
+fn check(badge: Badge, leg: BadgeLeg, required: f64, achieved: f64, fixes: Vec<CitedFix>) -> BadgeCheck {
+    BadgeCheck { badge, leg, required, achieved, passed: achieved >= required, fixes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::util::Coordinate;
+
+    /// Out and return of about 2 x 155km due north from 50N 5E taking 6 hours, climbing to 3100m after an early low point of 300m
+    fn out_and_return() -> Vec<Fix> {
+        let mut fixes = (0..5).map(|t| Fix::at(36000 + t * 60, 50., 5., 200)).collect::<Vec<_>>();
+        let steps = 360;
+        for step in 1..=steps {
+            let progress = step as f64 / steps as f64;
+            let latitude = 50. + 1.4 * (1. - (2. * progress - 1.).abs());
+            let altitude = match step {
+                1..=9 => 600 - step as i16 * 30,
+                10..=60 => 300 + (step as i16 - 10) * 56,
+                _ => 3100 - ((step - 60) as i16 * 8).min(2890),
+            };
+            fixes.push(Fix::at(36240 + step * 60, latitude, 5., altitude));
+        }
+        let landing = fixes.last().unwrap().clone();
+        fixes.extend((1..5).map(|t| Fix { timestamp: Time::from_seconds_since_midnight(landing.timestamp.seconds_since_midnight() + t * 60).unwrap(), ..landing.clone() }));
+        fixes
+    }
+
+    fn task_info(turn_point: (f64, f64)) -> Vec<TaskInfo> {
+        let point = |latitude: f64, longitude: f64, name: &str| TaskInfo::TaskPoint(TaskPoint { coordinate: Coordinate::from_decimal_degrees(latitude, longitude), name: Some(name.into()) });
+        vec![
+            TaskInfo::parse("C010623080000000000000101").unwrap(),
+            point(50., 5., "TAKEOFF"),
+            point(50., 5., "START"),
+            point(turn_point.0, turn_point.1, "TP"),
+            point(50., 5., "FINISH"),
+            point(50., 5., "LANDING"),
+        ]
+    }
+
+    #[test]
+    fn declaration_from_c_records() {
+        let declaration = Declaration::from_task_info(&task_info((51.4, 5.))).unwrap();
+        assert_eq!(declaration.time, Time::from_hms(8, 0, 0).unwrap());
+        assert_eq!(declaration.turn_points.len(), 1);
+        assert_eq!(declaration.start.name.as_deref(), Some("START"));
+        assert!((declaration.distance() - 2. * 155_673.).abs() < 100.);
+        assert!(Declaration::from_task_info(&task_info((51.4, 5.))[..4]).is_none());
+    }
+
+    #[test]
+    fn takeoff_landing_and_height_gain() {
+        let fixes = out_and_return();
+        assert_eq!(takeoff_and_landing(&fixes), Some((4, 364)));
+        let gain = height_gain(&fixes).unwrap();
+        assert_eq!((gain.low.index, gain.high.index, gain.gain), (0, 64, 2900.));
+        assert_eq!(gain.low.pressure_alt, 200);
+        let gain = height_gain(&fixes[5..]).unwrap();
+        assert_eq!((gain.low.index + 5, gain.gain), (14, 2800.));
+        assert!(takeoff_and_landing(&fixes[..5]).is_none());
+    }
+
+    #[test]
+    fn evaluates_badges() {
+        let fixes = out_and_return();
+        let declaration = Declaration::from_task_info(&task_info((51.4, 5.))).unwrap();
+        let date = Date { d: 1, m: 6, y: 23 };
+        let report = BadgeRules::default().evaluate(&fixes, Some(&declaration), Some(&date));
+
+        assert_eq!(report.duration, 6 * 3600);
+        let task = report.declared_task.as_ref().unwrap();
+        assert!(task.completed);
+        assert!(task.declared_before_takeoff);
+        assert_eq!(task.points.iter().map(|point| point.as_ref().unwrap().index).collect::<Vec<_>>(), vec![4, 184, 363]);
+        let distance = task.distance.as_ref().unwrap();
+        assert!(distance.height_loss < 0.);
+        assert_eq!(distance.claimed_distance, distance.distance);
+
+        assert!(report.passed(Badge::Silver, BadgeLeg::Distance));
+        assert!(report.passed(Badge::Gold, BadgeLeg::Distance));
+        assert!(!report.passed(Badge::Diamond, BadgeLeg::Distance));
+        assert!(report.passed(Badge::Diamond, BadgeLeg::Goal));
+        assert!(report.passed(Badge::Gold, BadgeLeg::Duration));
+        assert!(report.passed(Badge::Silver, BadgeLeg::HeightGain));
+        assert!(!report.passed(Badge::Gold, BadgeLeg::HeightGain));
+        let duration = report.checks.iter().find(|check| check.leg == BadgeLeg::Duration).unwrap();
+        assert_eq!(duration.fixes.iter().map(|fix| fix.index).collect::<Vec<_>>(), vec![4, 364]);
+
+        // a turn point that was never reached
+        let declaration = Declaration::from_task_info(&task_info((51.4, 6.))).unwrap();
+        let report = BadgeRules::default().evaluate(&fixes, Some(&declaration), Some(&date));
+        let task = report.declared_task.as_ref().unwrap();
+        assert!(!task.completed);
+        assert!(task.points[0].is_some() && task.points[1].is_none() && task.points[2].is_none());
+        assert!(!report.passed(Badge::Diamond, BadgeLeg::Goal));
+        assert!(!report.passed(Badge::Silver, BadgeLeg::Distance));
+    }
+
+    #[test]
+    fn declaration_across_the_century() {
+        let fixes = out_and_return();
+        let flight_date = Date { d: 1, m: 1, y: 0 };
+        let declared = |date: Date| {
+            let declaration = Declaration { date, time: Time::from_hms(23, 0, 0).unwrap(), ..Declaration::from_task_info(&task_info((51.4, 5.))).unwrap() };
+            let report = BadgeRules::default().evaluate(&fixes, Some(&declaration), Some(&flight_date));
+            report.declared_task.unwrap().declared_before_takeoff
+        };
+        // 31 December 1999 is before 1 January 2000 and 2 January 2000 is after
+        assert!(declared(Date { d: 31, m: 12, y: 99 }));
+        assert!(!declared(Date { d: 2, m: 1, y: 0 }));
+    }
+
+    #[test]
+    fn one_percent_rule() {
+        let fixes = [Fix::at(36000, 50., 5., 1600), Fix::at(39600, 50.5, 5., 600)];
+        let claim = DistanceClaim::new(&fixes, 0, 1, 55_000.);
+        assert_eq!(claim.height_loss, 1000.);
+        assert_eq!(claim.claimed_distance, 55_000. - 100. * 450.);
+        let claim = DistanceClaim::new(&fixes, 0, 1, 100_000.);
+        assert_eq!(claim.claimed_distance, 100_000.);
+    }
+}
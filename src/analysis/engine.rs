@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use crate::analysis::seconds_since;
+use crate::igc_file::IGCFile;
+use crate::records::event::{Event, EventKind};
+use crate::records::extension::Extension;
+use crate::records::fix::Fix;
+use crate::records::util::Time;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// B record extension that measures the engine
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineChannel {
+    /// `ENL`, environmental noise level, 000 to 999
+    ENL,
+    /// `MOP`, means of propulsion sensor, 000 to 999
+    MOP,
+    /// `RPM`, engine revolutions per minute
+    RPM,
+}
+
+impl EngineChannel {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EngineChannel::ENL => "ENL",
+            EngineChannel::MOP => "MOP",
+            EngineChannel::RPM => "RPM",
+        }
+    }
+}
+
+/// Levels at or above which a channel counts as the engine running
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineThresholds {
+    pub enl: i32,
+    pub mop: i32,
+    pub rpm: i32,
+    /// Runs shorter than this many seconds are ignored
+    pub min_duration: u32,
+}
+
+impl Default for EngineThresholds {
+    fn default() -> Self {
+        Self { enl: 500, mop: 250, rpm: 300, min_duration: 30 }
+    }
+}
+
+impl EngineThresholds {
+    pub fn threshold(&self, channel: EngineChannel) -> i32 {
+        match channel {
+            EngineChannel::ENL => self.enl,
+            EngineChannel::MOP => self.mop,
+            EngineChannel::RPM => self.rpm,
+        }
+    }
+}
+
+/// A period where the engine was running
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineRun {
+    pub start: Time,
+    pub end: Time,
+    pub duration: u32,
+    pub start_pressure_alt: i16,
+    pub start_gps_alt: Option<i16>,
+    pub end_pressure_alt: i16,
+    pub end_gps_alt: Option<i16>,
+    /// The channel that first crossed its threshold
+    pub channel: EngineChannel,
+    pub peak_level: i32,
+    /// `None` if the task window is unknown
+    pub in_task: Option<bool>,
+}
+
+/// Start and finish of the task
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskWindow {
+    pub start: Time,
+    pub finish: Time,
+}
+
+impl TaskWindow {
+    /// The task window from the first `STA` (start) and last `FIN` (finish) events
+    pub fn from_events(events: &[Event]) -> Option<Self> {
+        let start = events.iter().find(|event| event.kind() == EventKind::Start)?;
+        let finish = events.iter().rev().find(|event| event.kind() == EventKind::Finish)?;
+        Some(Self { start: start.time.clone(), finish: finish.time.clone() })
+    }
+
+    fn overlaps(&self, reference: &Time, start: u32, end: u32) -> bool {
+        let task_start = seconds_since(reference, &self.start);
+        let task_finish = seconds_since(reference, &self.finish);
+        start <= task_finish && end >= task_start
+    }
+}
+
+/// Result of running the engine detector over a flight
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineReport {
+    /// Channels declared in the I record that were used
+    pub channels: Vec<EngineChannel>,
+    pub runs: Vec<EngineRun>,
+    pub task_window: Option<TaskWindow>,
+}
+
+impl EngineReport {
+    /// Whether any engine run overlapped the task window
+    pub fn engine_run_in_task(&self) -> bool {
+        self.runs.iter().any(|run| run.in_task == Some(true))
+    }
+}
+
+/// Detects engine runs from the ENL, MOP and RPM extensions of the B records
+///
+/// The thresholds can be overridden per logger type by the three letter manufacturer code of the A record
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::engine::{EngineDetector, EngineThresholds};
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let detector = EngineDetector::default()
+///     .with_logger_thresholds("LXV", EngineThresholds { enl: 600, ..EngineThresholds::default() });
+/// let report = detector.detect_in_file(&IGCFile::parse(&file).unwrap());
+/// println!("{} engine runs", report.runs.len())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EngineDetector {
+    pub default_thresholds: EngineThresholds,
+    pub logger_thresholds: HashMap<String, EngineThresholds>,
+}
+
+impl EngineDetector {
+    pub fn new(default_thresholds: EngineThresholds) -> Self {
+        Self { default_thresholds, logger_thresholds: HashMap::new() }
+    }
+
+    pub fn with_logger_thresholds(mut self, manufacturer_code: &str, thresholds: EngineThresholds) -> Self {
+        self.logger_thresholds.insert(manufacturer_code.to_ascii_uppercase(), thresholds);
+        self
+    }
+
+    /// The thresholds used for a logger, falling back to the default thresholds
+    pub fn thresholds_for(&self, manufacturer_code: Option<&str>) -> &EngineThresholds {
+        manufacturer_code
+            .and_then(|code| self.logger_thresholds.get(&code.to_ascii_uppercase()))
+            .unwrap_or(&self.default_thresholds)
+    }
+
+    /// Runs the detector over a parsed file, records that failed to parse are ignored
+    pub fn detect_in_file(&self, igc_file: &IGCFile) -> EngineReport {
+        let manufacturer = igc_file.get_fr_ids().iter().find_map(|fr_id| fr_id.as_ref().ok()).map(|fr_id| match fr_id.manufacturer_info() {
+            Some(manufacturer) => manufacturer.code.to_string(),
+            None => fr_id.manufacturer.to_string(),
+        });
+        let thresholds = self.thresholds_for(manufacturer.as_deref());
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let events = igc_file.get_events().iter().filter_map(|event| event.as_ref().ok().cloned()).collect::<Vec<_>>();
+        match igc_file.get_i_extension_vec().iter().find_map(|i_record| i_record.as_ref().ok()) {
+            Some(i_record) => detect(&fixes, i_record, thresholds, TaskWindow::from_events(&events)),
+            None => EngineReport { channels: Vec::new(), runs: Vec::new(), task_window: TaskWindow::from_events(&events) },
+        }
+    }
+}
+
+/// Detects engine runs in `fixes` whose extensions are described by `i_record`
+pub fn detect(fixes: &[Fix], i_record: &Extension, thresholds: &EngineThresholds, task_window: Option<TaskWindow>) -> EngineReport {
+    let channels = [EngineChannel::ENL, EngineChannel::MOP, EngineChannel::RPM]
+        .into_iter()
+        .filter(|channel| i_record.has(channel.code()))
+        .collect::<Vec<_>>();
+    let reference = match fixes.first() {
+        Some(fix) => fix.timestamp.clone(),
+        None => return EngineReport { channels, runs: Vec::new(), task_window },
+    };
+
+    // (first fix, last fix, channel, peak level) of each period above a threshold
+    let mut periods: Vec<(usize, usize, EngineChannel, i32)> = Vec::new();
+    let mut running = false;
+    for (i, fix) in fixes.iter().enumerate() {
+        let above = channels
+            .iter()
+            .filter_map(|&channel| {
+                let level = fix.get_extension_value(i_record, channel.code())?;
+                (level >= thresholds.threshold(channel)).then_some((channel, level))
+            })
+            .max_by_key(|(_, level)| *level);
+        match (above, periods.last_mut()) {
+            (Some((_, level)), Some(period)) if running => {
+                period.1 = i;
+                period.3 = period.3.max(level);
+            }
+            (Some((channel, level)), _) => {
+                periods.push((i, i, channel, level));
+                running = true;
+            }
+            (None, _) => running = false,
+        }
+    }
+
+    let runs = periods
+        .into_iter()
+        .filter_map(|(first, last, channel, peak_level)| {
+            let (start_fix, end_fix) = (&fixes[first], &fixes[last]);
+            let start = seconds_since(&reference, &start_fix.timestamp);
+            let end = seconds_since(&reference, &end_fix.timestamp);
+            let duration = end.saturating_sub(start);
+            (duration >= thresholds.min_duration).then(|| EngineRun {
+                start: start_fix.timestamp.clone(),
+                end: end_fix.timestamp.clone(),
+                duration,
+                start_pressure_alt: start_fix.pressure_alt,
+                start_gps_alt: start_fix.gps_alt,
+                end_pressure_alt: end_fix.pressure_alt,
+                end_gps_alt: end_fix.gps_alt,
+                channel,
+                peak_level,
+                in_task: task_window.as_ref().map(|window| window.overlaps(&reference, start, end)),
+            })
+        })
+        .collect();
+
+    EngineReport { channels, runs, task_window }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix_line(time: &str, pressure_alt: u16, enl: u16) -> String {
+        format!("B{time}5107126N00149300WA{pressure_alt:05}00429{enl:03}")
+    }
+
+    fn content(enl: &[u16]) -> String {
+        let mut lines = vec!["AXXXABC".to_string(), "I013638ENL".to_string(), "E120000STA".to_string()];
+        for (i, level) in enl.iter().enumerate() {
+            let seconds = i * 10;
+            lines.push(fix_line(&format!("12{:02}{:02}", seconds / 60, seconds % 60), 500 + i as u16, *level));
+        }
+        lines.push("E121000FIN".to_string());
+        lines.join("\n")
+    }
+
+    #[test]
+    fn detects_engine_run() {
+        let igc_file = IGCFile::parse(&content(&[50, 60, 700, 800, 750, 900, 40, 30])).unwrap();
+        let report = EngineDetector::default().detect_in_file(&igc_file);
+        assert_eq!(report.channels, vec![EngineChannel::ENL]);
+        assert_eq!(report.runs.len(), 1);
+        let run = &report.runs[0];
+        assert_eq!(run.start, Time::from_hms(12, 0, 20).unwrap());
+        assert_eq!(run.end, Time::from_hms(12, 0, 50).unwrap());
+        assert_eq!(run.duration, 30);
+        assert_eq!(run.start_pressure_alt, 502);
+        assert_eq!(run.end_pressure_alt, 505);
+        assert_eq!(run.peak_level, 900);
+        assert_eq!(run.in_task, Some(true));
+        assert!(report.engine_run_in_task());
+    }
+
+    #[test]
+    fn short_runs_and_per_logger_thresholds() {
+        let igc_file = IGCFile::parse(&content(&[50, 700, 50, 50])).unwrap();
+        assert!(EngineDetector::default().detect_in_file(&igc_file).runs.is_empty());
+
+        let igc_file = IGCFile::parse(&content(&[50, 700, 700, 700, 700, 50])).unwrap();
+        let detector = EngineDetector::default().with_logger_thresholds("XXX", EngineThresholds { enl: 750, ..EngineThresholds::default() });
+        assert!(detector.detect_in_file(&igc_file).runs.is_empty());
+        assert_eq!(EngineDetector::default().detect_in_file(&igc_file).runs.len(), 1);
+    }
+
+    #[test]
+    fn without_extensions() {
+        let igc_file = IGCFile::parse(&fix_line("120000", 500, 999)).unwrap();
+        let report = EngineDetector::default().detect_in_file(&igc_file);
+        assert!(report.channels.is_empty());
+        assert!(report.runs.is_empty());
+        assert!(!report.engine_run_in_task());
+    }
+}
@@ -0,0 +1,453 @@
+use crate::airspace::AltitudeSource;
+use crate::igc_file::IGCFile;
+use crate::records::fix::Fix;
+use crate::records::util::{Coordinate, Date};
+use crate::track::indexed::IndexedTrack;
+use crate::track::project;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Seconds without turning that still count as the same thermal, e.g. when straightening out to center it
+const MAX_TURN_GAP: i64 = 10;
+
+/// Separation between two flights at one instant
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Separation {
+    /// Unix timestamp in seconds
+    pub timestamp: i64,
+    /// Meters along the ground
+    pub horizontal: f64,
+    /// Meters the first flight is above the second, negative if it is below
+    pub vertical: f64,
+}
+
+impl Separation {
+    /// Distance in meters in three dimensions
+    pub fn distance(&self) -> f64 {
+        self.horizontal.hypot(self.vertical)
+    }
+}
+
+/// A period where two flights were closer than the proximity distance
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosestApproach {
+    pub first: usize,
+    pub second: usize,
+    pub start: i64,
+    pub end: i64,
+    /// The smallest separation of the period
+    pub closest: Separation,
+}
+
+/// Which of two flights flew the same path first
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lead {
+    pub leader: usize,
+    pub follower: usize,
+    /// Mean seconds the follower was behind the leader on the same path
+    pub mean_lag: f64,
+    /// Share of the samples where the leader was ahead, from 0 to 1
+    pub leading_share: f64,
+    /// Samples where the flights were on the same path
+    pub samples: usize,
+}
+
+/// Comparison of two flights, `first` is the lower index
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairComparison {
+    pub first: usize,
+    pub second: usize,
+    /// Separation at every sample where both flights have a position
+    pub separations: Vec<Separation>,
+    pub closest: Option<Separation>,
+    /// `None` if the flights never flew the same path
+    pub lead: Option<Lead>,
+}
+
+/// A period of circling of one flight
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thermal {
+    pub flight: usize,
+    pub start: i64,
+    pub end: i64,
+    /// Mean position while circling
+    pub center: Coordinate,
+    pub entry_altitude: f64,
+    pub exit_altitude: f64,
+}
+
+impl Thermal {
+    /// Average climb in meters per second
+    pub fn climb_rate(&self) -> f64 {
+        (self.exit_altitude - self.entry_altitude) / (self.end - self.start).max(1) as f64
+    }
+}
+
+/// Thermals of several flights overlapping in time and place
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedThermal {
+    pub start: i64,
+    pub end: i64,
+    /// Mean of the centers of the thermals
+    pub center: Coordinate,
+    /// The thermals of each flight in order of entry
+    pub thermals: Vec<Thermal>,
+}
+
+impl SharedThermal {
+    /// Indices of the flights that shared the thermal, in order of entry
+    pub fn flights(&self) -> Vec<usize> {
+        let mut flights = Vec::new();
+        for thermal in &self.thermals {
+            if !flights.contains(&thermal.flight) { flights.push(thermal.flight) }
+        }
+        flights
+    }
+}
+
+/// Flights aligned on UTC and compared with each other
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightComparison {
+    /// Unix timestamp of the first sample
+    pub start: i64,
+    /// Seconds between samples
+    pub interval: u32,
+    /// Every pair of flights
+    pub pairs: Vec<PairComparison>,
+    pub closest_approaches: Vec<ClosestApproach>,
+    pub thermals: Vec<Thermal>,
+    pub shared_thermals: Vec<SharedThermal>,
+}
+
+impl FlightComparison {
+    /// The comparison of two flights in either order
+    pub fn pair(&self, first: usize, second: usize) -> Option<&PairComparison> {
+        let (first, second) = (first.min(second), first.max(second));
+        self.pairs.iter().find(|pair| pair.first == first && pair.second == second)
+    }
+}
+
+/// Comparison of several flights of the same day, for debriefs and proximity studies
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::gaggle::FlightComparator;
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let comparator = FlightComparator { interval: 10, ..FlightComparator::default() };
+/// let comparison = comparator.compare_files(&[file.clone(), file]);
+/// let pair = comparison.pair(0, 1).unwrap();
+/// assert_eq!(pair.closest.as_ref().unwrap().horizontal, 0.);
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightComparator {
+    /// Seconds between the samples the flights are compared at
+    pub interval: u32,
+    pub altitude_source: AltitudeSource,
+    /// Flights closer than this in meters in three dimensions are reported as closest approaches
+    pub proximity: f64,
+    /// Seconds before and after to look for the other flight on the same path
+    pub lead_window: u32,
+    /// Flights passing within this many meters of the same point count as on the same path
+    pub lead_distance: f64,
+    /// Minimum turn rate in degrees per second for a flight to count as circling
+    pub min_turn_rate: f64,
+    /// Shorter periods of circling in seconds are not thermals
+    pub min_thermal_duration: u32,
+    /// Thermals with centers within this many meters are the same thermal
+    pub thermal_radius: f64,
+    /// Flights spanning more seconds from the first to the last fix are not of the same day and are not compared
+    pub max_span: u32,
+}
+
+impl Default for FlightComparator {
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            altitude_source: AltitudeSource::Gps,
+            proximity: 100.,
+            lead_window: 120,
+            lead_distance: 200.,
+            min_turn_rate: 4.,
+            min_thermal_duration: 60,
+            thermal_radius: 1000.,
+            max_span: 86400,
+        }
+    }
+}
+
+impl FlightComparator {
+    /// Compares the parsed fixes of the files, records that failed to parse are ignored
+    pub fn compare_files(&self, igc_files: &[IGCFile]) -> FlightComparison {
+        self.compare(&igc_files.iter().map(IndexedTrack::from_igc_file).collect::<Vec<_>>())
+    }
+
+    /// Compares the flights on a common time line, tracks without a date are taken to be on the day of the first track with one
+    ///
+    /// If no track has a date the timestamps are on an arbitrary day and only the times of day are meaningful.
+    /// The comparison is empty when the flights together span more than `max_span`, e.g. when one file has a wrong date.
+    pub fn compare(&self, tracks: &[IndexedTrack]) -> FlightComparison {
+        let interval = self.interval.max(1) as i64;
+        let date = tracks.iter().find_map(|track| track.date().cloned()).unwrap_or(Date { d: 1, m: 1, y: 0 });
+        let tracks = tracks
+            .iter()
+            .map(|track| if track.date().is_some() { track.clone() } else { track.clone().with_date(date.clone()) })
+            .collect::<Vec<_>>();
+        let bounds = tracks
+            .iter()
+            .filter_map(|track| Some((track.timestamp(0)?, track.timestamp(track.len() - 1)?)))
+            .reduce(|(start, end), (track_start, track_end)| (start.min(track_start), end.max(track_end)));
+        let Some((start, end)) = bounds.filter(|(start, end)| end - start <= self.max_span as i64) else {
+            return FlightComparison { start: 0, interval: interval as u32, pairs: Vec::new(), closest_approaches: Vec::new(), thermals: Vec::new(), shared_thermals: Vec::new() }
+        };
+
+        let count = ((end - start) / interval + 1) as usize;
+        let samples = tracks
+            .iter()
+            .map(|track| (0..count).map(|k| track.position_at_timestamp(start + k as i64 * interval)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let reference_latitude = tracks.iter().find_map(|track| track.fixes().first()).map_or(0., |fix| fix.coordinates.to_decimal_degrees().0);
+        let points = samples
+            .iter()
+            .map(|flight| flight.iter().map(|fix| fix.as_ref().map(|fix| project(&fix.coordinates, reference_latitude))).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut pairs = Vec::new();
+        let mut closest_approaches = Vec::new();
+        for first in 0..tracks.len() {
+            for second in first + 1..tracks.len() {
+                let separations = (0..count)
+                    .filter_map(|k| {
+                        let (a, b) = (samples[first][k].as_ref()?, samples[second][k].as_ref()?);
+                        Some(Separation {
+                            timestamp: start + k as i64 * interval,
+                            horizontal: a.coordinates.distance_to(&b.coordinates),
+                            vertical: self.altitude_source.altitude(a) - self.altitude_source.altitude(b),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                closest_approaches.extend(self.closest_approaches(first, second, &separations, interval));
+                let closest = separations.iter().min_by(|a, b| a.distance().total_cmp(&b.distance())).cloned();
+                let lead = self.lead(&points, first, second, interval);
+                pairs.push(PairComparison { first, second, separations, closest, lead });
+            }
+        }
+
+        let mut thermals = samples
+            .iter()
+            .zip(&points)
+            .enumerate()
+            .flat_map(|(flight, (samples, points))| self.thermals(flight, samples, points, start, interval))
+            .collect::<Vec<_>>();
+        thermals.sort_by_key(|thermal| thermal.start);
+        let shared_thermals = self.shared_thermals(&thermals);
+        FlightComparison { start, interval: interval as u32, pairs, closest_approaches, thermals, shared_thermals }
+    }
+
+    /// Periods closer than `proximity`, a missing sample ends a period
+    fn closest_approaches(&self, first: usize, second: usize, separations: &[Separation], interval: i64) -> Vec<ClosestApproach> {
+        let mut approaches: Vec<ClosestApproach> = Vec::new();
+        for separation in separations.iter().filter(|separation| separation.distance() < self.proximity) {
+            match approaches.last_mut() {
+                // a sample in between that was further apart or missing ends the period
+                Some(approach) if approach.end == separation.timestamp - interval => {
+                    approach.end = separation.timestamp;
+                    if separation.distance() < approach.closest.distance() { approach.closest = separation.clone() }
+                }
+                _ => approaches.push(ClosestApproach { first, second, start: separation.timestamp, end: separation.timestamp, closest: separation.clone() }),
+            }
+        }
+        approaches
+    }
+
+    /// Compares each position of `second` with where `first` passed closest to it within the lead window
+    fn lead(&self, points: &[Vec<Option<(f64, f64)>>], first: usize, second: usize, interval: i64) -> Option<Lead> {
+        let window = (self.lead_window as i64 / interval) as usize;
+        let (mut lag_sum, mut ahead, mut behind, mut samples) = (0, 0, 0, 0);
+        for (k, point) in points[second].iter().enumerate() {
+            let Some((x, y)) = point else { continue };
+            let from = k.saturating_sub(window);
+            let nearest = points[first][from..(k + window + 1).min(points[first].len())]
+                .iter()
+                .enumerate()
+                .filter_map(|(j, other)| other.map(|(other_x, other_y)| (from + j, (other_x - x).hypot(other_y - y))))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((j, distance)) = nearest else { continue };
+            if distance > self.lead_distance { continue }
+            // positive if the first flight passed here before the second
+            let lag = (k as i64 - j as i64) * interval;
+            lag_sum += lag;
+            samples += 1;
+            if lag > 0 { ahead += 1 } else if lag < 0 { behind += 1 }
+        }
+        if samples == 0 { return None }
+        let mean_lag = lag_sum as f64 / samples as f64;
+        Some(match mean_lag >= 0. {
+            true => Lead { leader: first, follower: second, mean_lag, leading_share: ahead as f64 / samples as f64, samples },
+            false => Lead { leader: second, follower: first, mean_lag: -mean_lag, leading_share: behind as f64 / samples as f64, samples },
+        })
+    }
+
+    fn thermals(&self, flight: usize, samples: &[Option<Fix>], points: &[Option<(f64, f64)>], start: i64, interval: i64) -> Vec<Thermal> {
+        let heading = |k: usize| {
+            let ((from_x, from_y), (to_x, to_y)) = (points[k - 1]?, points[k]?);
+            if from_x == to_x && from_y == to_y { return None }
+            Some((to_x - from_x).atan2(to_y - from_y).to_degrees())
+        };
+        let circling = |k: usize| match (heading(k), heading(k + 1)) {
+            (Some(before), Some(after)) => ((after - before + 540.).rem_euclid(360.) - 180.).abs() / interval as f64 >= self.min_turn_rate,
+            _ => false,
+        };
+
+        let mut periods: Vec<(usize, usize)> = Vec::new();
+        for k in (1..points.len().saturating_sub(1)).filter(|&k| circling(k)) {
+            match periods.last_mut() {
+                Some((_, last)) if (k - *last) as i64 * interval <= MAX_TURN_GAP => *last = k,
+                _ => periods.push((k, k)),
+            }
+        }
+        periods
+            .into_iter()
+            .filter(|(first, last)| (last - first) as i64 * interval >= self.min_thermal_duration as i64)
+            .filter_map(|(first, last)| {
+                let fixes = samples[first..=last].iter().flatten().collect::<Vec<_>>();
+                let (latitude, longitude) = fixes
+                    .iter()
+                    .map(|fix| fix.coordinates.to_decimal_degrees())
+                    .fold((0., 0.), |(latitude, longitude), (fix_latitude, fix_longitude)| (latitude + fix_latitude, longitude + fix_longitude));
+                Some(Thermal {
+                    flight,
+                    start: start + first as i64 * interval,
+                    end: start + last as i64 * interval,
+                    center: Coordinate::from_decimal_degrees(latitude / fixes.len() as f64, longitude / fixes.len() as f64),
+                    entry_altitude: self.altitude_source.altitude(samples[first].as_ref()?),
+                    exit_altitude: self.altitude_source.altitude(samples[last].as_ref()?),
+                })
+            })
+            .collect()
+    }
+
+    /// Groups thermals sorted by start that overlap in time with a thermal of the group within `thermal_radius`
+    fn shared_thermals(&self, thermals: &[Thermal]) -> Vec<SharedThermal> {
+        let mut groups: Vec<SharedThermal> = Vec::new();
+        for thermal in thermals {
+            let group = groups.iter_mut().find(|group| {
+                thermal.start <= group.end && group.thermals.iter().any(|other| other.center.distance_to(&thermal.center) <= self.thermal_radius)
+            });
+            match group {
+                Some(group) => {
+                    group.end = group.end.max(thermal.end);
+                    group.thermals.push(thermal.clone());
+                }
+                None => groups.push(SharedThermal { start: thermal.start, end: thermal.end, center: thermal.center.clone(), thermals: vec![thermal.clone()] }),
+            }
+        }
+        groups.retain(|group| group.flights().len() > 1);
+        for group in &mut groups {
+            let n = group.thermals.len() as f64;
+            let (latitude, longitude) = group.thermals.iter().fold((0., 0.), |(latitude, longitude), thermal| {
+                let (thermal_latitude, thermal_longitude) = thermal.center.to_decimal_degrees();
+                (latitude + thermal_latitude, longitude + thermal_longitude)
+            });
+            group.center = Coordinate::from_decimal_degrees(latitude / n, longitude / n);
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Meters per degree of latitude
+    const DEGREE: f64 = 111_195.;
+
+    /// Latitude and longitude of a point `east` and `north` meters from 51N 10E
+    fn position((east, north): (f64, f64)) -> (f64, f64) {
+        (51. + north / DEGREE, 10. + east / (DEGREE * 51f64.to_radians().cos()))
+    }
+
+    /// Five minutes circling at 15° per second in a thermal drifting east, then five minutes north, starting `delay` seconds after 10:00
+    fn flight(delay: u32) -> IndexedTrack {
+        let circling = |seconds: f64| {
+            let angle = (seconds * 15.).to_radians();
+            (2. * seconds + 100. * angle.sin(), 100. * angle.cos())
+        };
+        let fixes = (0..=600)
+            .map(|seconds| match seconds {
+                0..=300 => {
+                    let (latitude, longitude) = position(circling(seconds as f64));
+                    Fix::at(36000 + delay + seconds, latitude, longitude, 1000 + 2 * seconds as i16)
+                }
+                _ => {
+                    let (east, north) = circling(300.);
+                    let (latitude, longitude) = position((east, north + 30. * (seconds - 300) as f64));
+                    Fix::at(36000 + delay + seconds, latitude, longitude, 1600)
+                }
+            })
+            .collect();
+        IndexedTrack::new(fixes).with_date(Date { d: 6, m: 8, y: 20 })
+    }
+
+    #[test]
+    fn leader_and_shared_thermal() {
+        let comparison = FlightComparator::default().compare(&[flight(60), flight(0)]);
+        assert_eq!(comparison.start, flight(0).timestamp(0).unwrap());
+
+        let pair = comparison.pair(1, 0).unwrap();
+        assert_eq!(pair.separations.len(), 541);
+        let lead = pair.lead.as_ref().unwrap();
+        assert_eq!((lead.leader, lead.follower), (1, 0));
+        assert!((lead.mean_lag - 60.).abs() < 1.);
+        assert!(lead.leading_share > 0.95);
+
+        assert_eq!(comparison.thermals.len(), 2);
+        assert!((comparison.thermals[0].climb_rate() - 2.).abs() < 0.1);
+        assert_eq!(comparison.shared_thermals.len(), 1);
+        assert_eq!(comparison.shared_thermals[0].flights(), [1, 0]);
+        assert!(comparison.closest_approaches.is_empty());
+    }
+
+    #[test]
+    fn flights_of_different_days_are_not_compared() {
+        let last_year = flight(0).with_date(Date { d: 6, m: 8, y: 19 });
+        let comparison = FlightComparator::default().compare(&[flight(0), last_year]);
+        assert!(comparison.pairs.is_empty());
+        assert!(comparison.thermals.is_empty());
+
+        let comparison = FlightComparator { max_span: 300, ..FlightComparator::default() }.compare(&[flight(0), flight(60)]);
+        assert!(comparison.pairs.is_empty());
+    }
+
+    #[test]
+    fn closest_approach() {
+        // one flight heading north and one heading east at 30m/s, crossing 20m apart vertically after 100 seconds
+        let north = (0..=200).map(|seconds| {
+            let (latitude, longitude) = position((0., 30. * seconds as f64 - 3000.));
+            Fix::at(36000 + seconds, latitude, longitude, 1000)
+        }).collect();
+        let east = (0..=200).map(|seconds| {
+            let (latitude, longitude) = position((30. * seconds as f64 - 3000., 0.));
+            Fix::at(36000 + seconds, latitude, longitude, 1020)
+        }).collect();
+        let comparison = FlightComparator::default().compare(&[IndexedTrack::new(north), IndexedTrack::new(east)]);
+
+        assert_eq!(comparison.closest_approaches.len(), 1);
+        let approach = &comparison.closest_approaches[0];
+        assert_eq!(approach.end - approach.start, 4);
+        assert_eq!(approach.closest.timestamp, comparison.start + 100);
+        assert!(approach.closest.horizontal < 5.);
+        assert_eq!(approach.closest.vertical, -20.);
+        // crossing paths only share the crossing point, passed at the same time
+        assert!(comparison.pairs[0].lead.as_ref().unwrap().mean_lag < 1.);
+        assert!(comparison.shared_thermals.is_empty());
+    }
+}
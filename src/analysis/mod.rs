@@ -0,0 +1,64 @@
+use crate::records::fix::Fix;
+use crate::records::util::Time;
+
+/// Satellite constellation analysis from F records
+pub mod satellites;
+/// Engine run detection from ENL, MOP and RPM extensions
+pub mod engine;
+/// Wind estimation from circling drift and true airspeed and heading
+pub mod wind;
+/// Pressure altitude calibration and QNH, QFE and flight level conversions
+pub mod altitude;
+/// FAI badge and declared task claim evaluation
+pub mod badges;
+/// Comparison of several flights, proximity and shared thermals
+pub mod gaggle;
+
+/// Seconds from `start` to `time`, assuming less than 24 hours passed so a time before `start` is on the next day
+pub(crate) fn seconds_since(start: &Time, time: &Time) -> u32 {
+    (time.seconds_since_midnight() + 86400 - start.seconds_since_midnight()) % 86400
+}
+
+/// Seconds since midnight of the first time, counting on past midnight when a time is more than 12 hours before the one before it
+#[derive(Default)]
+pub(crate) struct Day {
+    offset: i64,
+    previous: Option<i64>,
+}
+
+impl Day {
+    pub(crate) fn seconds(&mut self, seconds: i64) -> i64 {
+        if self.previous.is_some_and(|previous| seconds + 43200 < previous) { self.offset += 86400 }
+        self.previous = Some(seconds);
+        seconds + self.offset
+    }
+}
+
+/// Fixes moving slower than this in km/h count as on the ground
+pub(crate) const GROUND_SPEED: f64 = 30.;
+
+/// Ground speed in km/h between two fixes in either order, fixes at the same time count as one second apart
+pub(crate) fn ground_speed(from: &Fix, to: &Fix) -> f64 {
+    let dt = seconds_since(&from.timestamp, &to.timestamp).min(seconds_since(&to.timestamp, &from.timestamp)).max(1);
+    from.coordinates.distance_to(&to.coordinates) / dt as f64 * 3.6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seconds_since_passes_midnight() {
+        let start = Time::from_hms(23, 59, 0).unwrap();
+        assert_eq!(seconds_since(&start, &Time::from_hms(23, 59, 30).unwrap()), 30);
+        assert_eq!(seconds_since(&start, &Time::from_hms(0, 1, 0).unwrap()), 120);
+        assert_eq!(seconds_since(&start, &start), 0);
+    }
+
+    #[test]
+    fn day_passes_midnight() {
+        let mut day = Day::default();
+        let seconds = [86340, 86390, 86380, 60, 120].map(|seconds| day.seconds(seconds));
+        assert_eq!(seconds, [86340, 86390, 86380, 86460, 86520]);
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeSet;
+
+use crate::analysis::seconds_since;
+use crate::igc_file::IGCFile;
+use crate::records::fix::Fix;
+use crate::records::satellite::{Satellite, SatelliteId};
+use crate::records::util::Time;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Satellites in use at a fix, taken from the latest F record at or before the fix
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixSatellites {
+    pub time: Time,
+    /// `None` before the first F record
+    pub satellites: Option<Vec<SatelliteId>>,
+    pub gps_valid: bool,
+}
+
+impl FixSatellites {
+    pub fn count(&self) -> Option<usize> {
+        self.satellites.as_ref().map(|satellites| satellites.len())
+    }
+}
+
+/// Difference between two consecutive F records
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstellationChange {
+    pub time: Time,
+    pub added: Vec<SatelliteId>,
+    pub removed: Vec<SatelliteId>,
+    pub count: usize,
+}
+
+/// Consecutive fixes with fewer satellites than the threshold
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowSatellitePeriod {
+    pub start: Time,
+    pub end: Time,
+    pub fix_count: usize,
+    pub min_satellites: usize,
+}
+
+/// Consecutive fixes without a valid GPS altitude (`gps_alt: None`), with the satellites in use during the gap
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidityGap {
+    pub start: Time,
+    pub end: Time,
+    pub fix_count: usize,
+    /// Fewest satellites in use during the gap, `None` if no F record preceded it
+    pub min_satellites: Option<usize>,
+    /// Whether the satellite count was below the threshold at some point during the gap
+    pub low_satellites: bool,
+}
+
+/// Analysis of the satellites in use over the flight
+///
+/// Records that failed to parse are ignored
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::satellites::SatelliteAnalysis;
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let analysis = SatelliteAnalysis::from_igc_file(&IGCFile::parse(&file).unwrap());
+/// for period in analysis.low_satellite_periods(4) {
+///     println!("{:?} - {:?}: {} satellites", period.start, period.end, period.min_satellites)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SatelliteAnalysis {
+    /// F records in file order with the seconds since the first record
+    records: Vec<(u32, Time, Vec<SatelliteId>)>,
+    fixes: Vec<(u32, Time, bool)>,
+}
+
+impl SatelliteAnalysis {
+    pub fn new(satellites: &[Satellite], fixes: &[Fix]) -> Self {
+        // times are counted from the earlier of the first F record and the first fix
+        let start = match (satellites.first(), fixes.first()) {
+            (Some(satellite), Some(fix)) => {
+                if seconds_since(&satellite.time, &fix.timestamp) < 43200 { satellite.time.clone() } else { fix.timestamp.clone() }
+            }
+            (Some(satellite), None) => satellite.time.clone(),
+            (None, Some(fix)) => fix.timestamp.clone(),
+            (None, None) => Time { h: 0, m: 0, s: 0 },
+        };
+        let records = satellites
+            .iter()
+            .map(|satellite| (seconds_since(&start, &satellite.time), satellite.time.clone(), satellite.satellites()))
+            .collect();
+        let fixes = fixes
+            .iter()
+            .map(|fix| (seconds_since(&start, &fix.timestamp), fix.timestamp.clone(), fix.gps_alt.is_some()))
+            .collect();
+        Self { records, fixes }
+    }
+
+    pub fn from_igc_file(igc_file: &IGCFile) -> Self {
+        let satellites = igc_file.get_satellite_vec().iter().filter_map(|satellite| satellite.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        Self::new(&satellites, &fixes)
+    }
+
+    /// Satellites in use for every fix
+    pub fn satellites_per_fix(&self) -> Vec<FixSatellites> {
+        let mut record = 0;
+        self.fixes
+            .iter()
+            .map(|(seconds, time, gps_valid)| {
+                while record < self.records.len() && self.records[record].0 <= *seconds {
+                    record += 1;
+                }
+                FixSatellites {
+                    time: time.clone(),
+                    satellites: record.checked_sub(1).map(|record| self.records[record].2.clone()),
+                    gps_valid: *gps_valid,
+                }
+            })
+            .collect()
+    }
+
+    /// Satellites that were added or removed between consecutive F records, records without a change are skipped
+    pub fn constellation_changes(&self) -> Vec<ConstellationChange> {
+        self.records
+            .windows(2)
+            .filter_map(|window| {
+                let previous = window[0].2.iter().collect::<BTreeSet<_>>();
+                let current = window[1].2.iter().collect::<BTreeSet<_>>();
+                let added = current.difference(&previous).map(|&&id| id).collect::<Vec<_>>();
+                let removed = previous.difference(&current).map(|&&id| id).collect::<Vec<_>>();
+                (!added.is_empty() || !removed.is_empty()).then(|| ConstellationChange {
+                    time: window[1].1.clone(),
+                    added,
+                    removed,
+                    count: current.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// Periods of consecutive fixes where fewer than `min_satellites` were in use
+    pub fn low_satellite_periods(&self, min_satellites: usize) -> Vec<LowSatellitePeriod> {
+        let mut periods: Vec<LowSatellitePeriod> = Vec::new();
+        let mut in_period = false;
+        for fix in self.satellites_per_fix() {
+            match fix.count() {
+                Some(count) if count < min_satellites => {
+                    match periods.last_mut() {
+                        Some(period) if in_period => {
+                            period.end = fix.time;
+                            period.fix_count += 1;
+                            period.min_satellites = period.min_satellites.min(count);
+                        }
+                        _ => periods.push(LowSatellitePeriod { start: fix.time.clone(), end: fix.time, fix_count: 1, min_satellites: count }),
+                    }
+                    in_period = true;
+                }
+                _ => in_period = false,
+            }
+        }
+        periods
+    }
+
+    /// Periods of consecutive fixes without a valid GPS altitude, correlated with the satellites in use
+    pub fn validity_gaps(&self, min_satellites: usize) -> Vec<ValidityGap> {
+        let mut gaps: Vec<ValidityGap> = Vec::new();
+        let mut in_gap = false;
+        for fix in self.satellites_per_fix() {
+            if fix.gps_valid {
+                in_gap = false;
+                continue;
+            }
+            let count = fix.count();
+            match gaps.last_mut() {
+                Some(gap) if in_gap => {
+                    gap.end = fix.time;
+                    gap.fix_count += 1;
+                    gap.min_satellites = match (gap.min_satellites, count) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                }
+                _ => gaps.push(ValidityGap { start: fix.time.clone(), end: fix.time, fix_count: 1, min_satellites: count, low_satellites: false }),
+            }
+            if let Some(gap) = gaps.last_mut() {
+                gap.low_satellites |= count.is_some_and(|count| count < min_satellites);
+            }
+            in_gap = true;
+        }
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analysis(lines: &str) -> SatelliteAnalysis {
+        SatelliteAnalysis::from_igc_file(&IGCFile::parse(lines).unwrap())
+    }
+
+    const CONTENT: &str = "F120000010203040506
+B1200005107126N00149300WA002880042919509020
+B1200055107126N00149300WA002880042919509020
+F12000901020304
+B1200105107126N00149300WV002880042919509020
+B1200155107126N00149300WV002880042919509020
+F120018010203040506
+B1200205107126N00149300WA002880042919509020";
+
+    #[test]
+    fn satellites_per_fix() {
+        let per_fix = analysis(CONTENT).satellites_per_fix();
+        assert_eq!(per_fix.iter().map(|fix| fix.count()).collect::<Vec<_>>(), vec![Some(6), Some(6), Some(4), Some(4), Some(6)]);
+
+        let per_fix = analysis("B1200005107126N00149300WA002880042919509020\nF12000101").satellites_per_fix();
+        assert_eq!(per_fix[0].count(), None);
+    }
+
+    #[test]
+    fn constellation_changes() {
+        let changes = analysis(CONTENT).constellation_changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].removed.iter().map(|id| id.prn).collect::<Vec<_>>(), vec![5, 6]);
+        assert!(changes[0].added.is_empty());
+        assert_eq!(changes[1].added.len(), 2);
+        assert_eq!(changes[1].count, 6);
+    }
+
+    #[test]
+    fn low_satellite_periods() {
+        let periods = analysis(CONTENT).low_satellite_periods(5);
+        assert_eq!(periods, vec![LowSatellitePeriod {
+            start: Time::from_hms(12, 0, 10).unwrap(),
+            end: Time::from_hms(12, 0, 15).unwrap(),
+            fix_count: 2,
+            min_satellites: 4,
+        }]);
+        assert!(analysis(CONTENT).low_satellite_periods(4).is_empty());
+    }
+
+    #[test]
+    fn validity_gaps() {
+        let gaps = analysis(CONTENT).validity_gaps(5);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].fix_count, 2);
+        assert_eq!(gaps[0].min_satellites, Some(4));
+        assert!(gaps[0].low_satellites);
+        assert!(!analysis(CONTENT).validity_gaps(4)[0].low_satellites);
+    }
+
+    #[test]
+    fn across_midnight() {
+        let content = "F235950010203\nB2359555107126N00149300WA002880042919509020\nF00000001\nB0000055107126N00149300WA002880042919509020";
+        let per_fix = analysis(content).satellites_per_fix();
+        assert_eq!(per_fix.iter().map(|fix| fix.count()).collect::<Vec<_>>(), vec![Some(3), Some(1)]);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::analysis::seconds_since;
+use crate::igc_file::IGCFile;
+use crate::records::data_fix::DataFix;
+use crate::records::extension::Extension;
+use crate::records::fix::Fix;
+use crate::records::util::Time;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// How a wind estimate was obtained
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindMethod {
+    /// Drift of the glider over a full thermalling circle
+    CirclingDrift,
+    /// Difference between the ground velocity and the air velocity from true airspeed and heading
+    Vector,
+}
+
+/// A single wind estimate
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindEstimate {
+    pub time: Time,
+    /// Mean altitude in meters over the estimate, GPS altitude if valid and pressure altitude otherwise
+    pub altitude: i16,
+    /// Speed in km/h
+    pub speed: f64,
+    /// Direction the wind blows from in degrees clockwise from true north
+    pub direction: f64,
+    pub method: WindMethod,
+}
+
+impl WindEstimate {
+    fn from_vector(time: Time, altitude: i16, (east, north): (f64, f64), method: WindMethod) -> Self {
+        let speed = east.hypot(north) * 3.6;
+        let direction = (east.atan2(north).to_degrees() + 180.).rem_euclid(360.);
+        Self { time, altitude, speed, direction, method }
+    }
+
+    /// The velocity the wind blows towards as (east, north) in m/s
+    pub fn velocity(&self) -> (f64, f64) {
+        let towards = (self.direction + 180.).to_radians();
+        let speed = self.speed / 3.6;
+        (speed * towards.sin(), speed * towards.cos())
+    }
+}
+
+/// True airspeed and heading at a point in time, from B or K record extensions
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AirData {
+    pub time: Time,
+    /// True airspeed in km/h
+    pub true_airspeed: f64,
+    /// True heading in degrees
+    pub heading: f64,
+}
+
+impl AirData {
+    /// Air data from the `TAS` and `HDT` extensions of B records declared in the I record
+    pub fn from_fixes(fixes: &[Fix], i_record: &Extension) -> Vec<Self> {
+        fixes
+            .iter()
+            .filter_map(|fix| Self::new(&fix.timestamp, fix.get_extension_value(i_record, "TAS")?, fix.get_extension_value(i_record, "HDT")?))
+            .collect()
+    }
+
+    /// Air data from the `TAS` and `HDT` extensions of K records declared in the J record
+    pub fn from_data_fixes(data_fixes: &[DataFix], j_record: &Extension) -> Vec<Self> {
+        data_fixes
+            .iter()
+            .filter_map(|data_fix| Self::new(&data_fix.time, data_fix.get_extension_value(j_record, "TAS")?, data_fix.get_extension_value(j_record, "HDT")?))
+            .collect()
+    }
+
+    fn new(time: &Time, true_airspeed: i32, heading: i32) -> Option<Self> {
+        Some(Self { time: time.clone(), true_airspeed: true_airspeed as f64, heading: heading as f64 })
+    }
+
+    /// Velocity through the air as (east, north) in m/s
+    fn velocity(&self) -> (f64, f64) {
+        let speed = self.true_airspeed / 3.6;
+        (speed * self.heading.to_radians().sin(), speed * self.heading.to_radians().cos())
+    }
+}
+
+/// The mean wind in an altitude band or time interval
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindBand {
+    /// Lower bound, inclusive, in meters or seconds since midnight
+    pub from: i32,
+    /// Upper bound, exclusive, in meters or seconds since midnight
+    pub to: i32,
+    /// Speed in km/h of the vector mean of the estimates
+    pub speed: f64,
+    /// Direction the wind blows from in degrees clockwise from true north
+    pub direction: f64,
+    pub samples: usize,
+}
+
+/// Estimates the wind from a series of fixes
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::analysis::wind::{altitude_bands, WindEstimator};
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let estimates = WindEstimator::default().estimate_file(&IGCFile::parse(&file).unwrap());
+/// for band in altitude_bands(&estimates, 500) {
+///     println!("{}-{}m: {:.0}° {:.0}km/h", band.from, band.to, band.direction, band.speed);
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindEstimator {
+    /// Minimum turn rate in degrees per second for the glider to count as circling
+    pub min_turn_rate: f64,
+    /// Circles taking longer than this many seconds are discarded
+    pub max_circle_duration: u32,
+    /// Vector estimates are averaged over windows of this many seconds
+    pub vector_window: u32,
+}
+
+impl Default for WindEstimator {
+    fn default() -> Self {
+        Self { min_turn_rate: 4., max_circle_duration: 60, vector_window: 60 }
+    }
+}
+
+impl WindEstimator {
+    /// Runs both methods over a parsed file, sorted by time, records that failed to parse are ignored
+    ///
+    /// Air data for the vector method is taken from the B records if the I record declares `TAS` and `HDT`, otherwise from the K records
+    pub fn estimate_file(&self, igc_file: &IGCFile) -> Vec<WindEstimate> {
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+        let mut air_data = match igc_file.get_i_extension_vec().iter().find_map(|i_record| i_record.as_ref().ok()) {
+            Some(i_record) => AirData::from_fixes(&fixes, i_record),
+            None => Vec::new(),
+        };
+        if air_data.is_empty() {
+            if let Some(j_record) = igc_file.get_j_extension_vec().iter().find_map(|j_record| j_record.as_ref().ok()) {
+                let data_fixes = igc_file.get_data_fix_vec().iter().filter_map(|data_fix| data_fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+                air_data = AirData::from_data_fixes(&data_fixes, j_record);
+            }
+        }
+
+        let mut estimates = self.circling(&fixes);
+        estimates.extend(self.vector(&fixes, &air_data));
+        if let Some(first) = fixes.first() {
+            estimates.sort_by_key(|estimate| seconds_since(&first.timestamp, &estimate.time));
+        }
+        estimates
+    }
+
+    /// One estimate per full circle from the drift between the start and the end of the circle
+    pub fn circling(&self, fixes: &[Fix]) -> Vec<WindEstimate> {
+        let mut estimates = Vec::new();
+        // index of the fix the current circle started at and the heading change since
+        let mut circle: Option<(usize, f64)> = None;
+        let mut previous: Option<(usize, f64)> = None;
+        for i in 1..fixes.len() {
+            let (from, to) = (&fixes[i - 1], &fixes[i]);
+            if from.coordinates == to.coordinates { continue }
+            let heading = from.coordinates.bearing_to(&to.coordinates);
+            let Some((previous_start, previous_heading)) = previous.replace((i - 1, heading)) else { continue };
+
+            let dt = seconds_since(&fixes[previous_start].timestamp, &from.timestamp).max(1) as f64;
+            let turn = (heading - previous_heading + 540.).rem_euclid(360.) - 180.;
+            circle = match circle {
+                _ if turn.abs() / dt < self.min_turn_rate => None,
+                Some((start, total)) if total.signum() == turn.signum() => Some((start, total + turn)),
+                _ => Some((previous_start, turn)),
+            };
+
+            if let Some((start, total)) = circle {
+                if total.abs() < 360. { continue }
+                let duration = seconds_since(&fixes[start].timestamp, &fixes[i - 1].timestamp);
+                if duration > 0 && duration <= self.max_circle_duration {
+                    let (start_fix, end_fix) = (&fixes[start], &fixes[i - 1]);
+                    let distance = start_fix.coordinates.distance_to(&end_fix.coordinates);
+                    let bearing = start_fix.coordinates.bearing_to(&end_fix.coordinates).to_radians();
+                    let drift = (distance * bearing.sin() / duration as f64, distance * bearing.cos() / duration as f64);
+                    let altitude = mean_altitude(&fixes[start..i]);
+                    let time = seconds_since(&start_fix.timestamp, &end_fix.timestamp) / 2 + start_fix.timestamp.seconds_since_midnight();
+                    let time = Time::from_seconds_since_midnight(time % 86400).unwrap_or_else(|_| start_fix.timestamp.clone());
+                    estimates.push(WindEstimate::from_vector(time, altitude, drift, WindMethod::CirclingDrift));
+                }
+                circle = Some((i - 1, total - 360. * total.signum()));
+            }
+        }
+        estimates
+    }
+
+    /// One estimate per `vector_window` seconds from the ground velocity minus the air velocity
+    pub fn vector(&self, fixes: &[Fix], air_data: &[AirData]) -> Vec<WindEstimate> {
+        let Some(first) = fixes.first() else { return Vec::new() };
+        let fix_times = fixes.iter().map(|fix| seconds_since(&first.timestamp, &fix.timestamp)).collect::<Vec<_>>();
+
+        // (seconds since the first fix, altitude, wind velocity) for each air data sample inside the track
+        let samples = air_data.iter().filter_map(|air| {
+            let time = seconds_since(&first.timestamp, &air.time);
+            if time > *fix_times.last()? { return None }
+            let i = fix_times.partition_point(|&fix_time| fix_time <= time).clamp(1, fixes.len().max(2) - 1);
+            let (from, to) = (fixes.get(i - 1)?, fixes.get(i)?);
+            let dt = fix_times[i].checked_sub(fix_times[i - 1]).filter(|&dt| dt > 0)? as f64;
+            let distance = from.coordinates.distance_to(&to.coordinates);
+            let bearing = from.coordinates.bearing_to(&to.coordinates).to_radians();
+            let (air_east, air_north) = air.velocity();
+            let wind = (distance * bearing.sin() / dt - air_east, distance * bearing.cos() / dt - air_north);
+            Some((time, altitude(to), wind))
+        });
+
+        let mut estimates = Vec::new();
+        let mut window: Vec<(u32, i16, (f64, f64))> = Vec::new();
+        let window_length = self.vector_window.max(1);
+        for sample in samples {
+            if window.first().is_some_and(|&(start, _, _)| sample.0 / window_length != start / window_length) {
+                estimates.push(vector_estimate(&first.timestamp, &window));
+                window.clear();
+            }
+            window.push(sample);
+        }
+        if !window.is_empty() {
+            estimates.push(vector_estimate(&first.timestamp, &window));
+        }
+        estimates
+    }
+}
+
+fn vector_estimate(reference: &Time, window: &[(u32, i16, (f64, f64))]) -> WindEstimate {
+    let n = window.len() as f64;
+    let time = (window[0].0 + window[window.len() - 1].0) / 2 + reference.seconds_since_midnight();
+    let time = Time::from_seconds_since_midnight(time % 86400).unwrap_or_else(|_| reference.clone());
+    let altitude = (window.iter().map(|(_, altitude, _)| *altitude as f64).sum::<f64>() / n).round() as i16;
+    let east = window.iter().map(|(_, _, (east, _))| east).sum::<f64>() / n;
+    let north = window.iter().map(|(_, _, (_, north))| north).sum::<f64>() / n;
+    WindEstimate::from_vector(time, altitude, (east, north), WindMethod::Vector)
+}
+
+fn altitude(fix: &Fix) -> i16 {
+    fix.gps_alt.unwrap_or(fix.pressure_alt)
+}
+
+fn mean_altitude(fixes: &[Fix]) -> i16 {
+    (fixes.iter().map(|fix| altitude(fix) as f64).sum::<f64>() / fixes.len().max(1) as f64).round() as i16
+}
+
+/// Vector mean of the estimates in altitude bands of `band_height` meters, lowest band first
+pub fn altitude_bands(estimates: &[WindEstimate], band_height: u16) -> Vec<WindBand> {
+    bands(estimates, band_height.max(1) as i32, |estimate| estimate.altitude as i32)
+}
+
+/// Vector mean of the estimates in time intervals of `interval` seconds since midnight, earliest interval first
+pub fn time_intervals(estimates: &[WindEstimate], interval: u32) -> Vec<WindBand> {
+    bands(estimates, interval.max(1) as i32, |estimate| estimate.time.seconds_since_midnight() as i32)
+}
+
+fn bands(estimates: &[WindEstimate], size: i32, key: impl Fn(&WindEstimate) -> i32) -> Vec<WindBand> {
+    let mut bands: Vec<(i32, usize, (f64, f64))> = Vec::new();
+    for estimate in estimates {
+        let from = key(estimate).div_euclid(size) * size;
+        let (east, north) = estimate.velocity();
+        match bands.iter_mut().find(|(band_from, _, _)| *band_from == from) {
+            Some((_, samples, sum)) => {
+                *samples += 1;
+                *sum = (sum.0 + east, sum.1 + north);
+            }
+            None => bands.push((from, 1, (east, north))),
+        }
+    }
+    bands.sort_by_key(|(from, _, _)| *from);
+    bands
+        .into_iter()
+        .map(|(from, samples, (east, north))| {
+            let (east, north) = (-east / samples as f64, -north / samples as f64);
+            let direction = east.atan2(north).to_degrees().rem_euclid(360.);
+            WindBand { from, to: from + size, speed: east.hypot(north) * 3.6, direction, samples }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METERS_PER_DEGREE: f64 = 111_195.;
+
+    /// Latitude and longitude of a point `east` and `north` meters from 50N 10E
+    fn position(east: f64, north: f64) -> (f64, f64) {
+        (50. + north / METERS_PER_DEGREE, 10. + east / (METERS_PER_DEGREE * 50f64.to_radians().cos()))
+    }
+
+    /// Circles of 150m radius taking 30s each while drifting 20km/h towards the east
+    fn circling_flight() -> Vec<Fix> {
+        (0..=160)
+            .step_by(2)
+            .map(|t| {
+                let angle = (t as f64 / 30. * 360.).to_radians();
+                let drift = 20. / 3.6 * t as f64;
+                let (latitude, longitude) = position(150. * angle.sin() + drift, 150. * angle.cos());
+                Fix::at(43200 + t, latitude, longitude, 1000 + t as i16)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn circling_drift() {
+        let estimates = WindEstimator::default().circling(&circling_flight());
+        assert_eq!(estimates.len(), 5);
+        for estimate in &estimates {
+            assert!((estimate.speed - 20.).abs() < 1., "{estimate:?}");
+            assert!((estimate.direction - 270.).abs() < 3., "{estimate:?}");
+            assert_eq!(estimate.method, WindMethod::CirclingDrift);
+        }
+        assert_eq!(estimates[0].time, Time::from_hms(12, 0, 15).unwrap());
+    }
+
+    #[test]
+    fn straight_flight_has_no_circles() {
+        let fixes = (0..100)
+            .map(|t| {
+                let (latitude, longitude) = position(0., 30. * t as f64);
+                Fix::at(43200 + t, latitude, longitude, 1000)
+            })
+            .collect::<Vec<_>>();
+        assert!(WindEstimator::default().circling(&fixes).is_empty());
+    }
+
+    #[test]
+    fn vector_method() {
+        // heading north at 100km/h true airspeed with 20km/h of wind from the west
+        let i_record = Extension::parse("I023638TAS3941HDT").unwrap();
+        let fixes = (0..120)
+            .map(|t| {
+                let (latitude, longitude) = position(20. / 3.6 * t as f64, 100. / 3.6 * t as f64);
+                Fix { extension: "100000".into(), ..Fix::at(43200 + t, latitude, longitude, 1500) }
+            })
+            .collect::<Vec<_>>();
+        let air_data = AirData::from_fixes(&fixes, &i_record);
+        assert_eq!(air_data.len(), 120);
+        let estimates = WindEstimator::default().vector(&fixes, &air_data);
+        assert_eq!(estimates.len(), 2);
+        for estimate in &estimates {
+            assert!((estimate.speed - 20.).abs() < 1., "{estimate:?}");
+            assert!((estimate.direction - 270.).abs() < 3., "{estimate:?}");
+            assert_eq!(estimate.altitude, 1500);
+        }
+    }
+
+    #[test]
+    fn from_file_and_bands() {
+        let mut content = vec!["AXXXABC".to_string(), "J020810TAS1113HDT".to_string()];
+        content.extend(circling_flight().iter().map(Fix::to_string));
+        let igc_file = IGCFile::parse(&content.join("\n")).unwrap();
+        let estimates = WindEstimator::default().estimate_file(&igc_file);
+        assert_eq!(estimates.len(), 5);
+
+        let bands = altitude_bands(&estimates, 100);
+        assert_eq!(bands.len(), 2);
+        assert_eq!((bands[0].from, bands[0].to, bands[0].samples), (1000, 1100, 3));
+        assert!((bands[0].speed - 20.).abs() < 1.);
+        assert!((bands[0].direction - 270.).abs() < 3.);
+
+        let intervals = time_intervals(&estimates, 3600);
+        assert_eq!(intervals.len(), 1);
+        assert_eq!((intervals[0].from, intervals[0].samples), (43200, 5));
+    }
+}
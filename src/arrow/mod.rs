@@ -0,0 +1,244 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::types::Int32Type;
+use arrow_array::{ArrayRef, BooleanArray, DictionaryArray, Float64Array, Int32Array, RecordBatch, StringArray, Time32SecondArray, TimestampSecondArray};
+use arrow_buffer::NullBuffer;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+
+use crate::error::IGCError::ArrowExportError;
+use crate::igc_file::IGCFile;
+use crate::metadata::FlightMetadata;
+use crate::track::columns::FixColumns;
+use crate::{Result, StrWrapper};
+
+/// A flight to export with the name it is stored under
+#[derive(Debug, Clone)]
+pub struct Flight {
+    /// Written to the `flight_id` column and used in the keys of the Parquet metadata
+    pub id: String,
+    pub columns: FixColumns,
+    pub metadata: FlightMetadata,
+}
+
+impl Flight {
+    pub fn from_igc_file(id: &str, igc_file: &IGCFile) -> Self {
+        Self { id: id.to_string(), columns: FixColumns::from_igc_file(igc_file), metadata: FlightMetadata::from_igc_file(igc_file) }
+    }
+}
+
+/// Schema of the fixes, the extensions are nullable `Int32` columns named by their three letter code
+///
+/// `timestamp` is null when the date of the flight is not known, `time` is the UTC time of day of the B record
+/// and `gps_altitude` is null for fixes without a valid GPS altitude
+pub fn schema(extension_codes: &[&str], flight_id: bool) -> Schema {
+    let mut fields = Vec::with_capacity(8 + extension_codes.len());
+    if flight_id {
+        fields.push(Field::new_dictionary("flight_id", DataType::Int32, DataType::Utf8, false));
+    }
+    fields.extend([
+        Field::new("timestamp", DataType::Timestamp(TimeUnit::Second, Some("UTC".into())), true),
+        Field::new("time", DataType::Time32(TimeUnit::Second), false),
+        Field::new("latitude", DataType::Float64, false),
+        Field::new("longitude", DataType::Float64, false),
+        Field::new("pressure_altitude", DataType::Int32, false),
+        Field::new("gps_altitude", DataType::Int32, true),
+        Field::new("gps_valid", DataType::Boolean, false),
+    ]);
+    fields.extend(extension_codes.iter().map(|code| Field::new(*code, DataType::Int32, true)));
+    Schema::new(fields)
+}
+
+impl FixColumns {
+    /// The fixes as one record batch, the vectors of the columns are moved into the arrays without copying
+    /// # examples
+    /// ```rust
+    /// use std::fs;
+    /// use igc_parser::track::columns::FixColumns;
+    /// let columns = FixColumns::parse(&fs::read_to_string("./examples/example.igc").unwrap());
+    /// let batch = columns.into_record_batch().unwrap();
+    /// assert_eq!(batch.num_rows(), 5825);
+    /// assert!(batch.column_by_name("FXA").is_some());
+    /// ```
+    pub fn into_record_batch(self) -> Result<RecordBatch> {
+        let codes = self.extensions.iter().map(|column| column.code.clone()).collect::<Vec<_>>();
+        let schema = Arc::new(schema(&codes.iter().map(|code| code.as_ref()).collect::<Vec<_>>(), false));
+        RecordBatch::try_new(schema, fix_arrays(self, &codes)).map_err(|error| ArrowExportError(error.to_string()))
+    }
+
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.clone().into_record_batch()
+    }
+}
+
+/// The fixes of every flight in one record batch with a `flight_id` column, the extensions of all the flights are included
+pub fn record_batch(flights: &[Flight]) -> Result<RecordBatch> {
+    let batches = FlightBatches::new(flights);
+    let flight_batches = (0..flights.len()).map(|index| batches.batch(index)).collect::<Result<Vec<_>>>()?;
+    arrow_select::concat::concat_batches(&batches.schema, &flight_batches).map_err(|error| ArrowExportError(error.to_string()))
+}
+
+/// Writes the flights to a Parquet file, one row group per flight, with the metadata of each flight in the key-value
+/// metadata as `igc.<flight id>.<field>`, e.g. `igc.1234.pilot`
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::arrow::{write_parquet, Flight};
+/// use igc_parser::igc_file::IGCFile;
+/// let igc_file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let mut parquet = Vec::new();
+/// write_parquet(&mut parquet, &[Flight::from_igc_file("example", &igc_file)]).unwrap();
+/// ```
+pub fn write_parquet<W: Write + Send>(writer: W, flights: &[Flight]) -> Result<()> {
+    let key_values = flights.iter().flat_map(|flight| {
+        metadata_fields(&flight.metadata).into_iter().map(|(field, value)| KeyValue::new(format!("igc.{}.{field}", flight.id), value))
+    }).collect();
+    let properties = WriterProperties::builder().set_key_value_metadata(Some(key_values)).build();
+    let batches = FlightBatches::new(flights);
+    let parquet_error = |error: parquet::errors::ParquetError| ArrowExportError(error.to_string());
+    let mut writer = ArrowWriter::try_new(writer, batches.schema.clone(), Some(properties)).map_err(parquet_error)?;
+    for index in 0..flights.len() {
+        writer.write(&batches.batch(index)?).map_err(parquet_error)?;
+        writer.flush().map_err(parquet_error)?;
+    }
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+/// Batches of one flight each with the schema shared by all the flights
+struct FlightBatches<'a> {
+    flights: &'a [Flight],
+    schema: SchemaRef,
+    /// Every extension code of the flights in the order they first appear
+    codes: Vec<StrWrapper>,
+    ids: Arc<StringArray>,
+}
+
+impl<'a> FlightBatches<'a> {
+    fn new(flights: &'a [Flight]) -> Self {
+        let mut codes: Vec<StrWrapper> = Vec::new();
+        for column in flights.iter().flat_map(|flight| &flight.columns.extensions) {
+            if !codes.contains(&column.code) { codes.push(column.code.clone()) }
+        }
+        let schema = Arc::new(schema(&codes.iter().map(|code| code.as_ref()).collect::<Vec<_>>(), true));
+        let ids = Arc::new(StringArray::from_iter_values(flights.iter().map(|flight| flight.id.as_str())));
+        Self { flights, schema, codes, ids }
+    }
+
+    fn batch(&self, index: usize) -> Result<RecordBatch> {
+        let columns = &self.flights[index].columns;
+        let keys = Int32Array::from(vec![index as i32; columns.len()]);
+        let flight_id = DictionaryArray::<Int32Type>::try_new(keys, self.ids.clone()).map_err(|error| ArrowExportError(error.to_string()))?;
+        let mut arrays: Vec<ArrayRef> = vec![Arc::new(flight_id)];
+        arrays.extend(fix_arrays(columns.clone(), &self.codes));
+        RecordBatch::try_new(self.schema.clone(), arrays).map_err(|error| ArrowExportError(error.to_string()))
+    }
+}
+
+/// The arrays of the fix columns in the order of `schema`, extensions the flight does not have are all null
+fn fix_arrays(columns: FixColumns, codes: &[StrWrapper]) -> Vec<ArrayRef> {
+    let length = columns.len();
+    let time = columns.timestamps.iter().map(|timestamp| timestamp.rem_euclid(86400) as i32).collect::<Vec<_>>();
+    let timestamp = match columns.date {
+        Some(_) => TimestampSecondArray::from(columns.timestamps),
+        None => TimestampSecondArray::new_null(length),
+    };
+    let gps_valid = NullBuffer::from(columns.gps_valid.clone());
+    let mut arrays: Vec<ArrayRef> = vec![
+        Arc::new(timestamp.with_timezone("UTC")),
+        Arc::new(Time32SecondArray::from(time)),
+        Arc::new(Float64Array::from(columns.latitudes)),
+        Arc::new(Float64Array::from(columns.longitudes)),
+        Arc::new(Int32Array::from(columns.pressure_altitudes)),
+        Arc::new(Int32Array::new(columns.gps_altitudes.into(), Some(gps_valid))),
+        Arc::new(BooleanArray::from(columns.gps_valid)),
+    ];
+    let mut extensions = columns.extensions;
+    for code in codes {
+        arrays.push(match extensions.iter().position(|column| &column.code == code) {
+            Some(index) => {
+                let column = extensions.swap_remove(index);
+                Arc::new(Int32Array::new(column.values.into(), Some(NullBuffer::from(column.valid))))
+            }
+            None => Arc::new(Int32Array::new_null(length)),
+        });
+    }
+    arrays
+}
+
+/// The fields of the metadata that are known, with dates as ISO 8601
+fn metadata_fields(metadata: &FlightMetadata) -> Vec<(&'static str, String)> {
+    let mut fields = Vec::new();
+    if let Some(date) = &metadata.date { fields.push(("date", date.to_iso8601())) }
+    fields.extend(metadata.text_fields().into_iter().filter_map(|(field, value)| Some((field, value?.to_string()))));
+    if let Some(accuracy) = metadata.fix_accuracy { fields.push(("fix_accuracy", accuracy.to_string())) }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use crate::records::util::Coordinate;
+    use crate::synthetic::{ExtensionChannel, FlightGenerator};
+
+    fn example() -> Flight {
+        Flight::from_igc_file("example", &IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap())
+    }
+
+    fn synthetic() -> Flight {
+        let flight = FlightGenerator::new(Coordinate::from_decimal_degrees(56., 9.), 50.)
+            .with_extension(ExtensionChannel::ENL)
+            .ground(60)
+            .launch(300.)
+            .land_at(Coordinate::from_decimal_degrees(56.01, 9.))
+            .generate();
+        Flight::from_igc_file("synthetic", &IGCFile::parse(&flight.document.to_string()).unwrap())
+    }
+
+    #[test]
+    fn single_flight_batch() {
+        let flight = example();
+        let batch = flight.columns.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), flight.columns.len());
+        assert_eq!(batch.schema().field_with_name("timestamp").unwrap().data_type(), &DataType::Timestamp(TimeUnit::Second, Some("UTC".into())));
+        let timestamps = batch.column_by_name("timestamp").unwrap().as_any().downcast_ref::<TimestampSecondArray>().unwrap();
+        assert_eq!(timestamps.value(0), flight.columns.timestamps[0]);
+        let gps_altitudes = batch.column_by_name("gps_altitude").unwrap();
+        assert_eq!(gps_altitudes.null_count(), flight.columns.gps_valid.iter().filter(|valid| !**valid).count());
+    }
+
+    #[test]
+    fn batch_of_flights_has_every_extension() {
+        let flights = [example(), synthetic()];
+        let batch = record_batch(&flights).unwrap();
+        assert_eq!(batch.num_rows(), flights[0].columns.len() + flights[1].columns.len());
+        let names = batch.schema().fields().iter().map(|field| field.name().clone()).collect::<Vec<_>>();
+        assert_eq!(&names[names.len() - 3..], ["FXA", "SIU", "ENL"]);
+        let engine_noise = batch.column_by_name("ENL").unwrap();
+        assert_eq!(engine_noise.null_count(), flights[0].columns.len());
+        let flight_id = batch.column_by_name("flight_id").unwrap().as_any().downcast_ref::<DictionaryArray<Int32Type>>().unwrap();
+        assert_eq!(flight_id.keys().value(batch.num_rows() - 1), 1);
+    }
+
+    #[test]
+    fn parquet_with_metadata() {
+        let flights = [example(), synthetic()];
+        let path = std::env::temp_dir().join(format!("igc_parser_flights_{}.parquet", std::process::id()));
+        write_parquet(fs::File::create(&path).unwrap(), &flights).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&path).unwrap()).unwrap();
+        let key_values = reader.metadata().file_metadata().key_value_metadata().unwrap().clone();
+        let value = |key: &str| key_values.iter().find(|key_value| key_value.key == key).and_then(|key_value| key_value.value.clone());
+        assert_eq!(value("igc.example.registration").as_deref(), Some("OY-EXD"));
+        assert_eq!(value("igc.example.date").as_deref(), Some("2020-08-06"));
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let rows = reader.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum::<usize>();
+        assert_eq!(rows, flights[0].columns.len() + flights[1].columns.len());
+        fs::remove_file(path).unwrap();
+    }
+}
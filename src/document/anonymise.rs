@@ -0,0 +1,266 @@
+use crate::document::IGCDocument;
+use crate::records::file_header::{FileHeader, FileHeaderRecord};
+use crate::records::flight_recorder_id::FlightRecorderID;
+use crate::records::task_info::{TaskInfo, TaskPoint};
+use crate::records::util::{Coordinate, Date};
+use crate::records::Record;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Headers naming the crew or the glider, kept with an empty value when removed since they are mandatory
+const IDENTITY_HEADERS: [&str; 4] = ["PLT", "CM2", "GID", "CID"];
+
+/// Optional headers about the crew or the home airfield, left out unless identities are kept
+const PERSONAL_HEADERS: [&str; 5] = ["DB1", "DB2", "OOI", "CLB", "SIT"];
+
+/// What to do with an identifying value
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    Keep,
+    Remove,
+    /// Replace with a code derived from the value and the salt, so the same pilot gets the same code in every file
+    Pseudonymise,
+}
+
+/// Removes identities and home locations from a document before publishing it
+///
+/// G records are always removed since the file no longer matches its signature.
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::document::IGCDocument;
+/// use igc_parser::document::anonymise::{Anonymiser, Redaction};
+/// let document = IGCDocument::parse(&fs::read_to_string("./examples/example.igc").unwrap());
+/// let anonymiser = Anonymiser { identities: Redaction::Pseudonymise, salt: "dataset 2020".into(), date_shift: -30, ..Anonymiser::default() };
+/// let anonymised = anonymiser.anonymise(&document).to_string();
+/// assert!(!anonymised.contains("OY-EXD"));
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anonymiser {
+    /// Pilot, co-pilot, glider registration and competition ID headers
+    pub identities: Redaction,
+    /// Serial number of the logger in the A record
+    pub logger_serial: Redaction,
+    /// Secret mixed into pseudonyms so they can not be found by trying known names
+    pub salt: String,
+    /// Days added to every date
+    pub date_shift: i32,
+    /// Degrees added to the latitude and longitude of every position
+    pub offset: (f64, f64),
+    /// Fixes within `home_radius` of this position before the glider first leaves it and after it last returns are removed
+    pub home: Option<Coordinate>,
+    /// Meters around `home` to crop
+    pub home_radius: f64,
+    /// Remove L records, which can contain anything
+    pub drop_comments: bool,
+}
+
+impl Default for Anonymiser {
+    fn default() -> Self {
+        Self {
+            identities: Redaction::Remove,
+            logger_serial: Redaction::Remove,
+            salt: String::new(),
+            date_shift: 0,
+            offset: (0., 0.),
+            home: None,
+            home_radius: 3000.,
+            drop_comments: true,
+        }
+    }
+}
+
+impl Anonymiser {
+    pub fn anonymise(&self, document: &IGCDocument) -> IGCDocument {
+        let near_home = |coordinate: &Coordinate| self.home.as_ref().is_some_and(|home| home.distance_to(coordinate) <= self.home_radius);
+        let away = document.fixes().enumerate().filter(|(_, fix)| !near_home(&fix.coordinates)).map(|(i, _)| i).collect::<Vec<_>>();
+        let (first_away, last_away) = (away.first().copied(), away.last().copied());
+
+        let mut fix_index = 0;
+        let mut records = Vec::with_capacity(document.records.len());
+        for record in &document.records {
+            let record = match record {
+                Record::A(flight_recorder_id) => Record::A(self.flight_recorder_id(flight_recorder_id)),
+                Record::B(fix) => {
+                    fix_index += 1;
+                    match (first_away, last_away) {
+                        (Some(first), Some(last)) if (first..=last).contains(&(fix_index - 1)) => {}
+                        _ => continue,
+                    }
+                    let mut fix = fix.clone();
+                    fix.coordinates = self.translate(&fix.coordinates);
+                    Record::B(fix)
+                }
+                Record::C(TaskInfo::TaskPoint(task_point)) => Record::C(TaskInfo::TaskPoint(match near_home(&task_point.coordinate) {
+                    // takeoff and landing points may be left as zeros
+                    true => TaskPoint { coordinate: Coordinate::from_decimal_degrees(0., 0.), name: None },
+                    false if task_point.coordinate == Coordinate::from_decimal_degrees(0., 0.) => task_point.clone(),
+                    false => TaskPoint { coordinate: self.translate(&task_point.coordinate), name: task_point.name.clone() },
+                })),
+                Record::C(TaskInfo::DeclarationTime(declaration_time)) => {
+                    let mut declaration_time = declaration_time.clone();
+                    declaration_time.date = self.shift(&declaration_time.date);
+                    if let Some(flight_date) = declaration_time.flight_date() {
+                        declaration_time.set_flight_date(&self.shift(&flight_date));
+                    }
+                    Record::C(TaskInfo::DeclarationTime(declaration_time))
+                }
+                Record::E(event) if event.event_type.as_ref() == "DTE" => {
+                    let mut event = event.clone();
+                    if let Some(date) = event.extension.get(0..6).and_then(|date| Date::parse(date).ok()) {
+                        event.extension = format!("{}{}", self.shift(&date), &event.extension[6..]).into();
+                    }
+                    Record::E(event)
+                }
+                Record::G(_) => continue,
+                Record::H(header) => match self.header(header) {
+                    Some(header) => Record::H(header),
+                    None => continue,
+                },
+                Record::L(_) if self.drop_comments => continue,
+                record => record.clone(),
+            };
+            records.push(record);
+        }
+        IGCDocument::from_records(records)
+    }
+
+    fn flight_recorder_id(&self, flight_recorder_id: &FlightRecorderID) -> FlightRecorderID {
+        let id = match self.logger_serial {
+            Redaction::Keep => return flight_recorder_id.clone(),
+            Redaction::Remove => "0".repeat(flight_recorder_id.id.len()),
+            Redaction::Pseudonymise => self.pseudonym(&format!("{}{}", flight_recorder_id.manufacturer, flight_recorder_id.id), flight_recorder_id.id.len()),
+        };
+        FlightRecorderID { id: id.into(), ..flight_recorder_id.clone() }
+    }
+
+    /// `None` if the header should be left out
+    fn header(&self, record: &FileHeaderRecord) -> Option<FileHeaderRecord> {
+        let code = record.header.code();
+        if let FileHeader::Date(date) = &record.header { return record.with_date(&self.shift(date)) }
+        if self.identities == Redaction::Keep { return Some(record.clone()) }
+        if PERSONAL_HEADERS.contains(&code) { return None }
+        if !IDENTITY_HEADERS.contains(&code) || record.raw_value.trim().is_empty() { return Some(record.clone()) }
+        match self.identities {
+            Redaction::Remove => record.with_value(""),
+            // competition IDs are at most three characters
+            _ => record.with_value(&self.pseudonym(record.raw_value.trim(), if code == "CID" { 3 } else { 8 })),
+        }
+    }
+
+    fn shift(&self, date: &Date) -> Date {
+        if self.date_shift == 0 { return date.clone() }
+        Date::from_days_since_epoch(date.days_since_epoch() + self.date_shift as i64)
+    }
+
+    fn translate(&self, coordinate: &Coordinate) -> Coordinate {
+        if self.offset == (0., 0.) { return coordinate.clone() }
+        let (latitude, longitude) = coordinate.to_decimal_degrees();
+        Coordinate::from_decimal_degrees((latitude + self.offset.0).clamp(-90., 90.), (longitude + self.offset.1 + 540.).rem_euclid(360.) - 180.)
+    }
+
+    /// Upper case letters and digits from a FNV-1a hash of the salt and the value, ignoring case and surrounding whitespace
+    fn pseudonym(&self, value: &str, length: usize) -> String {
+        let value = value.trim().to_ascii_uppercase();
+        let mut hash = self.salt.bytes().chain([0]).chain(value.bytes()).fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        (0..length)
+            .map(|_| {
+                let digit = (hash % 36) as u32;
+                hash /= 36;
+                char::from_digit(digit, 36).unwrap_or('0').to_ascii_uppercase()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::fix::Fix;
+
+    /// Takes off from 51°N 1°W, flies 20km north and lands back home
+    fn document() -> IGCDocument {
+        IGCDocument::parse(&[
+            "AXXXABCFLIGHT:1",
+            "HFDTEDATE:010324,01",
+            "HFPLTPILOTINCHARGE:John Doe",
+            "HFCM2CREW2:",
+            "HFGIDGLIDERID:D-1234",
+            "HFCIDCOMPETITIONID:XY",
+            "HFGTYGLIDERTYPE:LS4",
+            "HFDB1PILOTBIRTHDATE:030476",
+            "HFSITSITE:Home Field",
+            "C010324100000010324000101",
+            "C5100000N00100000WHome Field",
+            "C5110000N00100000WTurn",
+            "C5100000N00100000WHome Field",
+            "B1000005100000N00100000WA0010000100",
+            "B1001005100500N00100000WA0050000500",
+            "B1002005110000N00100000WA1000001000",
+            "B1003005100500N00100000WA0050000500",
+            "B1004005100000N00100000WA0010000100",
+            "LXXXPILOT JOHN DOE",
+            "GABCDEF",
+        ].join("\r\n"))
+    }
+
+    #[test]
+    fn removes_identities() {
+        let anonymised = Anonymiser::default().anonymise(&document()).to_string();
+        let lines = anonymised.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "AXXX000FLIGHT:1");
+        assert_eq!(lines[2], "HFPLTPILOTINCHARGE:");
+        assert_eq!(lines[4], "HFGIDGLIDERID:");
+        assert_eq!(lines[6], "HFGTYGLIDERTYPE:LS4");
+        assert!(!anonymised.contains("John") && !anonymised.contains("030476") && !anonymised.contains("SITE"));
+        assert!(!lines.iter().any(|line| line.starts_with('G') || line.starts_with('L')));
+        assert_eq!(lines.len(), 7 + 4 + 5);
+    }
+
+    #[test]
+    fn pseudonyms_are_stable() {
+        let anonymiser = Anonymiser { identities: Redaction::Pseudonymise, logger_serial: Redaction::Pseudonymise, salt: "salt".into(), ..Anonymiser::default() };
+        let anonymised = anonymiser.anonymise(&document());
+        let again = anonymiser.anonymise(&IGCDocument::parse(&document().to_string().replace("John Doe", " JOHN DOE")));
+        assert_eq!(anonymised.to_string(), again.to_string());
+
+        let pilot = match &anonymised.records[2] {
+            Record::H(header) => header.clone(),
+            _ => panic!("expected the pilot header"),
+        };
+        assert!(matches!(&pilot.header, FileHeader::PilotInCharge(pilot) if pilot.len() == 8 && pilot.as_ref() != "JOHN DOE"));
+        assert!(anonymised.to_string().lines().any(|line| line.starts_with("HFCIDCOMPETITIONID:") && line.len() == 22));
+
+        let salted = Anonymiser { salt: "pepper".into(), ..anonymiser.clone() }.anonymise(&document());
+        assert_ne!(salted.to_string(), anonymised.to_string());
+    }
+
+    #[test]
+    fn shifts_dates_and_positions() {
+        let anonymiser = Anonymiser { date_shift: -1, offset: (1., -2.), ..Anonymiser::default() };
+        let anonymised = anonymiser.anonymise(&document());
+        assert_eq!(anonymised.date(), Some(&Date { d: 29, m: 2, y: 24 }));
+        let written = anonymised.to_string();
+        assert!(written.contains("HFDTEDATE:290224,01\r\n"));
+        assert!(written.contains("C290224100000290224000101\r\n"));
+        let fixes = anonymised.fixes().collect::<Vec<&Fix>>();
+        assert_eq!(fixes.len(), 5);
+        assert_eq!(fixes[0].coordinates.to_string(), "5200000N00300000W");
+        assert!(written.contains("C5210000N00300000WTurn\r\n"));
+    }
+
+    #[test]
+    fn crops_home() {
+        let home = Coordinate::from_decimal_degrees(51., -1.);
+        let anonymised = Anonymiser { home: Some(home), home_radius: 500., ..Anonymiser::default() }.anonymise(&document());
+        let fixes = anonymised.fixes().collect::<Vec<_>>();
+        assert_eq!(fixes.len(), 3);
+        assert_eq!(fixes[0].pressure_alt, 500);
+        assert_eq!(fixes[2].pressure_alt, 500);
+        let written = anonymised.to_string();
+        assert_eq!(written.matches("C0000000N00000000E\r\n").count(), 2);
+        assert!(written.contains("C5110000N00100000WTurn\r\n"));
+    }
+}
@@ -0,0 +1,359 @@
+use crate::analysis::{ground_speed, seconds_since, Day, GROUND_SPEED};
+use crate::document::IGCDocument;
+use crate::records::data_fix::CONTENT_START;
+use crate::records::extension::Extension;
+use crate::records::file_header::FileHeader;
+use crate::records::fix::{Fix, EXTENSION_START};
+use crate::records::task_info::TaskInfo;
+use crate::records::util::{Date, Time};
+use crate::records::Record;
+use crate::StrWrapper;
+
+/// Moving for fewer seconds in a row is GPS jitter on the ground, not a flight
+const MIN_MOVING_TIME: i64 = 30;
+
+impl IGCDocument {
+    /// Keeps the B, E, F and K records from `from` up to and including `to`, a range over midnight goes on to the next day
+    ///
+    /// Every other record is kept, and the `HFDTE` header and the flight date of the declaration move on when the range starts on a later day.
+    /// # examples
+    /// ```rust
+    /// use std::fs;
+    /// use igc_parser::document::IGCDocument;
+    /// use igc_parser::records::util::Time;
+    /// let document = IGCDocument::parse(&fs::read_to_string("./examples/example.igc").unwrap());
+    /// let trimmed = document.trim(&Time::from_hms(12, 0, 0).unwrap(), &Time::from_hms(13, 0, 0).unwrap());
+    /// assert!(trimmed.fixes().all(|fix| (12..13).contains(&fix.timestamp.h) || fix.timestamp == Time::from_hms(13, 0, 0).unwrap()));
+    /// ```
+    pub fn trim(&self, from: &Time, to: &Time) -> IGCDocument {
+        let offsets = self.offsets();
+        let first = offsets.iter().flatten().next().copied().unwrap_or(0);
+        let mut start = first.div_euclid(86400) * 86400 + from.seconds_since_midnight() as i64;
+        if start + 43200 < first { start += 86400 }
+        let end = start + seconds_since(from, to) as i64;
+        self.keep_timed(&offsets, |offset| (start..=end).contains(&offset))
+    }
+
+    /// Splits a file with several flights where the glider stayed on the ground for at least `min_ground_time` seconds
+    ///
+    /// The cut is halfway through the time on the ground, every part gets the header, I and J records and the task of the file.
+    /// Moving for less than 30 seconds in a row does not count as flying, so GPS jitter during a stop does not start a flight.
+    /// A file with a single flight is returned unchanged.
+    pub fn split_at_landings(&self, min_ground_time: u32) -> Vec<IGCDocument> {
+        let offsets = self.offsets();
+        let fixes = self.records.iter().zip(&offsets).filter_map(|(record, offset)| match record {
+            Record::B(fix) => Some((fix, (*offset)?)),
+            _ => None,
+        }).collect::<Vec<_>>();
+
+        // first and last fix of each run of consecutive fixes faster than GROUND_SPEED
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        let mut run = None;
+        for (i, pair) in fixes.windows(2).enumerate() {
+            if ground_speed(pair[0].0, pair[1].0) > GROUND_SPEED {
+                run = Some((run.map_or(i, |(first, _)| first), i + 1));
+            } else {
+                runs.extend(run.take());
+            }
+        }
+        runs.extend(run);
+
+        // first and last fix of each flight
+        let mut flights: Vec<(usize, usize)> = Vec::new();
+        for (first, last) in runs.into_iter().filter(|(first, last)| fixes[*last].1 - fixes[*first].1 >= MIN_MOVING_TIME) {
+            match flights.last_mut() {
+                Some(flight) if fixes[first].1 - fixes[flight.1].1 < min_ground_time as i64 => flight.1 = last,
+                _ => flights.push((first, last)),
+            }
+        }
+        if flights.len() < 2 { return vec![self.clone()] }
+
+        let cuts = flights.windows(2).map(|pair| (fixes[pair[0].1].1 + fixes[pair[1].0].1) / 2).collect::<Vec<_>>();
+        (0..=cuts.len()).map(|n| {
+            let from = if n == 0 { i64::MIN } else { cuts[n - 1] };
+            let to = cuts.get(n).copied().unwrap_or(i64::MAX);
+            self.keep_timed(&offsets, |offset| from <= offset && offset < to)
+        }).collect()
+    }
+
+    /// Joins the files a logger wrote for one flight when it was restarted
+    ///
+    /// Fragments are put in order of their date and first record, fragments without a date are left in the given order after the others.
+    /// The A record and headers come from the first fragment and the task from the first fragment that has one.
+    /// The I and J records declare every extension of any fragment, with the widest width any fragment declared it with,
+    /// and the B and K records are written again to match, padded with zeros to that width and for extensions their fragment did not record.
+    pub fn concatenate(fragments: &[IGCDocument]) -> IGCDocument {
+        let mut fragments = fragments.iter().collect::<Vec<_>>();
+        fragments.sort_by_key(|fragment| fragment.start_timestamp().unwrap_or(i64::MAX));
+        let Some(first) = fragments.first() else { return IGCDocument::from_records(Vec::new()) };
+
+        let i_record = merged(fragments.iter().filter_map(|fragment| fragment.extension('I')), EXTENSION_START);
+        let j_record = merged(fragments.iter().filter_map(|fragment| fragment.extension('J')), CONTENT_START);
+        let task = fragments.iter()
+            .map(|fragment| fragment.records.iter().filter(|record| matches!(record, Record::C(_))).cloned().collect::<Vec<_>>())
+            .find(|task| !task.is_empty())
+            .unwrap_or_default();
+
+        let mut records = first.records.iter().filter(|record| matches!(record, Record::A(_) | Record::D(_) | Record::H(_))).cloned().collect::<Vec<_>>();
+        records.extend(i_record.clone().map(Record::I));
+        records.extend(j_record.clone().map(Record::J));
+        records.extend(task);
+        for fragment in &fragments {
+            let (own_i_record, own_j_record) = (fragment.extension('I'), fragment.extension('J'));
+            for record in &fragment.records {
+                records.push(match record {
+                    Record::B(fix) => {
+                        let extension = relayout(&fix.extension, EXTENSION_START, own_i_record, i_record.as_ref());
+                        Record::B(Fix { extension, ..fix.clone() })
+                    }
+                    Record::K(data_fix) => {
+                        let mut data_fix = data_fix.clone();
+                        data_fix.content = relayout(&data_fix.content, CONTENT_START, own_j_record, j_record.as_ref());
+                        Record::K(data_fix)
+                    }
+                    Record::E(_) | Record::F(_) | Record::L(_) => record.clone(),
+                    _ => continue,
+                });
+            }
+        }
+        IGCDocument::from_records(records)
+    }
+
+    /// The document without the records of the kinds given by their letter, G records are always left out
+    ///
+    /// Dropping the B or K records also drops the I or J record declaring their extensions.
+    /// Dropping the I record clears the extension data of the B records and dropping the J record drops the K records.
+    pub fn without(&self, kinds: &[char]) -> IGCDocument {
+        let dropped = |kind: char| kinds.contains(&kind);
+        IGCDocument::from_records(self.records.iter().filter_map(|record| match record {
+            Record::G(_) => None,
+            record if dropped(record.letter()) => None,
+            Record::I(_) if dropped('B') => None,
+            Record::J(_) if dropped('K') => None,
+            Record::K(_) if dropped('J') => None,
+            Record::B(fix) if dropped('I') => Some(Record::B(Fix { extension: "".into(), ..fix.clone() })),
+            record => Some(record.clone()),
+        }).collect())
+    }
+
+    /// Seconds of each B, E, F and K record from midnight before the first one, going on past midnight
+    fn offsets(&self) -> Vec<Option<i64>> {
+        let mut day = Day::default();
+        self.records.iter().map(|record| Some(day.seconds(record.time()?.seconds_since_midnight() as i64))).collect()
+    }
+
+    /// Unix timestamp of the first timed record, `None` without a date
+    fn start_timestamp(&self) -> Option<i64> {
+        let first = self.records.iter().find_map(Record::time)?;
+        Some(self.date()?.days_since_epoch() * 86400 + first.seconds_since_midnight() as i64)
+    }
+
+    /// The first I or J record
+    fn extension(&self, kind: char) -> Option<&Extension> {
+        self.records.iter().find_map(|record| match record {
+            Record::I(extension) if kind == 'I' => Some(extension),
+            Record::J(extension) if kind == 'J' => Some(extension),
+            _ => None,
+        })
+    }
+
+    /// Keeps the untimed records except G records and the timed records for which `keep` is true, moving the date on to the day of the first one kept
+    fn keep_timed(&self, offsets: &[Option<i64>], keep: impl Fn(i64) -> bool) -> IGCDocument {
+        let records = self.records.iter().zip(offsets).filter(|(record, offset)| match offset {
+            Some(offset) => keep(*offset),
+            None => !matches!(record, Record::G(_)),
+        }).map(|(record, _)| record.clone()).collect();
+        let mut document = IGCDocument::from_records(records);
+        let days = offsets.iter().flatten().find(|&&offset| keep(offset)).map_or(0, |offset| offset.div_euclid(86400));
+        if days != 0 { document.move_date(days) }
+        document
+    }
+
+    /// Moves the `HFDTE` header and the flight date of the declaration on by `days`
+    fn move_date(&mut self, days: i64) {
+        let moved = |date: &Date| Date::from_days_since_epoch(date.days_since_epoch() + days);
+        for record in &mut self.records {
+            match record {
+                Record::H(header) => {
+                    if let FileHeader::Date(date) = &header.header {
+                        if let Some(header_with_date) = header.with_date(&moved(date)) { *header = header_with_date }
+                    }
+                }
+                Record::C(TaskInfo::DeclarationTime(declaration_time)) => {
+                    if let Some(flight_date) = declaration_time.flight_date() {
+                        declaration_time.set_flight_date(&moved(&flight_date));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// One record declaring every extension of `records` in the order they first appear, with the widest width they are declared with
+fn merged<'a>(records: impl Iterator<Item = &'a Extension>, data_start: usize) -> Option<Extension> {
+    let mut merged: Option<Extension> = None;
+    // code and number of bytes after the first one
+    let mut extensions: Vec<(StrWrapper, u8)> = Vec::new();
+    for record in records {
+        merged.get_or_insert_with(|| Extension { extension_type: record.extension_type.clone(), number_of_extensions: 0, extensions: Vec::new() });
+        for (from, to, code) in &record.extensions {
+            let length = to.saturating_sub(*from);
+            match extensions.iter_mut().find(|(known, _)| known == code) {
+                Some((_, known)) => *known = (*known).max(length),
+                None => extensions.push((code.clone(), length)),
+            }
+        }
+    }
+    let mut merged = merged?;
+    let mut start = data_start as u8;
+    for (code, length) in extensions {
+        let end = start.saturating_add(length);
+        merged.extensions.push((start, end, code));
+        start = end.saturating_add(1);
+    }
+    merged.number_of_extensions = merged.extensions.len() as u8;
+    Some(merged)
+}
+
+/// Extension data laid out as in `to`, taking each extension from where `from` declares it
+fn relayout(data: &str, data_start: usize, from: Option<&Extension>, to: Option<&Extension>) -> StrWrapper {
+    let to = match (from, to) {
+        (Some(from), Some(to)) if from.extensions != to.extensions => to,
+        (None, Some(to)) => to,
+        _ => return data.into(),
+    };
+    to.extensions.iter().map(|(start, end, code)| {
+        let width = end.saturating_sub(*start) as usize + 1;
+        let value = from.and_then(|from| from.slice(data, data_start, code)).unwrap_or("");
+        format!("{:0>width$}", value.get(value.len().saturating_sub(width)..).unwrap_or(""))
+    }).collect::<String>().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// B record at `seconds` after midnight, `north` meters north of 51°N 1°W
+    fn fix(seconds: u32, north: f64, extension: &str) -> String {
+        let time = Time::from_seconds_since_midnight(seconds % 86400).unwrap();
+        let coordinate = crate::records::util::Coordinate::from_decimal_degrees(51., -1.).destination(0., north);
+        format!("B{time}{coordinate}A0050000500{extension}")
+    }
+
+    fn document(lines: &[String]) -> IGCDocument {
+        IGCDocument::parse(&lines.join("\r\n"))
+    }
+
+    fn header(date: &str) -> Vec<String> {
+        ["AXXXABCFLIGHT:1", &format!("HFDTEDATE:{date},01"), "HFPLTPILOTINCHARGE:Jane Doe", "I013638FXA", "J010812TAS"]
+            .iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn trim_over_midnight() {
+        let mut lines = header("310324");
+        lines.push("C310324100000310324000100".into());
+        lines.extend((0..6).map(|i| fix(86280 + i * 60, 0., "010")));
+        lines.push("E000000PEV".into());
+        lines.push("GABCDEF".into());
+        let document = document(&lines);
+
+        let trimmed = document.trim(&Time::from_hms(0, 0, 0).unwrap(), &Time::from_hms(0, 1, 0).unwrap());
+        assert_eq!(trimmed.fixes().count(), 2);
+        assert_eq!(trimmed.date(), Some(&Date { d: 1, m: 4, y: 24 }));
+        let written = trimmed.to_string();
+        assert!(written.contains("C310324100000010424000100\r\n") && written.contains("E000000PEV\r\n"));
+        assert!(written.contains("I013638FXA\r\n") && !written.contains("GABCDEF"));
+
+        let before = document.trim(&Time::from_hms(23, 0, 0).unwrap(), &Time::from_hms(23, 59, 0).unwrap());
+        assert_eq!(before.fixes().count(), 2);
+        assert_eq!(before.date(), document.date());
+    }
+
+    #[test]
+    fn split_two_flights() {
+        let mut lines = header("010324");
+        // on the ground for 10 minutes, 20 minutes flying north at 60 km/h, 30 minutes on the ground, 20 minutes flying back
+        lines.extend((0..10).map(|i| fix(36000 + i * 60, 0., "010")));
+        lines.extend((0..20).map(|i| fix(36600 + i * 60, i as f64 * 1000., "010")));
+        lines.extend((0..30).map(|i| fix(37800 + i * 60, 20000., "010")));
+        lines.extend((0..=20).map(|i| fix(39600 + i * 60, 20000. - i as f64 * 1000., "010")));
+        let document = document(&lines);
+
+        let flights = document.split_at_landings(300);
+        assert_eq!(flights.len(), 2);
+        assert_eq!(flights.iter().map(|flight| flight.fixes().count()).sum::<usize>(), 81);
+        assert_eq!(flights[1].fixes().next().unwrap().timestamp, Time::from_hms(10, 45, 0).unwrap());
+        assert!(flights.iter().all(|flight| flight.date() == document.date() && flight.to_string().starts_with("AXXXABCFLIGHT:1\r\nHFDTE")));
+        assert_eq!(document.split_at_landings(3600).len(), 1);
+    }
+
+    #[test]
+    fn split_ignores_jitter_on_the_ground() {
+        let mut lines = header("010324");
+        // like `split_two_flights` with a 20m GPS jump logged every second in the middle of the 30 minutes on the ground
+        lines.extend((0..20).map(|i| fix(36600 + i * 60, i as f64 * 1000., "010")));
+        lines.extend((0..15).map(|i| fix(37800 + i * 60, 20000., "010")));
+        lines.extend([fix(38700, 20000., "010"), fix(38701, 20020., "010"), fix(38702, 20000., "010")]);
+        lines.extend((16..30).map(|i| fix(37800 + i * 60, 20000., "010")));
+        lines.extend((0..=20).map(|i| fix(39600 + i * 60, 20000. - i as f64 * 1000., "010")));
+        let document = document(&lines);
+
+        let flights = document.split_at_landings(300);
+        assert_eq!(flights.len(), 2);
+        assert_eq!(flights[1].fixes().next().unwrap().timestamp, Time::from_hms(10, 45, 0).unwrap());
+    }
+
+    #[test]
+    fn concatenate_restart() {
+        let mut first = header("010324");
+        first.extend((0..3).map(|i| fix(36000 + i * 60, i as f64 * 1000., "010")));
+        first.push("K100000120".into());
+        let mut second = header("010324");
+        second[3] = "I023638FXA3941ENL".into();
+        second.push("C010324090000010324000100".into());
+        second.extend((3..5).map(|i| fix(36000 + i * 60, i as f64 * 1000., "020500")));
+        second.push("GABCDEF".into());
+
+        let joined = IGCDocument::concatenate(&[document(&second), document(&first)]);
+        let written = joined.to_string();
+        assert!(written.contains("I023638FXA3941ENL\r\n") && written.contains("J010812TAS\r\n"));
+        assert!(written.contains("C010324090000010324000100\r\n") && written.contains("K100000120\r\n"));
+        assert!(!written.contains("GABCDEF"));
+        let extensions = joined.fixes().map(|fix| fix.extension.to_string()).collect::<Vec<_>>();
+        assert_eq!(extensions, ["010000", "010000", "010000", "020500", "020500"]);
+        assert_eq!(written.matches("HFDTE").count(), 1);
+    }
+
+    #[test]
+    fn concatenate_widens_extensions() {
+        let mut first = header("010324");
+        first[3] = "I023638FXA3941ENL".into();
+        first.push(fix(36000, 0., "010120"));
+        let mut second = header("010324");
+        second[3] = "I023638FXA3942ENL".into();
+        second.push(fix(36060, 1000., "0201234"));
+
+        let joined = IGCDocument::concatenate(&[document(&first), document(&second)]);
+        assert!(joined.to_string().contains("I023638FXA3942ENL\r\n"));
+        let extensions = joined.fixes().map(|fix| fix.extension.to_string()).collect::<Vec<_>>();
+        assert_eq!(extensions, ["0100120", "0201234"]);
+    }
+
+    #[test]
+    fn drop_kinds() {
+        let mut lines = header("010324");
+        lines.extend((0..3).map(|i| fix(36000 + i * 60, 0., "010")));
+        lines.push("K100000120".into());
+        lines.push("LXXXcomment".into());
+        let document = document(&lines);
+
+        let written = document.without(&['K', 'L']).to_string();
+        assert!(!written.contains("J010812TAS") && !written.contains("K100000") && !written.contains("LXXX"));
+        assert!(written.contains("I013638FXA"));
+        let without_i = document.without(&['I']);
+        assert!(without_i.fixes().all(|fix| fix.extension.is_empty()));
+        assert!(without_i.to_string().contains("J010812TAS"));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::records::file_header::FileHeader;
+use crate::records::fix::Fix;
+use crate::records::util::Date;
+use crate::records::Record;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
+
+/// Anonymisation and redaction of personal data and locations
+pub mod anonymise;
+/// Trimming, splitting, joining and filtering, edited documents leave out G records since the signature no longer matches
+pub mod edit;
+
+/// An IGC file as records in the order of the file, for editing and writing IGC files
+///
+/// Writing a document gives a valid IGC file with CRLF line endings, but a G record is only valid for the exact file it was made for.
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::document::IGCDocument;
+/// let content = fs::read_to_string("./examples/example.igc").unwrap();
+/// let document = IGCDocument::parse(&content);
+/// assert_eq!(document.to_string().lines().next(), content.lines().next());
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(description = "The records of an IGC file in the order of the file"))]
+#[derive(Debug, Clone)]
+pub struct IGCDocument {
+    pub records: Vec<Record>,
+}
+
+impl IGCDocument {
+    /// Parses every line of the file, lines that fail to parse are left out, use `IGCFile` to see the errors
+    pub fn parse(content: &str) -> Self {
+        Self { records: content.lines().filter_map(|line| Record::parse(line).ok()).collect() }
+    }
+
+    pub fn from_records(records: Vec<Record>) -> Self {
+        Self { records }
+    }
+
+    pub fn fixes(&self) -> impl Iterator<Item = &Fix> {
+        self.records.iter().filter_map(|record| match record {
+            Record::B(fix) => Some(fix),
+            _ => None,
+        })
+    }
+
+    /// The date of the flight from the first `HFDTE` header
+    pub fn date(&self) -> Option<&Date> {
+        self.records.iter().find_map(|record| match record {
+            Record::H(header) => match &header.header {
+                FileHeader::Date(date) => Some(date),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for IGCDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for record in &self.records {
+            write!(f, "{record}\r\n")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn example_round_trip() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let document = IGCDocument::parse(&content);
+        let written = document.to_string();
+        let parsed_lines = content.lines().filter(|line| Record::parse(line).is_ok());
+        for (original, written) in parsed_lines.zip(written.lines()) {
+            assert_eq!(original.trim_end(), written.trim_end());
+        }
+        assert_eq!(written.lines().count(), document.records.len());
+        assert_eq!(document.date(), Some(&Date { d: 6, m: 8, y: 20 }));
+        assert_eq!(document.fixes().count(), 5825);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn records_are_internally_tagged() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let document = IGCDocument::parse(&content);
+        let serialised = serde_json::to_value(&document).unwrap();
+        assert_eq!(serialised["records"][0]["type"], serde_json::json!("flight_recorder_id"));
+        let parsed: IGCDocument = serde_json::from_value(serialised).unwrap();
+        assert_eq!(parsed.to_string(), document.to_string());
+    }
+}
@@ -1,7 +1,11 @@
 use thiserror::Error;
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg(feature = "schema")] use schemars::JsonSchema;
+
+/// Serialised as `{"kind": ..., "line": null, "message": ...}`, see `kind` and `message`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "ErrorFields", try_from = "ErrorFields"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone, Error)]
 pub enum IGCError {
     #[error("Error initializing time: {0}")]
@@ -35,5 +39,132 @@ pub enum IGCError {
     #[error("Error initializing data fix: {0}")]
     DataFixInitError(String),
     #[error("Error initializing IGC file: {0}")]
-    IGCFileInitError(String)
-}
\ No newline at end of file
+    IGCFileInitError(String),
+    #[error("Error initializing airspace: {0}")]
+    AirspaceInitError(String),
+    #[error("Error initializing terrain: {0}")]
+    TerrainInitError(String),
+    #[error("Error exporting to Arrow or Parquet: {0}")]
+    ArrowExportError(String)
+}
+
+impl IGCError {
+    /// Snake case name of what failed to initialise, e.g. `fix` for `FixInitError`
+    pub fn kind(&self) -> &'static str {
+        use IGCError::*;
+        match self {
+            TimeInitError(_) => "time",
+            DateInitError(_) => "date",
+            FixInitError(_) => "fix",
+            RecordInitError(_) => "record",
+            CoordinateInitError(_) => "coordinate",
+            FRIDInitError(_) => "flight_recorder_id",
+            TaskInfoInitError(_) => "task_info",
+            DiffGPSInitError(_) => "differential_gps",
+            EventInitError(_) => "event",
+            SatelliteInitError(_) => "satellite",
+            SecurityInitError(_) => "security",
+            FileHeaderInitError(_) => "file_header",
+            ExtensionInitError(_) => "extension",
+            CommentInitError(_) => "comment",
+            DataFixInitError(_) => "data_fix",
+            IGCFileInitError(_) => "igc_file",
+            AirspaceInitError(_) => "airspace",
+            TerrainInitError(_) => "terrain",
+            ArrowExportError(_) => "arrow_export",
+        }
+    }
+
+    /// The message without the prefix naming the kind
+    pub fn message(&self) -> &str {
+        use IGCError::*;
+        match self {
+            TimeInitError(message) | DateInitError(message) | FixInitError(message) | RecordInitError(message)
+            | CoordinateInitError(message) | FRIDInitError(message) | TaskInfoInitError(message) | DiffGPSInitError(message)
+            | EventInitError(message) | SatelliteInitError(message) | SecurityInitError(message) | FileHeaderInitError(message)
+            | ExtensionInitError(message) | CommentInitError(message) | DataFixInitError(message) | IGCFileInitError(message)
+            | AirspaceInitError(message) | TerrainInitError(message) | ArrowExportError(message) => message,
+        }
+    }
+
+    /// The error of the kind with the message, `None` for an unknown kind
+    pub fn from_kind(kind: &str, message: String) -> Option<Self> {
+        use IGCError::*;
+        Some(match kind {
+            "time" => TimeInitError(message),
+            "date" => DateInitError(message),
+            "fix" => FixInitError(message),
+            "record" => RecordInitError(message),
+            "coordinate" => CoordinateInitError(message),
+            "flight_recorder_id" => FRIDInitError(message),
+            "task_info" => TaskInfoInitError(message),
+            "differential_gps" => DiffGPSInitError(message),
+            "event" => EventInitError(message),
+            "satellite" => SatelliteInitError(message),
+            "security" => SecurityInitError(message),
+            "file_header" => FileHeaderInitError(message),
+            "extension" => ExtensionInitError(message),
+            "comment" => CommentInitError(message),
+            "data_fix" => DataFixInitError(message),
+            "igc_file" => IGCFileInitError(message),
+            "airspace" => AirspaceInitError(message),
+            "terrain" => TerrainInitError(message),
+            "arrow_export" => ArrowExportError(message),
+            _ => return None,
+        })
+    }
+}
+
+/// Every kind of error, in the order of the variants, for the schema
+#[cfg(feature = "schema")]
+fn kinds() -> Vec<&'static str> {
+    use IGCError::*;
+    let variants: [fn(String) -> IGCError; 19] = [
+        TimeInitError, DateInitError, FixInitError, RecordInitError, CoordinateInitError, FRIDInitError, TaskInfoInitError,
+        DiffGPSInitError, EventInitError, SatelliteInitError, SecurityInitError, FileHeaderInitError, ExtensionInitError,
+        CommentInitError, DataFixInitError, IGCFileInitError, AirspaceInitError, TerrainInitError, ArrowExportError,
+    ];
+    variants.iter().map(|variant| variant(String::new()).kind()).collect()
+}
+
+/// The serialised form of an error, `line` is the line of the file it came from counting from 1, or `null` when not known
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(rename = "Error"))]
+pub(crate) struct ErrorFields {
+    #[cfg_attr(feature = "schema", schemars(extend("enum" = kinds())))]
+    pub(crate) kind: String,
+    pub(crate) line: Option<usize>,
+    pub(crate) message: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<IGCError> for ErrorFields {
+    fn from(error: IGCError) -> Self {
+        Self { kind: error.kind().to_string(), line: None, message: error.message().to_string() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ErrorFields> for IGCError {
+    type Error = String;
+
+    fn try_from(fields: ErrorFields) -> Result<Self, String> {
+        let kind = fields.kind;
+        IGCError::from_kind(&kind, fields.message).ok_or_else(|| format!("'{kind}' is not a kind of error"))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "schema")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_round_trip() {
+        for kind in kinds() {
+            assert_eq!(IGCError::from_kind(kind, "message".into()).map(|error| error.kind()), Some(kind));
+        }
+        assert!(IGCError::from_kind("unknown", "message".into()).is_none());
+    }
+}
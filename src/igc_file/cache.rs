@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::serialization::Entry;
+use super::IGCFile;
+use crate::error::{ErrorFields, IGCError};
+use crate::error::IGCError::IGCFileInitError;
+use crate::records::comment::Comment;
+use crate::records::data_fix::DataFix;
+use crate::records::diff_gps::DiffGPS;
+use crate::records::event::Event;
+use crate::records::extension::Extension;
+use crate::records::file_header::FileHeaderRecord;
+use crate::records::fix::Fix;
+use crate::records::flight_recorder_id::FlightRecorderID;
+use crate::records::satellite::Satellite;
+use crate::records::security::Security;
+use crate::records::task_info::TaskInfo;
+use crate::records::util::{Coordinate, Latitude, Longitude, Time};
+use crate::Result;
+
+/// Start of every cache, followed by the version as two little endian bytes
+const MAGIC: &[u8; 4] = b"IGCC";
+
+/// Version of the format written by `IGCFile::to_cache`, caches of any other version are rejected by `IGCFile::from_cache`
+pub const CACHE_VERSION: u16 = 1;
+
+/// Everything in an `IGCFile`, B records as columns and the other records as their lines,
+/// the strings are borrowed from the cache when reading
+#[derive(Deserialize, Serialize)]
+struct CachedFile<S> {
+    fixes: FixDeltas<S>,
+    fr_ids: Vec<Entry<S>>,
+    task_info: Vec<Entry<S>>,
+    differential_gps_records: Vec<Entry<S>>,
+    events: Vec<Entry<S>>,
+    satellite_vec: Vec<Entry<S>>,
+    security_vec: Vec<Entry<S>>,
+    file_header_vec: Vec<Entry<S>>,
+    i_extension_vec: Vec<Entry<S>>,
+    j_extension_vec: Vec<Entry<S>>,
+    data_fix_vec: Vec<Entry<S>>,
+    comment_vec: Vec<Entry<S>>,
+}
+
+/// The valid fixes with every number stored as the difference from the previous fix, so most take one byte
+#[derive(Deserialize, Serialize)]
+struct FixDeltas<S> {
+    /// Position in the fixes and error of every B record that failed to parse
+    errors: Vec<(u32, ErrorFields)>,
+    /// Seconds since midnight
+    times: Vec<i32>,
+    latitudes: Vec<i64>,
+    longitudes: Vec<i64>,
+    pressure_altitudes: Vec<i32>,
+    /// Relative to the previous valid GPS altitude, `None` where the fix is not valid
+    gps_altitudes: Vec<Option<i32>>,
+    extensions: Vec<S>,
+}
+
+/// Degrees and thousandths of minutes as written in a B record, doubled with the lowest bit set for south or west
+fn pack(degrees: u8, minutes: f32, positive: bool) -> i64 {
+    ((degrees as i64 * 100_000 + (minutes * 1000.).round() as i64) << 1) | !positive as i64
+}
+
+fn unpack(packed: i64) -> Result<(u8, f32, bool)> {
+    let (value, positive) = (packed >> 1, packed & 1 == 0);
+    let degrees = u8::try_from(value / 100_000).map_err(|_| IGCFileInitError(format!("{packed} is not a packed coordinate")))?;
+    Ok((degrees, (value % 100_000) as f32 / 1000., positive))
+}
+
+impl FixDeltas<String> {
+    fn encode(fixes: &[Result<Fix>], error_lines: &HashMap<(char, usize), usize>) -> Self {
+        let mut columns = Self {
+            errors: Vec::new(),
+            times: Vec::with_capacity(fixes.len()),
+            latitudes: Vec::with_capacity(fixes.len()),
+            longitudes: Vec::with_capacity(fixes.len()),
+            pressure_altitudes: Vec::with_capacity(fixes.len()),
+            gps_altitudes: Vec::with_capacity(fixes.len()),
+            extensions: Vec::with_capacity(fixes.len()),
+        };
+        let (mut time, mut latitude, mut longitude, mut pressure_altitude, mut gps_altitude) = (0, 0, 0, 0, 0);
+        for (index, fix) in fixes.iter().enumerate() {
+            let fix = match fix {
+                Ok(fix) => fix,
+                Err(error) => {
+                    let line = error_lines.get(&('B', index)).copied();
+                    columns.errors.push((index as u32, ErrorFields { line, ..error.clone().into() }));
+                    continue
+                }
+            };
+            let Coordinate { latitude: lat, longitude: lon } = &fix.coordinates;
+            let next = (
+                fix.timestamp.seconds_since_midnight() as i32,
+                pack(lat.degrees, lat.minutes, lat.is_north),
+                pack(lon.degrees, lon.minutes, lon.is_east),
+                fix.pressure_alt as i32,
+            );
+            columns.times.push(next.0 - time);
+            columns.latitudes.push(next.1 - latitude);
+            columns.longitudes.push(next.2 - longitude);
+            columns.pressure_altitudes.push(next.3 - pressure_altitude);
+            (time, latitude, longitude, pressure_altitude) = next;
+            columns.gps_altitudes.push(fix.gps_alt.map(|altitude| {
+                let delta = altitude as i32 - gps_altitude;
+                gps_altitude = altitude as i32;
+                delta
+            }));
+            columns.extensions.push(fix.extension.to_string());
+        }
+        columns
+    }
+}
+
+impl FixDeltas<&str> {
+    fn decode(self, error_lines: &mut HashMap<(char, usize), usize>) -> Result<Vec<Result<Fix>>> {
+        let lengths = [self.latitudes.len(), self.longitudes.len(), self.pressure_altitudes.len(), self.gps_altitudes.len(), self.extensions.len()];
+        if lengths.iter().any(|length| *length != self.times.len()) {
+            return Err(IGCFileInitError("the fix columns of the cache have different lengths".to_string()))
+        }
+        let mut fixes = Vec::with_capacity(self.times.len() + self.errors.len());
+        let mut errors = self.errors.into_iter().peekable();
+        let (mut time, mut latitude, mut longitude, mut pressure_altitude, mut gps_altitude) = (0, 0, 0, 0, 0);
+        let columns = self.times.into_iter()
+            .zip(self.latitudes).zip(self.longitudes).zip(self.pressure_altitudes).zip(self.gps_altitudes).zip(self.extensions);
+        for (((((time_delta, latitude_delta), longitude_delta), pressure_delta), gps_delta), extension) in columns {
+            while let Some((_, fields)) = errors.next_if(|(index, _)| *index as usize == fixes.len()) {
+                fixes.push(Err(error(fields, 'B', fixes.len(), error_lines)?));
+            }
+            time += time_delta;
+            latitude += latitude_delta;
+            longitude += longitude_delta;
+            pressure_altitude += pressure_delta;
+            let (lat_degrees, lat_minutes, is_north) = unpack(latitude)?;
+            let (lon_degrees, lon_minutes, is_east) = unpack(longitude)?;
+            fixes.push(Ok(Fix {
+                timestamp: Time::from_seconds_since_midnight(time as u32)?,
+                coordinates: Coordinate {
+                    latitude: Latitude { degrees: lat_degrees, minutes: lat_minutes, is_north },
+                    longitude: Longitude { degrees: lon_degrees, minutes: lon_minutes, is_east },
+                },
+                pressure_alt: pressure_altitude as i16,
+                gps_alt: gps_delta.map(|delta| {
+                    gps_altitude += delta;
+                    gps_altitude as i16
+                }),
+                extension: extension.into(),
+            }));
+        }
+        for (_, fields) in errors {
+            fixes.push(Err(error(fields, 'B', fixes.len(), error_lines)?));
+        }
+        Ok(fixes)
+    }
+}
+
+/// The error from its serialised form, remembering its line
+fn error(fields: ErrorFields, letter: char, index: usize, error_lines: &mut HashMap<(char, usize), usize>) -> Result<IGCError> {
+    if let Some(line) = fields.line { error_lines.insert((letter, index), line); }
+    IGCError::try_from(fields).map_err(IGCFileInitError)
+}
+
+fn lines<T: Display>(results: &[Result<T>], letter: char, error_lines: &HashMap<(char, usize), usize>) -> Vec<Entry<String>> {
+    results.iter().enumerate().map(|(index, result)| match result {
+        Ok(record) => Entry::Ok(record.to_string()),
+        Err(error) => Entry::Error(ErrorFields { line: error_lines.get(&(letter, index)).copied(), ..error.clone().into() }),
+    }).collect()
+}
+
+fn parsed<T>(entries: Vec<Entry<&str>>, letter: char, parse: fn(&str) -> Result<T>, error_lines: &mut HashMap<(char, usize), usize>) -> Result<Vec<Result<T>>> {
+    entries.into_iter().enumerate().map(|(index, entry)| match entry {
+        Entry::Ok(line) => Ok(parse(line)),
+        Entry::Error(fields) => Ok(Err(error(fields, letter, index, error_lines)?)),
+    }).collect()
+}
+
+impl IGCFile {
+    /// Compact binary form of the parsed file for caching, loading it with `from_cache` is much faster than parsing the text
+    ///
+    /// Starts with a version header, fixes are stored as delta encoded columns and the other records as their lines
+    /// # examples
+    /// ```rust
+    /// use std::fs;
+    /// use igc_parser::igc_file::IGCFile;
+    /// let igc_file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+    /// let cache = igc_file.to_cache().unwrap();
+    /// let cached = IGCFile::from_cache(&cache).unwrap();
+    /// assert_eq!(cached.get_fixes().len(), igc_file.get_fixes().len());
+    /// ```
+    pub fn to_cache(&self) -> Result<Vec<u8>> {
+        let error_lines = &self.error_lines;
+        let cached = CachedFile {
+            fixes: FixDeltas::encode(&self.fixes, error_lines),
+            fr_ids: lines(&self.fr_ids, 'A', error_lines),
+            task_info: lines(&self.task_info, 'C', error_lines),
+            differential_gps_records: lines(&self.differential_gps_records, 'D', error_lines),
+            events: lines(&self.events, 'E', error_lines),
+            satellite_vec: lines(&self.satellite_vec, 'F', error_lines),
+            security_vec: lines(&self.security_vec, 'G', error_lines),
+            file_header_vec: lines(&self.file_header_vec, 'H', error_lines),
+            i_extension_vec: lines(&self.i_extension_vec, 'I', error_lines),
+            j_extension_vec: lines(&self.j_extension_vec, 'J', error_lines),
+            data_fix_vec: lines(&self.data_fix_vec, 'K', error_lines),
+            comment_vec: lines(&self.comment_vec, 'L', error_lines),
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(CACHE_VERSION.to_le_bytes());
+        postcard::to_extend(&cached, bytes).map_err(|error| IGCFileInitError(format!("unable to write the cache: {error}")))
+    }
+
+    /// Loads a file written by `to_cache`, caches written by another version of the format are an error so they can be parsed again
+    pub fn from_cache(bytes: &[u8]) -> Result<Self> {
+        let Some(payload) = bytes.strip_prefix(MAGIC) else {
+            return Err(IGCFileInitError("the bytes are not an IGC file cache".to_string()))
+        };
+        let version = match payload {
+            [low, high, ..] => u16::from_le_bytes([*low, *high]),
+            _ => return Err(IGCFileInitError("the cache has no version".to_string())),
+        };
+        if version != CACHE_VERSION {
+            return Err(IGCFileInitError(format!("cache version {version} is not supported, expected version {CACHE_VERSION}")))
+        }
+        let cached: CachedFile<&str> = postcard::from_bytes(&payload[2..])
+            .map_err(|error| IGCFileInitError(format!("unable to read the cache: {error}")))?;
+        let mut error_lines = HashMap::new();
+        let lines = &mut error_lines;
+        Ok(Self {
+            fixes: cached.fixes.decode(lines)?,
+            fr_ids: parsed(cached.fr_ids, 'A', FlightRecorderID::parse, lines)?,
+            task_info: parsed(cached.task_info, 'C', TaskInfo::parse, lines)?,
+            differential_gps_records: parsed(cached.differential_gps_records, 'D', DiffGPS::parse, lines)?,
+            events: parsed(cached.events, 'E', Event::parse, lines)?,
+            satellite_vec: parsed(cached.satellite_vec, 'F', Satellite::parse, lines)?,
+            security_vec: parsed(cached.security_vec, 'G', Security::parse, lines)?,
+            file_header_vec: parsed(cached.file_header_vec, 'H', FileHeaderRecord::parse, lines)?,
+            i_extension_vec: parsed(cached.i_extension_vec, 'I', Extension::parse, lines)?,
+            j_extension_vec: parsed(cached.j_extension_vec, 'J', Extension::parse, lines)?,
+            data_fix_vec: parsed(cached.data_fix_vec, 'K', DataFix::parse, lines)?,
+            comment_vec: parsed(cached.comment_vec, 'L', Comment::parse, lines)?,
+            error_lines,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn example_round_trip() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let igc_file = IGCFile::parse(&content).unwrap();
+        let cache = igc_file.to_cache().unwrap();
+        assert!(cache.len() < content.len() * 2 / 3, "{} bytes", cache.len());
+        let cached = IGCFile::from_cache(&cache).unwrap();
+        assert_eq!(serde_json::to_value(cached).unwrap(), serde_json::to_value(igc_file).unwrap());
+    }
+
+    #[test]
+    fn exact_coordinates_and_errors() {
+        let content = "BROKEN\nB2359595407121S00249342WV0028000421\nB0000005400000N18000000EA-001200000\nL ok";
+        let igc_file = IGCFile::parse(content).unwrap();
+        let cached = IGCFile::from_cache(&igc_file.to_cache().unwrap()).unwrap();
+        let fixes = cached.get_fixes();
+        assert!(fixes[0].is_err());
+        assert_eq!(cached.error_line('B', 0), Some(1));
+        for (cached, parsed) in fixes[1..].iter().zip(&igc_file.get_fixes()[1..]) {
+            let (cached, parsed) = (cached.as_ref().unwrap(), parsed.as_ref().unwrap());
+            assert_eq!(cached.coordinates, parsed.coordinates);
+            assert_eq!((&cached.timestamp, cached.pressure_alt, cached.gps_alt), (&parsed.timestamp, parsed.pressure_alt, parsed.gps_alt));
+        }
+        assert_eq!(cached.get_comment_vec()[0].as_ref().unwrap().content.as_ref(), " ok");
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let mut cache = IGCFile::parse("L comment").unwrap().to_cache().unwrap();
+        cache[4] += 1;
+        assert!(IGCFile::from_cache(&cache).is_err());
+        assert!(IGCFile::from_cache(b"HFDTEDATE:060820").is_err());
+    }
+}
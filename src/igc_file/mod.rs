@@ -4,7 +4,7 @@ use crate::records::diff_gps::DiffGPS;
 use crate::error::IGCError::IGCFileInitError;
 use crate::records::event::Event;
 use crate::records::extension::Extension;
-use crate::records::file_header::FileHeader;
+use crate::records::file_header::FileHeaderRecord;
 use crate::records::fix::Fix;
 use crate::records::flight_recorder_id::FlightRecorderID;
 use crate::records::Record;
@@ -12,13 +12,26 @@ use crate::records::satellite::Satellite;
 use crate::records::security::Security;
 use crate::records::task_info::TaskInfo;
 use crate::Result;
+use std::collections::HashMap;
 
 #[cfg(feature = "serde")] use serde::{Serialize, Deserialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+/// Serialisation of parsed files with each line as `{"ok": ...}` or `{"error": ...}`
+#[cfg(feature = "serde")]
+mod serialization;
+/// Compact binary cache of parsed files with the fixes stored as delta encoded columns
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "cache")]
+pub use cache::CACHE_VERSION;
+
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "serialization::IGCFileFields", try_from = "serialization::IGCFileFields"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(description = "Every line of an IGC file parsed into the results for its kind of record"))]
 #[derive(Clone)]
 /// For parsing the entire file at once, then it gives access to vectors holding the result of the parsing
 ///
+/// Serialised with every result as `{"ok": record}` or `{"error": {"kind": ..., "line": ..., "message": ...}}`
 ///
 /// Not very efficient if you only need to parse one specific kind of record but still fast enough for almost all use cases
 /// # examples
@@ -43,11 +56,13 @@ pub struct IGCFile {
     events: Vec<Result<Event>>,
     satellite_vec: Vec<Result<Satellite>>,
     security_vec: Vec<Result<Security>>,
-    file_header_vec: Vec<Result<FileHeader>>,
+    file_header_vec: Vec<Result<FileHeaderRecord>>,
     i_extension_vec: Vec<Result<Extension>>,
     j_extension_vec: Vec<Result<Extension>>,
     data_fix_vec: Vec<Result<DataFix>>,
     comment_vec: Vec<Result<Comment>>,
+    /// Line of the file each error came from, by record letter and index in its vector
+    error_lines: HashMap<(char, usize), usize>,
 }
 
 impl IGCFile {
@@ -55,105 +70,151 @@ impl IGCFile {
     /// ´content´ is the UTF-8 content of the file you want to parse,
     /// it is rare for this parsing to return an Err instead of Ok(Self) but it can happen
     pub fn parse(content: &str) -> Result<Self> {
-        let mut fr_ids: Vec<Result<FlightRecorderID>> = Vec::new();
-        let mut fixes: Vec<Result<Fix>> = Vec::new();
-        let mut task_info: Vec<Result<TaskInfo>> = Vec::new();
-        let mut differential_gps_records: Vec<Result<DiffGPS>> = Vec::new();
-        let mut events: Vec<Result<Event>> = Vec::new();
-        let mut satellite_vec: Vec<Result<Satellite>> = Vec::new();
-        let mut security_vec: Vec<Result<Security>> = Vec::new();
-        let mut file_header_vec: Vec<Result<FileHeader>> = Vec::new();
-        let mut i_extension_vec: Vec<Result<Extension>> = Vec::new();
-        let mut j_extension_vec: Vec<Result<Extension>> = Vec::new();
-        let mut data_fix_vec: Vec<Result<DataFix>> = Vec::new();
-        let mut comment_vec: Vec<Result<Comment>> = Vec::new();
-        for line in content.lines() {
-            let record = Record::parse(line);
-            match line.chars().next() {
-                Some(letter) => {
-                    match letter {
-                        'A' => fr_ids.push(match record {
-                            Ok(Record::A(frid)) => Ok(frid),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'B' => fixes.push(match record {
-                            Ok(Record::B(fix)) => Ok(fix),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'C' => task_info.push(match record {
-                            Ok(Record::C(info)) => Ok(info),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'D' => differential_gps_records.push(match record {
-                            Ok(Record::D(diff_gps)) => Ok(diff_gps),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'E' => events.push(match record {
-                            Ok(Record::E(event)) => Ok(event),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'F' => satellite_vec.push(match record {
-                            Ok(Record::F(sat)) => Ok(sat),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'G' => security_vec.push(match record {
-                            Ok(Record::G(sec)) => Ok(sec),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'H' => file_header_vec.push(match record {
-                            Ok(Record::H(header)) => Ok(header),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'I' => i_extension_vec.push(match record {
-                            Ok(Record::I(ext)) => Ok(ext),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'J' => j_extension_vec.push(match record {
-                            Ok(Record::J(ext)) => Ok(ext),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'K' => data_fix_vec.push(match record {
-                            Ok(Record::K(data_fix)) => Ok(data_fix),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        'L' => comment_vec.push(match record {
-                            Ok(Record::L(comment)) => Ok(comment),
-                            Err(error) => Err(error),
-                            _ => unreachable!(),
-                        }),
-                        _ => return Err(IGCFileInitError(format!("{line} does not have a valid start letter"))),
-                    }
+        let mut igc_file = Self::empty();
+        for (number, line) in content.lines().enumerate() {
+            igc_file.push_record(number + 1, line, Record::parse(line))?;
+        }
+        Ok(igc_file)
+    }
 
+    /// Same as `parse` but the lines are parsed in parallel, the result is identical to `parse`
+    #[cfg(feature = "parallel")]
+    pub fn parse_parallel(content: &str) -> Result<Self> {
+        use rayon::prelude::*;
+        let lines = content.lines().collect::<Vec<&str>>();
+        let records = lines
+            .par_iter()
+            .with_min_len(crate::parallel::MIN_LINES_PER_TASK)
+            .map(|line| Record::parse(line))
+            .collect::<Vec<_>>();
+        let mut igc_file = Self::empty();
+        for (number, (line, record)) in lines.into_iter().zip(records).enumerate() {
+            igc_file.push_record(number + 1, line, record)?;
+        }
+        Ok(igc_file)
+    }
+
+    fn empty() -> Self {
+        Self {
+            fr_ids: Vec::new(),
+            fixes: Vec::new(),
+            task_info: Vec::new(),
+            differential_gps_records: Vec::new(),
+            events: Vec::new(),
+            satellite_vec: Vec::new(),
+            security_vec: Vec::new(),
+            file_header_vec: Vec::new(),
+            i_extension_vec: Vec::new(),
+            j_extension_vec: Vec::new(),
+            data_fix_vec: Vec::new(),
+            comment_vec: Vec::new(),
+            error_lines: HashMap::new(),
+        }
+    }
+
+    fn push_record(&mut self, number: usize, line: &str, record: Result<Record>) -> Result<()> {
+        let is_error = record.is_err();
+        match line.chars().next() {
+            Some(letter) => {
+                match letter {
+                    'A' => self.fr_ids.push(match record {
+                        Ok(Record::A(frid)) => Ok(frid),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'B' => self.fixes.push(match record {
+                        Ok(Record::B(fix)) => Ok(fix),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'C' => self.task_info.push(match record {
+                        Ok(Record::C(info)) => Ok(info),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'D' => self.differential_gps_records.push(match record {
+                        Ok(Record::D(diff_gps)) => Ok(diff_gps),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'E' => self.events.push(match record {
+                        Ok(Record::E(event)) => Ok(event),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'F' => self.satellite_vec.push(match record {
+                        Ok(Record::F(sat)) => Ok(sat),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'G' => self.security_vec.push(match record {
+                        Ok(Record::G(sec)) => Ok(sec),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'H' => self.file_header_vec.push(match record {
+                        Ok(Record::H(header)) => Ok(header),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'I' => self.i_extension_vec.push(match record {
+                        Ok(Record::I(ext)) => Ok(ext),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'J' => self.j_extension_vec.push(match record {
+                        Ok(Record::J(ext)) => Ok(ext),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'K' => self.data_fix_vec.push(match record {
+                        Ok(Record::K(data_fix)) => Ok(data_fix),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    'L' => self.comment_vec.push(match record {
+                        Ok(Record::L(comment)) => Ok(comment),
+                        Err(error) => Err(error),
+                        _ => unreachable!(),
+                    }),
+                    _ => return Err(IGCFileInitError(format!("{line} does not have a valid start letter"))),
                 }
-                None => return Err(IGCFileInitError(format!("{line} does not have a valid start letter"))),
+                if is_error { self.error_lines.insert((letter, self.count(letter) - 1), number); }
             }
+            None => return Err(IGCFileInitError(format!("{line} does not have a valid start letter"))),
+        }
+        Ok(())
+    }
+
+    /// Number of results for the record letter
+    fn count(&self, letter: char) -> usize {
+        match letter {
+            'A' => self.fr_ids.len(),
+            'B' => self.fixes.len(),
+            'C' => self.task_info.len(),
+            'D' => self.differential_gps_records.len(),
+            'E' => self.events.len(),
+            'F' => self.satellite_vec.len(),
+            'G' => self.security_vec.len(),
+            'H' => self.file_header_vec.len(),
+            'I' => self.i_extension_vec.len(),
+            'J' => self.j_extension_vec.len(),
+            'K' => self.data_fix_vec.len(),
+            'L' => self.comment_vec.len(),
+            _ => 0,
         }
+    }
 
-        Ok(Self {
-            fr_ids,
-            fixes,
-            task_info,
-            differential_gps_records,
-            events,
-            satellite_vec,
-            security_vec,
-            file_header_vec,
-            i_extension_vec,
-            j_extension_vec,
-            data_fix_vec,
-            comment_vec,
-        })
+    /// Line of the file, counting from 1, that the error at `index` of the results for the record letter came from
+    /// # examples
+    /// ```rust
+    /// use igc_parser::igc_file::IGCFile;
+    /// let igc_file = IGCFile::parse("HFDTEDATE:060820\nB1602405407121N00249342WA0028000421\nBROKEN").unwrap();
+    /// assert!(igc_file.get_fixes()[1].is_err());
+    /// assert_eq!(igc_file.error_line('B', 1), Some(3));
+    /// ```
+    pub fn error_line(&self, letter: char, index: usize) -> Option<usize> {
+        self.error_lines.get(&(letter, index)).copied()
     }
 
     pub fn get_fr_ids(&self) -> &Vec<Result<FlightRecorderID>> {
@@ -184,7 +245,7 @@ impl IGCFile {
         &self.security_vec
     }
 
-    pub fn get_file_header_vec(&self) -> &Vec<Result<FileHeader>> {
+    pub fn get_file_header_vec(&self) -> &Vec<Result<FileHeaderRecord>> {
         &self.file_header_vec
     }
 
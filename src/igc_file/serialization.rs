@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
+
+use super::IGCFile;
+use crate::error::{ErrorFields, IGCError};
+use crate::records::comment::Comment;
+use crate::records::data_fix::DataFix;
+use crate::records::diff_gps::DiffGPS;
+use crate::records::event::Event;
+use crate::records::extension::Extension;
+use crate::records::file_header::FileHeaderRecord;
+use crate::records::fix::Fix;
+use crate::records::flight_recorder_id::FlightRecorderID;
+use crate::records::satellite::Satellite;
+use crate::records::security::Security;
+use crate::records::task_info::TaskInfo;
+use crate::Result;
+
+/// One parsed line, `{"ok": record}` or `{"error": {"kind": ..., "line": ..., "message": ...}}`
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(rename = "{T}Entry"))]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Entry<T> {
+    Ok(T),
+    Error(ErrorFields),
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(rename = "IGCFile"))]
+pub(crate) struct IGCFileFields {
+    fr_ids: Vec<Entry<FlightRecorderID>>,
+    fixes: Vec<Entry<Fix>>,
+    task_info: Vec<Entry<TaskInfo>>,
+    differential_gps_records: Vec<Entry<DiffGPS>>,
+    events: Vec<Entry<Event>>,
+    satellite_vec: Vec<Entry<Satellite>>,
+    security_vec: Vec<Entry<Security>>,
+    file_header_vec: Vec<Entry<FileHeaderRecord>>,
+    i_extension_vec: Vec<Entry<Extension>>,
+    j_extension_vec: Vec<Entry<Extension>>,
+    data_fix_vec: Vec<Entry<DataFix>>,
+    comment_vec: Vec<Entry<Comment>>,
+}
+
+fn entries<T>(results: Vec<Result<T>>, letter: char, error_lines: &HashMap<(char, usize), usize>) -> Vec<Entry<T>> {
+    results.into_iter().enumerate().map(|(index, result)| match result {
+        Ok(record) => Entry::Ok(record),
+        Err(error) => Entry::Error(ErrorFields { line: error_lines.get(&(letter, index)).copied(), ..error.into() }),
+    }).collect()
+}
+
+fn results<T>(entries: Vec<Entry<T>>, letter: char, error_lines: &mut HashMap<(char, usize), usize>) -> std::result::Result<Vec<Result<T>>, String> {
+    entries.into_iter().enumerate().map(|(index, entry)| match entry {
+        Entry::Ok(record) => Ok(Ok(record)),
+        Entry::Error(fields) => {
+            if let Some(line) = fields.line { error_lines.insert((letter, index), line); }
+            Ok(Err(IGCError::try_from(fields)?))
+        }
+    }).collect()
+}
+
+impl From<IGCFile> for IGCFileFields {
+    fn from(file: IGCFile) -> Self {
+        let lines = &file.error_lines;
+        Self {
+            fr_ids: entries(file.fr_ids, 'A', lines),
+            fixes: entries(file.fixes, 'B', lines),
+            task_info: entries(file.task_info, 'C', lines),
+            differential_gps_records: entries(file.differential_gps_records, 'D', lines),
+            events: entries(file.events, 'E', lines),
+            satellite_vec: entries(file.satellite_vec, 'F', lines),
+            security_vec: entries(file.security_vec, 'G', lines),
+            file_header_vec: entries(file.file_header_vec, 'H', lines),
+            i_extension_vec: entries(file.i_extension_vec, 'I', lines),
+            j_extension_vec: entries(file.j_extension_vec, 'J', lines),
+            data_fix_vec: entries(file.data_fix_vec, 'K', lines),
+            comment_vec: entries(file.comment_vec, 'L', lines),
+        }
+    }
+}
+
+impl TryFrom<IGCFileFields> for IGCFile {
+    type Error = String;
+
+    fn try_from(fields: IGCFileFields) -> std::result::Result<Self, String> {
+        let mut lines = HashMap::new();
+        Ok(Self {
+            fr_ids: results(fields.fr_ids, 'A', &mut lines)?,
+            fixes: results(fields.fixes, 'B', &mut lines)?,
+            task_info: results(fields.task_info, 'C', &mut lines)?,
+            differential_gps_records: results(fields.differential_gps_records, 'D', &mut lines)?,
+            events: results(fields.events, 'E', &mut lines)?,
+            satellite_vec: results(fields.satellite_vec, 'F', &mut lines)?,
+            security_vec: results(fields.security_vec, 'G', &mut lines)?,
+            file_header_vec: results(fields.file_header_vec, 'H', &mut lines)?,
+            i_extension_vec: results(fields.i_extension_vec, 'I', &mut lines)?,
+            j_extension_vec: results(fields.j_extension_vec, 'J', &mut lines)?,
+            data_fix_vec: results(fields.data_fix_vec, 'K', &mut lines)?,
+            comment_vec: results(fields.comment_vec, 'L', &mut lines)?,
+            error_lines: lines,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use serde_json::json;
+
+    fn example() -> IGCFile {
+        IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn example_round_trip() {
+        let igc_file = example();
+        let serialised = serde_json::to_value(igc_file.clone()).unwrap();
+        let parsed: IGCFile = serde_json::from_value(serialised.clone()).unwrap();
+        assert_eq!(parsed.get_fixes().len(), igc_file.get_fixes().len());
+        assert_eq!(serde_json::to_value(parsed).unwrap(), serialised);
+    }
+
+    #[test]
+    fn records_are_tagged_with_decimal_degrees_and_iso_times() {
+        let igc_file = IGCFile::parse("HFDTEDATE:060820\nB1602405407121N00249342WA0028000421").unwrap();
+        let serialised = serde_json::to_value(igc_file).unwrap();
+        assert_eq!(serialised["file_header_vec"][0]["ok"]["header"], json!({"kind": "date", "value": "2020-08-06"}));
+        let fix = &serialised["fixes"][0]["ok"];
+        assert_eq!(fix["timestamp"], json!("16:02:40Z"));
+        assert_eq!(fix["coordinates"]["latitude"], json!(54.1186833));
+        assert_eq!(fix["coordinates"]["longitude"], json!(-2.8223667));
+    }
+
+    #[test]
+    fn errors_have_kind_line_and_message() {
+        let igc_file = IGCFile::parse("HFDTEDATE:060820\nB1602405407121N00249342WA0028000421\nBROKEN").unwrap();
+        let serialised = serde_json::to_value(igc_file).unwrap();
+        let error = &serialised["fixes"][1]["error"];
+        assert_eq!(error["kind"], json!("fix"));
+        assert_eq!(error["line"], json!(3));
+        assert!(error["message"].as_str().unwrap().contains("BROKEN"));
+        let parsed: IGCFile = serde_json::from_value(serialised).unwrap();
+        assert_eq!(parsed.error_line('B', 1), Some(3));
+        let unknown = json!({"error": {"kind": "unknown", "line": null, "message": ""}});
+        assert!(serde_json::from_value::<Entry<Fix>>(unknown).is_ok_and(|entry| IGCError::try_from(match entry {
+            Entry::Error(fields) => fields,
+            Entry::Ok(_) => unreachable!(),
+        }).is_err()));
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn schema_is_up_to_date() {
+        check_schema("./schema/igc_file.schema.json", schemars::schema_for!(IGCFile));
+        check_schema("./schema/igc_document.schema.json", schemars::schema_for!(crate::document::IGCDocument));
+    }
+
+    /// Compares the generated schema with the shipped file, or rewrites the file when `UPDATE_SCHEMA` is set
+    #[cfg(feature = "schema")]
+    fn check_schema(path: &str, schema: schemars::Schema) {
+        use serde_json::Value;
+        let generated = serde_json::to_string_pretty(&schema).unwrap() + "\n";
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            fs::write(path, &generated).unwrap();
+        }
+        let shipped: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(shipped, serde_json::from_str::<Value>(&generated).unwrap(), "{path} is out of date, run the tests with UPDATE_SCHEMA=1");
+    }
+}
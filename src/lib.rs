@@ -24,6 +24,7 @@
 //! println!("{}", valid_fixes.len())
 //! ```
 
+#[cfg(not(feature = "thread-safe"))]
 use std::rc::Rc;
 
 #[cfg(feature = "thread-safe")]
@@ -47,3 +48,26 @@ pub mod igc_file;
 pub mod error;
 /// Builder for a parser to parse only specific kinds of records
 pub mod parser_builder;
+/// Typed summary of the flight metadata from the headers and the A record
+pub mod metadata;
+/// IGC manufacturer codes
+pub mod manufacturer;
+/// Analysis of a parsed flight
+pub mod analysis;
+/// IGC files as ordered records for editing, anonymising and writing
+pub mod document;
+/// Simplification, resampling and time indexing of fix sequences
+pub mod track;
+/// Scripted synthetic flights with known ground truth for tests and demos
+pub mod synthetic;
+/// OpenAir airspace files and airspace infringement checking
+pub mod airspace;
+/// Parallel parsing of large files and batches of files, requires the `parallel` feature
+#[cfg(feature = "parallel")]
+pub mod parallel;
+/// Ground elevation from SRTM tiles and height above ground, requires the `terrain` feature
+#[cfg(feature = "terrain")]
+pub mod terrain;
+/// Arrow record batches and Parquet files of fixes, requires the `arrow` feature
+#[cfg(feature = "arrow")]
+pub mod arrow;
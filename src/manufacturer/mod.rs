@@ -0,0 +1,111 @@
+/// An IGC approved flight recorder manufacturer
+#[derive(Debug, PartialEq)]
+pub struct Manufacturer {
+    /// Three letter code used in A records and long file names
+    pub code: &'static str,
+    /// Legacy single character code used in short file names and short-form A records
+    pub short_code: Option<char>,
+    pub name: &'static str,
+    /// Known recorder models, not exhaustive
+    pub models: &'static [&'static str],
+}
+
+impl Manufacturer {
+    /// Finds the manufacturer of a three letter code, case insensitive
+    pub fn from_code(code: &str) -> Option<&'static Manufacturer> {
+        MANUFACTURERS.iter().find(|manufacturer| manufacturer.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Finds the manufacturer of a legacy single character code, case insensitive
+    pub fn from_short_code(short_code: char) -> Option<&'static Manufacturer> {
+        MANUFACTURERS
+            .iter()
+            .find(|manufacturer| manufacturer.short_code.is_some_and(|c| c.eq_ignore_ascii_case(&short_code)))
+    }
+
+    /// Finds the manufacturer of a code as written in an A record, either three letters or a single character
+    pub fn from_a_record_code(code: &str) -> Option<&'static Manufacturer> {
+        let mut chars = code.chars();
+        match (chars.next(), chars.next()) {
+            (Some(short_code), None) => Self::from_short_code(short_code),
+            _ => Self::from_code(code),
+        }
+    }
+
+    /// Prefix of the manufacturer specific L records this manufacturer writes, e.g. `LLXV` for LXNAV or `LFLA` for Flarm
+    pub fn comment_prefix(&self) -> String {
+        format!("L{}", self.code)
+    }
+}
+
+/// All manufacturers in the registry
+pub fn registry() -> &'static [Manufacturer] {
+    MANUFACTURERS
+}
+
+/// Name of the manufacturer for a three letter code as used in the A record, `None` if the code is unknown
+/// # examples
+/// ```rust
+/// use igc_parser::manufacturer::manufacturer_name;
+/// assert_eq!(manufacturer_name("FLA"), Some("Flarm"));
+/// assert_eq!(manufacturer_name("ABC"), None);
+/// ```
+pub fn manufacturer_name(code: &str) -> Option<&'static str> {
+    Manufacturer::from_code(code).map(|manufacturer| manufacturer.name)
+}
+
+const MANUFACTURERS: &[Manufacturer] = &[
+    Manufacturer { code: "ACT", short_code: Some('I'), name: "Aircotec", models: &["Top Navigator", "XC-Trainer"] },
+    Manufacturer { code: "CAM", short_code: Some('C'), name: "Cambridge Aero Instruments", models: &["Cambridge 302", "Cambridge 25", "Cambridge 20", "Cambridge 10"] },
+    Manufacturer { code: "CNI", short_code: None, name: "ClearNav Instruments", models: &["ClearNav"] },
+    Manufacturer { code: "DSX", short_code: Some('D'), name: "Data Swan/DSX", models: &["T-BOX"] },
+    Manufacturer { code: "EWA", short_code: Some('E'), name: "EW Avionics", models: &["EW Model D", "EW microRecorder"] },
+    Manufacturer { code: "FIL", short_code: Some('F'), name: "Filser", models: &["DX 50", "LX 20"] },
+    Manufacturer { code: "FLA", short_code: Some('G'), name: "Flarm", models: &["FLARM Classic", "PowerFLARM"] },
+    Manufacturer { code: "FLY", short_code: None, name: "Flytec", models: &[] },
+    Manufacturer { code: "GCS", short_code: Some('A'), name: "Garrecht", models: &["Volkslogger"] },
+    Manufacturer { code: "IMI", short_code: Some('M'), name: "IMI Gliding Equipment", models: &["Erixx"] },
+    Manufacturer { code: "LGS", short_code: None, name: "Logstream", models: &[] },
+    Manufacturer { code: "LXN", short_code: Some('L'), name: "LX Navigation", models: &["LX 5000", "LX 7007", "Colibri"] },
+    Manufacturer { code: "LXV", short_code: Some('V'), name: "LXNAV", models: &["Colibri II", "Nano", "LX 8000", "LX 9000"] },
+    Manufacturer { code: "NAV", short_code: None, name: "Naviter", models: &["Oudie"] },
+    Manufacturer { code: "NKL", short_code: Some('K'), name: "Nielsen Kellerman", models: &[] },
+    Manufacturer { code: "NTE", short_code: Some('N'), name: "New Technologies", models: &[] },
+    Manufacturer { code: "PES", short_code: Some('P'), name: "Peschges", models: &["VP8"] },
+    Manufacturer { code: "PFE", short_code: None, name: "PressFinish Electronics", models: &[] },
+    Manufacturer { code: "PRT", short_code: Some('R'), name: "Print Technik", models: &[] },
+    Manufacturer { code: "SCH", short_code: Some('H'), name: "Scheffel", models: &[] },
+    Manufacturer { code: "SDI", short_code: Some('S'), name: "Streamline Data Instruments", models: &[] },
+    Manufacturer { code: "TRI", short_code: Some('T'), name: "Triadis Engineering", models: &[] },
+    Manufacturer { code: "WES", short_code: Some('W'), name: "Westerboer", models: &[] },
+    Manufacturer { code: "XCS", short_code: None, name: "XCSoar", models: &["XCSoar"] },
+    Manufacturer { code: "ZAN", short_code: Some('Z'), name: "Zander", models: &[] },
+    Manufacturer { code: "XXX", short_code: Some('X'), name: "Other manufacturer", models: &[] },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        assert_eq!(Manufacturer::from_code("LXV").unwrap().name, "LXNAV");
+        assert_eq!(Manufacturer::from_code("lxv").unwrap().name, "LXNAV");
+        assert_eq!(Manufacturer::from_short_code('G').unwrap().code, "FLA");
+        assert_eq!(Manufacturer::from_a_record_code("L").unwrap().code, "LXN");
+        assert_eq!(Manufacturer::from_a_record_code("CAM").unwrap().short_code, Some('C'));
+        assert!(Manufacturer::from_code("ABC").is_none());
+        assert!(Manufacturer::from_short_code('Q').is_none());
+        assert_eq!(Manufacturer::from_code("FLA").unwrap().comment_prefix(), "LFLA");
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        for (i, manufacturer) in registry().iter().enumerate() {
+            assert!(registry()[i + 1..].iter().all(|other| other.code != manufacturer.code));
+            if let Some(short_code) = manufacturer.short_code {
+                assert!(registry()[i + 1..].iter().all(|other| other.short_code != Some(short_code)));
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::igc_file::IGCFile;
+use crate::records::file_header::{FileHeader, FileHeaderRecord};
+use crate::records::flight_recorder_id::FlightRecorderID;
+use crate::records::util::Date;
+use crate::{Result, StrWrapper};
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Summary of a flight built from the H records and the A record
+///
+/// Every field is `None` when the file does not contain it, or when the header is present but empty.
+/// If a header occurs more than once the first valid one is used
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::metadata::FlightMetadata;
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let metadata = FlightMetadata::from_igc_file(&IGCFile::parse(&file).unwrap());
+/// assert_eq!(metadata.registration.as_deref(), Some("OY-EXD"));
+/// assert_eq!(metadata.logger_manufacturer.as_deref(), Some("Flarm"));
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Default)]
+pub struct FlightMetadata {
+    pub date: Option<Date>,
+    pub pilot: Option<StrWrapper>,
+    pub co_pilot: Option<StrWrapper>,
+    pub glider_type: Option<StrWrapper>,
+    pub registration: Option<StrWrapper>,
+    pub competition_id: Option<StrWrapper>,
+    pub competition_class: Option<StrWrapper>,
+    /// Manufacturer name decoded from the code of the A record using the manufacturer registry
+    pub logger_manufacturer: Option<StrWrapper>,
+    /// The manufacturer code as written in the A record
+    pub logger_manufacturer_code: Option<StrWrapper>,
+    pub logger_serial: Option<StrWrapper>,
+    pub logger_type: Option<StrWrapper>,
+    pub firmware: Option<StrWrapper>,
+    pub hardware: Option<StrWrapper>,
+    pub gps_receiver: Option<StrWrapper>,
+    pub pressure_sensor: Option<StrWrapper>,
+    pub datum: Option<StrWrapper>,
+    /// Fix accuracy in meters
+    pub fix_accuracy: Option<u16>,
+}
+
+fn set_text(field: &mut Option<StrWrapper>, value: &StrWrapper) {
+    if field.is_none() && !value.is_empty() {
+        *field = Some(value.clone());
+    }
+}
+
+impl FlightMetadata {
+    pub fn from_igc_file(igc_file: &IGCFile) -> Self {
+        Self::from_records(igc_file.get_fr_ids(), igc_file.get_file_header_vec())
+    }
+
+    /// Builds the metadata from A and H records, e.g. from a parser builder that only parsed those
+    pub fn from_records(fr_ids: &[Result<FlightRecorderID>], headers: &[Result<FileHeaderRecord>]) -> Self {
+        let mut metadata = FlightMetadata::default();
+
+        if let Some(fr_id) = fr_ids.iter().find_map(|fr_id| fr_id.as_ref().ok()) {
+            metadata.logger_manufacturer = fr_id.manufacturer_info().map(|manufacturer| manufacturer.name.into());
+            set_text(&mut metadata.logger_manufacturer_code, &fr_id.manufacturer);
+            set_text(&mut metadata.logger_serial, &fr_id.id);
+        }
+
+        for header in headers.iter().filter_map(|header| header.as_ref().ok()) {
+            match &header.header {
+                FileHeader::Date(date) => { metadata.date.get_or_insert_with(|| date.clone()); },
+                FileHeader::FixAccuracy(accuracy) => { metadata.fix_accuracy.get_or_insert(*accuracy); },
+                FileHeader::PilotInCharge(pilot) => set_text(&mut metadata.pilot, pilot),
+                FileHeader::SecondPilot(co_pilot) => set_text(&mut metadata.co_pilot, co_pilot),
+                FileHeader::GliderType(glider_type) => set_text(&mut metadata.glider_type, glider_type),
+                FileHeader::GliderID(registration) => set_text(&mut metadata.registration, registration),
+                FileHeader::GPSDatum(datum) => set_text(&mut metadata.datum, datum),
+                FileHeader::Firmware(firmware) => set_text(&mut metadata.firmware, firmware),
+                FileHeader::Hardware(hardware) => set_text(&mut metadata.hardware, hardware),
+                FileHeader::LoggerType(logger_type) => set_text(&mut metadata.logger_type, logger_type),
+                FileHeader::GPSManufacturer(gps_receiver) => set_text(&mut metadata.gps_receiver, gps_receiver),
+                FileHeader::PressureSensor(pressure_sensor) => set_text(&mut metadata.pressure_sensor, pressure_sensor),
+                FileHeader::CompetitionID(competition_id) => set_text(&mut metadata.competition_id, competition_id),
+                FileHeader::CompetitionClass(competition_class) => set_text(&mut metadata.competition_class, competition_class),
+                _ => {}
+            }
+        }
+
+        metadata
+    }
+
+    /// Every text field by name, `None` for the ones the file does not give
+    pub fn text_fields(&self) -> [(&'static str, Option<&str>); 15] {
+        [
+            ("pilot", self.pilot.as_deref()),
+            ("co_pilot", self.co_pilot.as_deref()),
+            ("glider_type", self.glider_type.as_deref()),
+            ("registration", self.registration.as_deref()),
+            ("competition_id", self.competition_id.as_deref()),
+            ("competition_class", self.competition_class.as_deref()),
+            ("logger_manufacturer", self.logger_manufacturer.as_deref()),
+            ("logger_manufacturer_code", self.logger_manufacturer_code.as_deref()),
+            ("logger_serial", self.logger_serial.as_deref()),
+            ("logger_type", self.logger_type.as_deref()),
+            ("firmware", self.firmware.as_deref()),
+            ("hardware", self.hardware.as_deref()),
+            ("gps_receiver", self.gps_receiver.as_deref()),
+            ("pressure_sensor", self.pressure_sensor.as_deref()),
+            ("datum", self.datum.as_deref()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_from_file() {
+        let content = "AXXXABC FLIGHT:1
+HFFXA035
+HFDTE160701
+HFPLTPILOTINCHARGE: Bloggs Bill D
+HFCM2CREW2:
+HFGTYGLIDERTYPE: Schleicher ASH-25
+HFGIDGLIDERID: ABCD-1234
+HFDTM100GPSDATUM: WGS-1984
+HFCIDCOMPETITIONID: XYZ-78910
+HFCIDCOMPETITIONID: SECOND
+HFCCLCOMPETITIONCLASS:15m Motor Glider";
+        let metadata = FlightMetadata::from_igc_file(&IGCFile::parse(content).unwrap());
+        assert_eq!(metadata.date, Some(Date { d: 16, m: 7, y: 1 }));
+        assert_eq!(metadata.fix_accuracy, Some(35));
+        assert_eq!(metadata.pilot.as_deref(), Some("Bloggs Bill D"));
+        assert_eq!(metadata.co_pilot, None);
+        assert_eq!(metadata.glider_type.as_deref(), Some("Schleicher ASH-25"));
+        assert_eq!(metadata.registration.as_deref(), Some("ABCD-1234"));
+        assert_eq!(metadata.datum.as_deref(), Some("WGS-1984"));
+        assert_eq!(metadata.competition_id.as_deref(), Some("XYZ-78910"));
+        assert_eq!(metadata.competition_class.as_deref(), Some("15m Motor Glider"));
+        assert_eq!(metadata.logger_manufacturer.as_deref(), Some("Other manufacturer"));
+        assert_eq!(metadata.logger_manufacturer_code.as_deref(), Some("XXX"));
+        assert_eq!(metadata.logger_serial.as_deref(), Some("ABC"));
+        assert_eq!(metadata.firmware, None);
+
+        let fields = metadata.text_fields();
+        assert!(fields.contains(&("glider_type", Some("Schleicher ASH-25"))) && fields.contains(&("firmware", None)));
+    }
+
+    #[test]
+    fn empty_file() {
+        let metadata = FlightMetadata::from_igc_file(&IGCFile::parse("").unwrap());
+        assert!(metadata.date.is_none());
+        assert!(metadata.logger_manufacturer.is_none());
+    }
+}
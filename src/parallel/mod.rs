@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::error::IGCError::IGCFileInitError;
+use crate::igc_file::IGCFile;
+use crate::records::fix::Fix;
+use crate::Result;
+
+/// Lower bound on how many lines a single rayon task parses, so short lines are not split into tiny jobs
+pub(crate) const MIN_LINES_PER_TASK: usize = 1024;
+
+/// Parses all B records (fixes) of `content` in parallel chunks
+///
+/// The fixes are returned in the same order as in the file, and a line that fails to parse keeps its place as an `Err`
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::parallel;
+/// let file = fs::read_to_string("./examples/example.igc").unwrap();
+/// let fixes = parallel::parse_fixes(&file);
+/// println!("{}", fixes.len())
+/// ```
+pub fn parse_fixes(content: &str) -> Vec<Result<Fix>> {
+    let lines = content.lines().filter(|line| line.starts_with('B')).collect::<Vec<&str>>();
+    lines
+        .par_iter()
+        .with_min_len(MIN_LINES_PER_TASK)
+        .map(|line| Fix::parse(line))
+        .collect()
+}
+
+/// Parses several files concurrently, the n'th result belongs to the n'th content
+pub fn parse_files<S: AsRef<str> + Sync>(contents: &[S]) -> Vec<Result<IGCFile>> {
+    contents
+        .par_iter()
+        .map(|content| IGCFile::parse(content.as_ref()))
+        .collect()
+}
+
+/// Reads and parses every `.igc` file in the directory concurrently
+///
+/// The files are sorted by path, a file that can not be read or parsed gives an `Err` next to its path
+pub fn parse_directory(path: impl AsRef<Path>) -> Result<Vec<(PathBuf, Result<IGCFile>)>> {
+    let path = path.as_ref();
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => return Err(IGCFileInitError(format!("could not read directory '{}': {error}", path.display()))),
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("igc")))
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+
+    Ok(paths
+        .into_par_iter()
+        .map(|path| {
+            let igc_file = match fs::read_to_string(&path) {
+                Ok(content) => IGCFile::parse(&content),
+                Err(error) => Err(IGCFileInitError(format!("could not read '{}': {error}", path.display()))),
+            };
+            (path, igc_file)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_fixes_match_sequential() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let parallel = parse_fixes(&content);
+        let sequential = IGCFile::parse(&content).unwrap().get_fixes().clone();
+        assert_eq!(parallel.len(), sequential.len());
+        assert!(parallel.iter().zip(sequential.iter()).all(|(a, b)| match (a, b) {
+            (Ok(a), Ok(b)) => a == b,
+            (Err(a), Err(b)) => a.to_string() == b.to_string(),
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn errors_keep_their_position() {
+        let content = "B1602405407121N00249342WA002800042120509950\nB16024\nB1602455107126N00149300WA002880042919509020";
+        let fixes = parse_fixes(content);
+        assert!(fixes[0].is_ok());
+        assert!(fixes[1].is_err());
+        assert!(fixes[2].is_ok());
+    }
+
+    #[test]
+    fn files_are_parsed_in_order() {
+        let contents = ["HFDTE160701", "", "LCOMMENT"];
+        let parsed = parse_files(&contents);
+        assert_eq!(parsed[0].as_ref().unwrap().get_file_header_vec().len(), 1);
+        assert!(parsed[1].is_ok());
+        assert_eq!(parsed[2].as_ref().unwrap().get_comment_vec().len(), 1);
+    }
+
+    #[test]
+    fn parse_parallel_matches_parse() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let parallel = IGCFile::parse_parallel(&content).unwrap();
+        let sequential = IGCFile::parse(&content).unwrap();
+        assert_eq!(parallel.get_fixes().len(), sequential.get_fixes().len());
+        assert_eq!(parallel.get_comment_vec().len(), sequential.get_comment_vec().len());
+        assert!(IGCFile::parse_parallel("HFDTE160701\n\nLCOMMENT").is_err());
+    }
+
+    #[test]
+    fn builder_on_files() {
+        let contents = ["LCOMMENT\nE160245PEV", "E160245PEV\nE160305PEV"];
+        let parsed = crate::parser_builder::new_builder().parse_e_records().on_files(&contents);
+        assert_eq!(parsed[0].as_ref().unwrap().get_events().len(), 1);
+        assert_eq!(parsed[1].as_ref().unwrap().get_events().len(), 2);
+    }
+
+    #[test]
+    fn directory() {
+        let parsed = parse_directory("./examples").unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert!(parsed[0].1.is_ok());
+        assert!(parse_directory("./does_not_exist").is_err());
+    }
+}
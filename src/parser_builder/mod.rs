@@ -1,12 +1,10 @@
-use std::marker::PhantomData;
-
 use crate::error::IGCError::IGCFileInitError;
 use crate::records::comment::Comment;
 use crate::records::data_fix::DataFix;
 use crate::records::diff_gps::DiffGPS;
 use crate::records::event::Event;
 use crate::records::extension::Extension;
-use crate::records::file_header::FileHeader;
+use crate::records::file_header::FileHeaderRecord;
 use crate::records::fix::Fix;
 use crate::records::flight_recorder_id::FlightRecorderID;
 use crate::records::satellite::Satellite;
@@ -43,7 +41,7 @@ const L: bool,
     events: Option<Vec<Result<Event>>>,
     satellite_vec: Option<Vec<Result<Satellite>>>,
     security_vec: Option<Vec<Result<Security>>>,
-    file_header_vec: Option<Vec<Result<FileHeader>>>,
+    file_header_vec: Option<Vec<Result<FileHeaderRecord>>>,
     i_extension_vec: Option<Vec<Result<Extension>>>,
     j_extension_vec: Option<Vec<Result<Extension>>>,
     data_fix_vec: Option<Vec<Result<DataFix>>>,
@@ -247,13 +245,13 @@ const L: bool,
 > Parsed<A,B,C,D,E,F,G,true,I,J,K,L> {
     /// Get file headers from the parsed file
     /// Identical to `get_h_records`
-    pub fn get_file_header_vec(&self) -> &Vec<Result<FileHeader>> {
+    pub fn get_file_header_vec(&self) -> &Vec<Result<FileHeaderRecord>> {
         self.file_header_vec.as_ref().expect("uncreachable typestate error")
     }
 
     /// Get file headers from the parsed file
     /// Identical to `get_file_header_vec`
-    pub fn get_h_records(&self) -> &Vec<Result<FileHeader>> {
+    pub fn get_h_records(&self) -> &Vec<Result<FileHeaderRecord>> {
         self.get_file_header_vec()
     }
 }
@@ -428,7 +426,7 @@ impl<
             true => Some(Vec::new()),
             false => None,
         };
-        let mut file_header_vec: Option<Vec<Result<FileHeader>>> = match H {
+        let mut file_header_vec: Option<Vec<Result<FileHeaderRecord>>> = match H {
             true => Some(Vec::new()),
             false => None,
         };
@@ -588,6 +586,16 @@ impl<
             comment_vec,
         })
     }
+
+    /// Parses several files concurrently with this builder, the n'th result belongs to the n'th content
+    #[cfg(feature = "parallel")]
+    pub fn on_files<S: AsRef<str> + Sync>(self, contents: &[S]) -> Vec<Result<Parsed<A,B,C,D,E,F,G,H,I,J,K,L>>> {
+        use rayon::prelude::*;
+        contents
+            .par_iter()
+            .map(|content| self.clone().on_file(content.as_ref()))
+            .collect()
+    }
 }
 // Implementation for enabling parsing of flight record IDs when A is false.
 impl<
@@ -1,9 +1,14 @@
+use std::fmt;
+
 use crate::error::IGCError::CommentInitError;
+use crate::manufacturer::Manufacturer;
 use crate::{Result, StrWrapper};
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct Comment {
     pub content: StrWrapper,
@@ -11,8 +16,52 @@ pub struct Comment {
 
 impl Comment {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(CommentRef::parse(line)?.to_owned())
+    }
+
+    /// The manufacturer if this is a manufacturer specific comment such as `LFLA...` or `LLXV...`
+    pub fn manufacturer(&self) -> Option<&'static Manufacturer> {
+        self.content.get(0..3).and_then(Manufacturer::from_code)
+    }
+}
+
+impl fmt::Display for Comment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "L{}", self.content)
+    }
+}
+
+/// Borrowed counterpart of `Comment`
+#[derive(Debug, Clone)]
+pub struct CommentRef<'a> {
+    pub content: &'a str,
+}
+
+impl<'a> CommentRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.is_empty() { return Err(CommentInitError(format!("'{line}' is too short to be a comment")))}
-        let content = line[1..].to_string().into();
+        let content = &line[1..];
         Ok(Self {content})
     }
-}
\ No newline at end of file
+
+    /// The manufacturer if this is a manufacturer specific comment such as `LFLA...` or `LLXV...`
+    pub fn manufacturer(&self) -> Option<&'static Manufacturer> {
+        self.content.get(0..3).and_then(Manufacturer::from_code)
+    }
+
+    pub fn to_owned(&self) -> Comment {
+        Comment { content: self.content.into() }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manufacturer_comment() {
+        let comment = Comment::parse("LFLA124044 STEALTH OFF").unwrap();
+        assert_eq!(comment.manufacturer().unwrap().name, "Flarm");
+        assert!(Comment::parse("LCOMMENT").unwrap().manufacturer().is_none());
+        assert!(Comment::parse("L").unwrap().manufacturer().is_none());
+    }
+}
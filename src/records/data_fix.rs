@@ -1,10 +1,15 @@
+use std::fmt;
+
 use crate::{error::IGCError::DataFixInitError, StrWrapper};
+use crate::records::extension::Extension;
 use crate::records::util::Time;
 use crate::Result;
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct DataFix {
     pub time: Time,
@@ -13,11 +18,47 @@ pub struct DataFix {
 
 impl DataFix {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(DataFixRef::parse(line)?.to_owned())
+    }
+
+    /// The raw text of an extension declared in the J record, e.g. `TAS`
+    pub fn get_extension(&self, j_record: &Extension, code: &str) -> Option<&str> {
+        j_record.slice(&self.content, CONTENT_START, code)
+    }
+
+    /// The numeric value of an extension declared in the J record, `None` if it is missing or not a number
+    pub fn get_extension_value(&self, j_record: &Extension, code: &str) -> Option<i32> {
+        self.get_extension(j_record, code)?.trim().parse::<i32>().ok()
+    }
+}
+
+/// The content of a K record starts at byte 8
+pub(crate) const CONTENT_START: usize = 8;
+
+impl fmt::Display for DataFix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "K{}{}", self.time, self.content)
+    }
+}
+
+/// Borrowed counterpart of `DataFix`
+#[derive(Debug, Clone)]
+pub struct DataFixRef<'a> {
+    pub time: Time,
+    pub content: &'a str,
+}
+
+impl<'a> DataFixRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.len() < 7 { return Err(DataFixInitError(format!("'{line}' is too short to be parsed as a data fix"))) };
         let time = Time::parse(&line[1..7])?;
-        let content = line[7..].to_string().into();
+        let content = &line[7..];
         Ok(Self {time, content})
     }
+
+    pub fn to_owned(&self) -> DataFix {
+        DataFix { time: self.time.clone(), content: self.content.into() }
+    }
 }
 
 #[cfg(test)]
@@ -35,4 +76,13 @@ mod tests {
     fn too_short() {
         assert!(DataFix::parse("F160").is_err())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decoded_extensions() {
+        let j_record = Extension::parse("J020810TAS1113HDT").unwrap();
+        let data_fix = DataFix::parse("K160240095270").unwrap();
+        assert_eq!(data_fix.get_extension(&j_record, "TAS"), Some("095"));
+        assert_eq!(data_fix.get_extension_value(&j_record, "HDT"), Some(270));
+        assert_eq!(data_fix.get_extension(&j_record, "WDI"), None);
+    }
+}
@@ -1,16 +1,21 @@
+use std::fmt;
+
 use crate::error::IGCError::DiffGPSInitError;
 use crate::Result;
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct DiffGPS {
     pub qualifier: DiffGPSQualifier,
     pub dgps_station_id: u16,
 }
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq)]
 pub enum DiffGPSQualifier {
     GPS,
@@ -39,8 +44,17 @@ impl DiffGPS {
     }
 }
 
-#[cfg(test)]
+impl fmt::Display for DiffGPS {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let qualifier = match self.qualifier {
+            DiffGPSQualifier::GPS => 1,
+            DiffGPSQualifier::DGPS => 2,
+        };
+        write!(f, "D{qualifier}{:04}", self.dgps_station_id)
+    }
+}
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
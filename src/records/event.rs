@@ -1,10 +1,14 @@
+use std::fmt;
+
 use crate::{error::IGCError::EventInitError, StrWrapper};
-use crate::records::util::Time;
+use crate::records::util::{Date, Time};
 use crate::Result;
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct Event {
     pub time: Time,
@@ -12,18 +16,159 @@ pub struct Event {
     pub extension: StrWrapper,
 }
 
+/// The kind of an event from its three letter code
+///
+/// Codes that carry a value have it parsed from the extension, the value is `None` when it is missing or malformed
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    /// `PEV`, pilot event (pilot pressed the event button)
+    PilotEvent,
+    /// `ATS`, altimeter pressure setting in hPa
+    AltimeterSetting(Option<f32>),
+    /// `BFI`, blind flying instrument
+    BlindFlyingInstrument,
+    /// `CCN`, camera connected
+    CameraConnect,
+    /// `CDC`, camera disconnected
+    CameraDisconnect,
+    /// `CGD`, change of geodetic datum
+    ChangeOfGeodeticDatum,
+    /// `DTE`, the UTC date changed, e.g. after passing midnight or a restart of the recorder
+    DateChange(Option<Date>),
+    /// `EON`, engine on
+    EngineOn,
+    /// `EOF`, engine off
+    EngineOff,
+    /// `FXA`, fix accuracy in meters
+    FixAccuracy(Option<u16>),
+    /// `MOP`, means of propulsion reading
+    MeansOfPropulsion(Option<u16>),
+    /// `OAT`, outside air temperature in degrees Celsius
+    OutsideAirTemperature(Option<f32>),
+    /// `TEM`, temperature in degrees Celsius
+    Temperature(Option<f32>),
+    /// `UND`, undercarriage (landing gear) operated
+    Undercarriage,
+    /// `STA`, start of the task
+    Start,
+    /// `ONT`, on task
+    OnTask,
+    /// `TPC`, turn point confirmation
+    TurnPointConfirmation,
+    /// `FIN`, finish of the task
+    Finish,
+    /// `PFC`, post flight claim
+    PostFlightClaim,
+    /// `CMD`, a command given to the recorder, with its text
+    Command(StrWrapper),
+    /// `GPS`, the recorder reported a change of the GPS receiver status, with the status as written
+    GPSStatus(StrWrapper),
+    /// `LLX`, a recorder specific log entry, with its text
+    LogEntry(StrWrapper),
+    /// `ON`, the recorder was switched on, a second `ON` in a file is a restart
+    RecorderOn,
+    /// `OFF`, the recorder was switched off
+    RecorderOff,
+    /// Any other code such as manufacturer specific events
+    Other(StrWrapper),
+}
+
+impl EventKind {
+    pub fn parse(event_type: &str, extension: &str) -> Self {
+        let value = extension.trim();
+        match event_type {
+            "PEV" => EventKind::PilotEvent,
+            "ATS" => EventKind::AltimeterSetting(parse_pressure(value)),
+            "BFI" => EventKind::BlindFlyingInstrument,
+            "CCN" => EventKind::CameraConnect,
+            "CDC" => EventKind::CameraDisconnect,
+            "CGD" => EventKind::ChangeOfGeodeticDatum,
+            "DTE" => EventKind::DateChange(value.get(0..6).and_then(|date| Date::parse(date).ok())),
+            "EON" => EventKind::EngineOn,
+            "EOF" => EventKind::EngineOff,
+            "FXA" => EventKind::FixAccuracy(value.parse::<u16>().ok()),
+            "MOP" => EventKind::MeansOfPropulsion(value.parse::<u16>().ok()),
+            "OAT" => EventKind::OutsideAirTemperature(value.parse::<f32>().ok()),
+            "TEM" => EventKind::Temperature(value.parse::<f32>().ok()),
+            "UND" => EventKind::Undercarriage,
+            "STA" => EventKind::Start,
+            "ONT" => EventKind::OnTask,
+            "TPC" => EventKind::TurnPointConfirmation,
+            "FIN" => EventKind::Finish,
+            "PFC" => EventKind::PostFlightClaim,
+            "CMD" => EventKind::Command(value.into()),
+            "GPS" => EventKind::GPSStatus(value.into()),
+            "LLX" => EventKind::LogEntry(value.into()),
+            "ON" | "ON " => EventKind::RecorderOn,
+            "OFF" => EventKind::RecorderOff,
+            code => EventKind::Other(code.into()),
+        }
+    }
+}
+
+/// ATS is written in hPa with two implied decimals, `ATS102312` is 1023.12 hPa
+fn parse_pressure(value: &str) -> Option<f32> {
+    if value.contains('.') {
+        value.parse::<f32>().ok()
+    } else {
+        value.parse::<u32>().ok().map(|pressure| pressure as f32 / 100.)
+    }
+}
+
 impl Event {
     pub(crate) fn parse(line: &str) -> Result<Self> {
-        if line.len() < 10 { return Err(EventInitError(format!("'{line}' is too short to be parsed as an event record"))) };
-        let time = Time::parse(&line[1..7])?;
-        let event_type = line[7..10].to_string().into();
-        let extension = line[10..].to_string().into();
-        Ok(Self {time, event_type, extension})
+        Ok(EventRef::parse(line)?.to_owned())
     }
     
     pub fn is_pev(&self) -> bool {
         self.event_type.as_ref() == "PEV"
     }
+
+    pub fn kind(&self) -> EventKind {
+        EventKind::parse(&self.event_type, &self.extension)
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "E{}{}{}", self.time, self.event_type, self.extension)
+    }
+}
+
+/// Borrowed counterpart of `Event`
+#[derive(Debug, Clone)]
+pub struct EventRef<'a> {
+    pub time: Time,
+    pub event_type: &'a str,
+    pub extension: &'a str,
+}
+
+impl<'a> EventRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
+        if line.len() < 9 { return Err(EventInitError(format!("'{line}' is too short to be parsed as an event record"))) };
+        let time = Time::parse(&line[1..7])?;
+        // `ON` is the only two letter code
+        let (event_type, extension) = if line.len() == 9 { (&line[7..], "") } else { (&line[7..10], &line[10..]) };
+        Ok(Self {time, event_type, extension})
+    }
+
+    pub fn is_pev(&self) -> bool {
+        self.event_type == "PEV"
+    }
+
+    pub fn kind(&self) -> EventKind {
+        EventKind::parse(self.event_type, self.extension)
+    }
+
+    pub fn to_owned(&self) -> Event {
+        Event {
+            time: self.time.clone(),
+            event_type: self.event_type.into(),
+            extension: self.extension.into(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -42,5 +187,25 @@ mod tests {
 
         assert!(!event.is_pev());
     }
-}
 
+    #[test]
+    fn event_kinds() {
+        assert_eq!(Event::parse("E160245PEV").unwrap().kind(), EventKind::PilotEvent);
+        assert_eq!(Event::parse("E160245DTE170701").unwrap().kind(), EventKind::DateChange(Some(Date { d: 17, m: 7, y: 1 })));
+        assert_eq!(Event::parse("E160245DTE").unwrap().kind(), EventKind::DateChange(None));
+        assert_eq!(Event::parse("E160245ATS102312").unwrap().kind(), EventKind::AltimeterSetting(Some(1023.12)));
+        assert_eq!(Event::parse("E160245FXA035").unwrap().kind(), EventKind::FixAccuracy(Some(35)));
+        assert_eq!(Event::parse("E160245EON").unwrap().kind(), EventKind::EngineOn);
+        assert_eq!(Event::parse("E160245OAT-12.5").unwrap().kind(), EventKind::OutsideAirTemperature(Some(-12.5)));
+        assert_eq!(Event::parse("E160245LXAHRS").unwrap().kind(), EventKind::Other("LXA".into()));
+        assert_eq!(EventRef::parse("E160245MOP123").unwrap().kind(), EventKind::MeansOfPropulsion(Some(123)));
+        assert_eq!(Event::parse("E160245CMDSTART LOG").unwrap().kind(), EventKind::Command("START LOG".into()));
+        assert_eq!(Event::parse("E160245GPS3D FIX").unwrap().kind(), EventKind::GPSStatus("3D FIX".into()));
+        assert_eq!(Event::parse("E160245LLXRESET").unwrap().kind(), EventKind::LogEntry("RESET".into()));
+        assert_eq!(Event::parse("E160245ON").unwrap().kind(), EventKind::RecorderOn);
+        assert_eq!(Event::parse("E160245ON ").unwrap().kind(), EventKind::RecorderOn);
+        assert_eq!(Event::parse("E160245OFF").unwrap().kind(), EventKind::RecorderOff);
+        assert_eq!(Event::parse("E160245ON").unwrap().to_string(), "E160245ON");
+        assert!(Event::parse("E160245O").is_err());
+    }
+}
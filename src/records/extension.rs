@@ -1,28 +1,106 @@
-use std::num::ParseIntError;
+use std::fmt;
+
 use crate::{error::IGCError::ExtensionInitError, StrWrapper};
 use crate::Result;
 
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq)]
 pub enum ExtensionType {I, J}
 
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct Extension {
     pub extension_type: ExtensionType,
     pub number_of_extensions: u8,
+    /// Start byte, end byte and code of each extension, serialised as objects with `start`, `end` and `code`
+    #[cfg_attr(feature = "serde", serde(with = "extension_fields"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<ExtensionField>"))]
     pub extensions: Vec<(u8, u8, StrWrapper)>
 }
 
+/// The serialised form of one extension of an I or J record
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+struct ExtensionField {
+    start: u8,
+    end: u8,
+    code: StrWrapper,
+}
+
+#[cfg(feature = "serde")]
+mod extension_fields {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(extensions: &[(u8, u8, StrWrapper)], serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(extensions.iter().map(|(start, end, code)| ExtensionField { start: *start, end: *end, code: code.clone() }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<(u8, u8, StrWrapper)>, D::Error> {
+        Ok(Vec::<ExtensionField>::deserialize(deserializer)?.into_iter().map(|field| (field.start, field.end, field.code)).collect())
+    }
+}
+
 impl Extension {
     pub(crate) fn parse(line: &str) -> Result<Self> {
-        let extension_type = match &line[0..1] {
-            "I" => ExtensionType::I,
-            "J" => ExtensionType::J,
+        Ok(ExtensionRef::parse(line)?.to_owned())
+    }
+
+    /// Start and end byte (1-indexed, inclusive) in the B or K record for the extension with the three letter code
+    pub fn position(&self, code: &str) -> Option<(u8, u8)> {
+        self.extensions
+            .iter()
+            .find(|(_, _, extension_code)| extension_code.as_ref() == code)
+            .map(|(start, end, _)| (*start, *end))
+    }
+
+    pub fn has(&self, code: &str) -> bool {
+        self.position(code).is_some()
+    }
+
+    /// The part of `data` holding the extension with the three letter code, where `data` starts at byte `data_start` of the record
+    pub(crate) fn slice<'a>(&self, data: &'a str, data_start: usize, code: &str) -> Option<&'a str> {
+        let (start, end) = self.position(code)?;
+        let (start, end) = ((start as usize).checked_sub(data_start)?, (end as usize).checked_sub(data_start)?);
+        data.get(start..=end)
+    }
+}
+
+impl fmt::Display for Extension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let letter = match self.extension_type {
+            ExtensionType::I => 'I',
+            ExtensionType::J => 'J',
+        };
+        write!(f, "{letter}{:02}", self.number_of_extensions)?;
+        for (start, end, code) in &self.extensions {
+            write!(f, "{start:02}{end:02}{code}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Borrowed counterpart of `Extension`
+#[derive(Debug, Clone)]
+pub struct ExtensionRef<'a> {
+    pub extension_type: ExtensionType,
+    pub number_of_extensions: u8,
+    pub extensions: Vec<(u8, u8, &'a str)>
+}
+
+impl<'a> ExtensionRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
+        let extension_type = match line.get(0..1) {
+            Some("I") => ExtensionType::I,
+            Some("J") => ExtensionType::J,
             _ => return Err(ExtensionInitError(format!("'{line}' does not start with a valid prefix for an extension")))
         };
         if line.len() < 3 { return Err(ExtensionInitError(format!("'{line}' is too short to be parsed as a fix extension")))}
@@ -30,24 +108,30 @@ impl Extension {
             Ok(number_of_extensions) => number_of_extensions,
             Err(_) => return Err(ExtensionInitError(format!("'{line}' does not have a valid number of extensions field")))
         };
-        if line.len() != (3 + number_of_extensions * 7) as usize { return Err(ExtensionInitError(format!("'{line}' does not have the correct length according to number of extensions"))) }
-        let extensions = line[3..]
-            .chars()
-            .collect::<Vec<char>>()
-            .chunks(7)
-            .map(|c|
-                (c[0..2].iter().collect::<String>().parse::<u8>(),
-                 c[2..4].iter().collect::<String>().parse::<u8>(),
-                 c[4..7].iter().collect::<String>())).collect::<Vec<(core::result::Result<u8, ParseIntError>, core::result::Result<u8, ParseIntError>, String)>>();
-        if !extensions.iter().all(|(start, end, _)| start.is_ok() && end.is_ok()) {
-            return Err(ExtensionInitError(format!("'{line}' has invalid start/end characters")))
+        if line.len() != 3 + number_of_extensions as usize * 7 { return Err(ExtensionInitError(format!("'{line}' does not have the correct length according to number of extensions"))) }
+        let mut extensions = Vec::with_capacity(number_of_extensions as usize);
+        for n in 0..number_of_extensions as usize {
+            let offset = 3 + n * 7;
+            let (start, end, code) = match (line.get(offset..offset + 2), line.get(offset + 2..offset + 4), line.get(offset + 4..offset + 7)) {
+                (Some(start), Some(end), Some(code)) => (start, end, code),
+                _ => return Err(ExtensionInitError(format!("'{line}' has invalid characters in extension {n}")))
+            };
+            match (start.parse::<u8>(), end.parse::<u8>()) {
+                (Ok(start), Ok(end)) => extensions.push((start, end, code)),
+                _ => return Err(ExtensionInitError(format!("'{line}' has invalid start/end characters")))
+            }
         }
-        let extensions = extensions.into_iter().map(|(start, end, s)| {
-            (start.unwrap(), end.unwrap(), s.into())
-        }).collect::<Vec<_>>();
 
         Ok(Self {extension_type, number_of_extensions, extensions})
     }
+
+    pub fn to_owned(&self) -> Extension {
+        Extension {
+            extension_type: self.extension_type.clone(),
+            number_of_extensions: self.number_of_extensions,
+            extensions: self.extensions.iter().map(|&(start, end, code)| (start, end, code.into())).collect(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,9 +1,87 @@
+use std::fmt;
+
 use crate::{error::IGCError::FileHeaderInitError, StrWrapper};
 use crate::records::util::Date;
 use crate::Result;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
+
+/// Who entered the header, the letter after `H`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeaderSource {
+    /// `HF`, written by the flight recorder
+    FlightRecorder,
+    /// `HO`, entered by an official observer
+    OfficialObserver,
+    /// `HP`, entered by the pilot
+    Pilot,
+    /// `HS`, not defined by the spec but written by some loggers and flight software
+    Unspecified,
+}
+
+impl HeaderSource {
+    fn parse(line: &str) -> Result<Self> {
+        match line.get(1..2) {
+            Some("F") => Ok(HeaderSource::FlightRecorder),
+            Some("O") => Ok(HeaderSource::OfficialObserver),
+            Some("P") => Ok(HeaderSource::Pilot),
+            Some("S") => Ok(HeaderSource::Unspecified),
+            _ => Err(FileHeaderInitError(format!("'{line}' does not have a valid header source, expected F, O, P or S"))),
+        }
+    }
+
+    pub fn letter(&self) -> char {
+        match self {
+            HeaderSource::FlightRecorder => 'F',
+            HeaderSource::OfficialObserver => 'O',
+            HeaderSource::Pilot => 'P',
+            HeaderSource::Unspecified => 'S',
+        }
+    }
+}
+
+/// Reference system of the GPS altitude in the B records (`HFALG`)
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GPSAltitudeReference {
+    /// `GEO`, altitude above the geoid (mean sea level)
+    Geoid,
+    /// `ELL`, altitude above the WGS84 ellipsoid
+    Ellipsoid,
+}
 
+/// Reference of the pressure altitude in the B records (`HFALP`)
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PressureAltitudeReference {
+    /// `ISA`, standard atmosphere with 1013.25 hPa at sea level
+    ISA,
+    /// `MSL`, corrected to mean sea level with the actual QNH
+    MSL,
+}
+
+/// A parsed H record together with who entered it
+///
+/// `header` holds the normalised value (surrounding whitespace removed),
+/// `raw_value` is the text exactly as it was written after the long name
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone)]
+pub struct FileHeaderRecord {
+    pub source: HeaderSource,
+    pub header: FileHeader,
+    /// The long name between the three letter code and the `:`, e.g. `PILOTINCHARGE`
+    pub long_name: Option<StrWrapper>,
+    pub raw_value: StrWrapper,
+}
+
+/// Serialised as the snake case name of the header in `kind` with the typed value in `value`, e.g. `{"kind": "glider_id", "value": "D-1234"}`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(tag = "kind", content = "value", rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub enum FileHeader {
     Date(Date),
@@ -11,97 +89,319 @@ pub enum FileHeader {
     PilotInCharge(StrWrapper),
     SecondPilot(StrWrapper),
     GliderType(StrWrapper),
+    #[cfg_attr(feature = "serde", serde(rename = "glider_id"))]
     GliderID(StrWrapper),
+    #[cfg_attr(feature = "serde", serde(rename = "gps_datum"))]
     GPSDatum(StrWrapper),
     Firmware(StrWrapper),
     Hardware(StrWrapper),
     LoggerType(StrWrapper),
+    #[cfg_attr(feature = "serde", serde(rename = "gps_manufacturer"))]
     GPSManufacturer(StrWrapper),
     PressureSensor(StrWrapper),
+    #[cfg_attr(feature = "serde", serde(rename = "competition_id"))]
     CompetitionID(StrWrapper),
     CompetitionClass(StrWrapper),
+    /// Offset from UTC in hours (`HFTZN`)
+    TimeZone(f32),
+    #[cfg_attr(feature = "serde", serde(rename = "gps_altitude_reference"))]
+    GPSAltitudeReference(GPSAltitudeReference),
+    PressureAltitudeReference(PressureAltitudeReference),
+    /// Security status of the flight recorder (`HFFRS`)
+    SecurityStatus(StrWrapper),
+    /// Means of propulsion sensor (`HFMOP`)
+    MeansOfPropulsion(StrWrapper),
+    /// Site or airfield (`HFSIT`)
+    Site(StrWrapper),
+    /// Units of measure (`HFUNT`)
+    Units(StrWrapper),
+    Club(StrWrapper),
+    /// Official observer ID (`HOOOI`)
+    OfficialObserver(StrWrapper),
+    PilotBirthDate(Date),
+    SecondPilotBirthDate(Date),
+    /// Any header with a code that is not recognised
+    Unknown { code: StrWrapper, value: StrWrapper },
 }
 
-fn get_file_header_with_string_content<'a>(line: &'a str, header_name: &str) -> Result<&'a str> {
-    if line.len() < header_name.len() { return Err(FileHeaderInitError(format!("'{line}' does not have the correct length to be parsed as a pilot in charge record"))) };
-    Ok(&line[header_name.len()..])
+/// Known long names for each three letter code, compared without spaces, case and the legacy `NNN` prefix of `DTM`
+/// A code without an entry accepts any long name in front of the `:`
+fn known_long_names(code: &str) -> Option<&'static [&'static str]> {
+    match code {
+        "DTE" => Some(&["DATE"]),
+        "FXA" => Some(&["FIXACCURACY"]),
+        "PLT" => Some(&["PILOTINCHARGE", "PILOT"]),
+        "CM2" => Some(&["CREW2", "SECONDPILOT", "COPILOT", "CREW"]),
+        "GTY" => Some(&["GLIDERTYPE", "TYPE"]),
+        "GID" => Some(&["GLIDERID", "REGISTRATION", "GLIDERREGISTRATION"]),
+        "DTM" => Some(&["GPSDATUM", "DATUM"]),
+        "RFW" => Some(&["FIRMWAREVERSION", "FIRMWARE"]),
+        "RHW" => Some(&["HARDWAREVERSION", "HARDWARE"]),
+        "FTY" => Some(&["FRTYPE", "LOGGERTYPE", "TYPE"]),
+        "GPS" => Some(&["GPSRECEIVER", "RECEIVER", "GPS"]),
+        "PRS" => Some(&["PRESSALTSENSOR", "PRESSURESENSOR", "PRESSALT"]),
+        "CID" => Some(&["COMPETITIONID", "COMPID"]),
+        "CCL" => Some(&["COMPETITIONCLASS", "COMPCLASS", "CLASS"]),
+        "TZN" | "TZO" => Some(&["TIMEZONE", "TIMEZONEOFFSET"]),
+        "ALG" => Some(&["ALTGPS"]),
+        "ALP" => Some(&["ALTPRESSURE"]),
+        "MOP" => Some(&["MOPSENSOR", "SENSOR", "MEANSOFPROPULSION"]),
+        "SIT" => Some(&["SITE"]),
+        "UNT" => Some(&["UNITS"]),
+        "CLB" => Some(&["CLUB"]),
+        "OOI" => Some(&["OOID", "OFFICIALOBSERVER", "OFFICIALOBSERVERID"]),
+        "DB1" | "DB2" => Some(&["DATEOFBIRTH", "BIRTHDATE", "PILOTBIRTHDATE", "CREW2BIRTHDATE"]),
+        _ => None,
+    }
 }
 
-impl FileHeader {
+fn normalise_long_name(long_name: &str) -> String {
+    long_name
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Splits what follows the three letter code into the long name and the raw value
+///
+/// The value starts after the first `:` when the text in front of it is a known long name,
+/// a line without `:` only consisting of a known long name has an empty value,
+/// otherwise everything after the code is the value
+fn split_header_value<'a>(code: &str, rest: &'a str) -> (Option<&'a str>, &'a str) {
+    let long_names = known_long_names(code);
+    match rest.split_once(':') {
+        Some((long_name, value)) => {
+            let normalised = normalise_long_name(long_name);
+            match long_names {
+                Some(long_names) if !normalised.is_empty() && !long_names.contains(&normalised.as_str()) => (None, rest),
+                _ => (Some(long_name), value),
+            }
+        }
+        None if long_names.is_some_and(|long_names| long_names.contains(&normalise_long_name(rest).as_str())) => (Some(rest), ""),
+        None => (None, rest),
+    }
+}
+
+fn parse_date(line: &str, content: &str) -> Result<Date> {
+    match content.get(0..6) {
+        Some(date) => Date::parse(date),
+        None => Err(FileHeaderInitError(format!("'{line}' does not have the correct length to be parsed as a file header date"))),
+    }
+}
+
+impl FileHeaderRecord {
     pub(crate) fn parse(line: &str) -> Result<Self> {
-        match &line[0..5] {
-            "HFDTE" => {
-                if line.len() != 11 { 
-                    match &line[5..10]{
-                        "DATE:" =>
-                        { 
-                            let date = Date::parse(&line[10..16])?;
-                            Ok(FileHeader::Date(date))
-                        }
-                        _ => return Err(FileHeaderInitError(format!("'{line}' does not have the correct length to be parsed as a file header date"))) 
-                    }
-                } else {
-                    let date = Date::parse(&line[5..11])?;
-                    Ok(FileHeader::Date(date))
-                }
-            },
-            "HFFXA" => {
-                if line.len() != 8 { return Err(FileHeaderInitError(format!("'{line}' does not have the correct length to be parsed as a file header fix accuracy"))) };
-                let accuracy = line[6..8].parse::<u16>();
-                match accuracy {
-                    Ok(accuracy) => Ok(FileHeader::FixAccuracy(accuracy)),
-                    Err(_) => Err(FileHeaderInitError(format!("'{line}' can not be parsed as a fix accuracy number"))),
-                }
-            },
-            "HFPLT" => {
-                let pilot_in_charge = get_file_header_with_string_content(line, "HFPLTPILOTINCHARGE:")?;
-                Ok(FileHeader::PilotInCharge(pilot_in_charge.to_string().into()))
-            },
-            "HFCM2" => {
-                let second_pilot = get_file_header_with_string_content(line, "HFCM2CREW2:")?;
-                Ok(FileHeader::SecondPilot(second_pilot.to_string().into()))
-            },
-            "HFGTY" => {
-                let content = get_file_header_with_string_content(line, "HFGTYGLIDERTYPE:")?;
-                Ok(FileHeader::GliderType(content.to_string().into()))
-            },
-            "HFGID" => {
-                let content = get_file_header_with_string_content(line, "HFGIDGLIDERID:")?;
-                Ok(FileHeader::GliderID(content.to_string().into()))
-            },
-            "HFDTM" => {
-                let content = get_file_header_with_string_content(line, "HFDTMNNNGPSDATUM:")?;
-                Ok(FileHeader::GPSDatum(content.to_string().into()))
-            },
-            "HFRFW" => {
-                let content = get_file_header_with_string_content(line, "HFRFWFIRMWAREVERSION:")?;
-                Ok(FileHeader::Firmware(content.to_string().into()))
-            },
-            "HFRHW" => {
-                let content = get_file_header_with_string_content(line, "HFRHWHARDWAREVERSION:")?;
-                Ok(FileHeader::Hardware(content.to_string().into()))
-            },
-            "HFFTY" => {
-                let content = get_file_header_with_string_content(line, "HFFTYFRTYPE:")?;
-                Ok(FileHeader::LoggerType(content.to_string().into()))
-            },
-            "HFGPS" => {
-                let content = get_file_header_with_string_content(line, "HFGPS")?;
-                Ok(FileHeader::GPSManufacturer(content.to_string().into()))
+        Ok(FileHeaderRecordRef::parse(line)?.to_owned())
+    }
+
+    /// The same header with a new value, parsed again so the typed header matches
+    pub(crate) fn with_value(&self, value: &str) -> Option<Self> {
+        let long_name = self.long_name.as_deref().unwrap_or("");
+        Self::parse(&format!("H{}{}{long_name}:{value}", self.source.letter(), self.header.code())).ok()
+    }
+
+    /// The `HFDTE` header with a new date, keeping the flight number after it
+    pub(crate) fn with_date(&self, date: &Date) -> Option<Self> {
+        let rest = self.raw_value.trim_start().get(6..).unwrap_or("");
+        self.with_value(&format!("{date}{rest}"))
+    }
+}
+
+impl FileHeader {
+    /// The three letter code of the header, time zones are written as `TZN`
+    pub fn code(&self) -> &str {
+        match self {
+            FileHeader::Date(_) => "DTE",
+            FileHeader::FixAccuracy(_) => "FXA",
+            FileHeader::PilotInCharge(_) => "PLT",
+            FileHeader::SecondPilot(_) => "CM2",
+            FileHeader::GliderType(_) => "GTY",
+            FileHeader::GliderID(_) => "GID",
+            FileHeader::GPSDatum(_) => "DTM",
+            FileHeader::Firmware(_) => "RFW",
+            FileHeader::Hardware(_) => "RHW",
+            FileHeader::LoggerType(_) => "FTY",
+            FileHeader::GPSManufacturer(_) => "GPS",
+            FileHeader::PressureSensor(_) => "PRS",
+            FileHeader::CompetitionID(_) => "CID",
+            FileHeader::CompetitionClass(_) => "CCL",
+            FileHeader::TimeZone(_) => "TZN",
+            FileHeader::GPSAltitudeReference(_) => "ALG",
+            FileHeader::PressureAltitudeReference(_) => "ALP",
+            FileHeader::SecurityStatus(_) => "FRS",
+            FileHeader::MeansOfPropulsion(_) => "MOP",
+            FileHeader::Site(_) => "SIT",
+            FileHeader::Units(_) => "UNT",
+            FileHeader::Club(_) => "CLB",
+            FileHeader::OfficialObserver(_) => "OOI",
+            FileHeader::PilotBirthDate(_) => "DB1",
+            FileHeader::SecondPilotBirthDate(_) => "DB2",
+            FileHeader::Unknown { code, .. } => code,
+        }
+    }
+}
+
+impl fmt::Display for FileHeaderRecord {
+    /// The H record with the long name and the raw value, so `header` is not used except for the code
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = self.source.letter();
+        match &self.long_name {
+            Some(long_name) => write!(f, "H{source}{}{long_name}:{}", self.header.code(), self.raw_value),
+            None => write!(f, "H{source}{}{}", self.header.code(), self.raw_value),
+        }
+    }
+}
+
+/// Borrowed counterpart of `FileHeaderRecord`
+#[derive(Debug, Clone)]
+pub struct FileHeaderRecordRef<'a> {
+    pub source: HeaderSource,
+    pub header: FileHeaderRef<'a>,
+    pub long_name: Option<&'a str>,
+    pub raw_value: &'a str,
+}
+
+impl<'a> FileHeaderRecordRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
+        let source = HeaderSource::parse(line)?;
+        let (code, rest) = match (line.get(2..5), line.get(5..)) {
+            (Some(code), Some(rest)) => (code, rest),
+            _ => return Err(FileHeaderInitError(format!("'{line}' is too short to be parsed as a file header"))),
+        };
+        let (long_name, raw_value) = split_header_value(code, rest);
+        let header = FileHeaderRef::from_value(line, code, rest, raw_value.trim())?;
+        Ok(Self { source, header, long_name, raw_value })
+    }
+
+    pub fn to_owned(&self) -> FileHeaderRecord {
+        FileHeaderRecord {
+            source: self.source,
+            header: self.header.to_owned(),
+            long_name: self.long_name.map(|long_name| long_name.into()),
+            raw_value: self.raw_value.into(),
+        }
+    }
+}
+
+/// Borrowed counterpart of `FileHeader`
+#[derive(Debug, Clone)]
+pub enum FileHeaderRef<'a> {
+    Date(Date),
+    FixAccuracy(u16),
+    PilotInCharge(&'a str),
+    SecondPilot(&'a str),
+    GliderType(&'a str),
+    GliderID(&'a str),
+    GPSDatum(&'a str),
+    Firmware(&'a str),
+    Hardware(&'a str),
+    LoggerType(&'a str),
+    GPSManufacturer(&'a str),
+    PressureSensor(&'a str),
+    CompetitionID(&'a str),
+    CompetitionClass(&'a str),
+    TimeZone(f32),
+    GPSAltitudeReference(GPSAltitudeReference),
+    PressureAltitudeReference(PressureAltitudeReference),
+    SecurityStatus(&'a str),
+    MeansOfPropulsion(&'a str),
+    Site(&'a str),
+    Units(&'a str),
+    Club(&'a str),
+    OfficialObserver(&'a str),
+    PilotBirthDate(Date),
+    SecondPilotBirthDate(Date),
+    Unknown { code: &'a str, value: &'a str },
+}
+
+impl<'a> FileHeaderRef<'a> {
+    /// Parses the header content of an H record, the source letter is not checked
+    pub fn parse(line: &'a str) -> Result<Self> {
+        let (code, rest) = match (line.get(2..5), line.get(5..)) {
+            (Some(code), Some(rest)) => (code, rest),
+            _ => return Err(FileHeaderInitError(format!("'{line}' is too short to be parsed as a file header"))),
+        };
+        let (_, raw_value) = split_header_value(code, rest);
+        FileHeaderRef::from_value(line, code, rest, raw_value.trim())
+    }
+
+    /// `rest` is everything after the code and `value` is the normalised value
+    fn from_value(line: &str, code: &'a str, rest: &'a str, value: &'a str) -> Result<Self> {
+        match code {
+            "DTE" => Ok(FileHeaderRef::Date(parse_date(line, value)?)),
+            "FXA" => match value.parse::<u16>() {
+                Ok(accuracy) => Ok(FileHeaderRef::FixAccuracy(accuracy)),
+                Err(_) => Err(FileHeaderInitError(format!("'{line}' can not be parsed as a fix accuracy number"))),
             },
-            "HFPRS" => {
-                let content = get_file_header_with_string_content(line, "HFPRSPRESSALTSENSOR:")?;
-                Ok(FileHeader::PressureSensor(content.to_string().into()))
+            "PLT" => Ok(FileHeaderRef::PilotInCharge(value)),
+            "CM2" => Ok(FileHeaderRef::SecondPilot(value)),
+            "GTY" => Ok(FileHeaderRef::GliderType(value)),
+            "GID" => Ok(FileHeaderRef::GliderID(value)),
+            "DTM" => Ok(FileHeaderRef::GPSDatum(value)),
+            "RFW" => Ok(FileHeaderRef::Firmware(value)),
+            "RHW" => Ok(FileHeaderRef::Hardware(value)),
+            "FTY" => Ok(FileHeaderRef::LoggerType(value)),
+            "GPS" => Ok(FileHeaderRef::GPSManufacturer(value)),
+            "PRS" => Ok(FileHeaderRef::PressureSensor(value)),
+            "CID" => Ok(FileHeaderRef::CompetitionID(value)),
+            "CCL" => Ok(FileHeaderRef::CompetitionClass(value)),
+            "TZN" | "TZO" => match value.parse::<f32>() {
+                Ok(offset) if (-14.0..=14.0).contains(&offset) => Ok(FileHeaderRef::TimeZone(offset)),
+                _ => Err(FileHeaderInitError(format!("'{line}' can not be parsed as a time zone offset in hours"))),
             },
-            "HFCID" => {
-                let content = get_file_header_with_string_content(line, "HFCIDCOMPETITIONID:")?;
-                Ok(FileHeader::CompetitionID(content.to_string().into()))
+            "ALG" => match value {
+                "GEO" => Ok(FileHeaderRef::GPSAltitudeReference(GPSAltitudeReference::Geoid)),
+                "ELL" => Ok(FileHeaderRef::GPSAltitudeReference(GPSAltitudeReference::Ellipsoid)),
+                _ => Err(FileHeaderInitError(format!("'{line}' is not a GPS altitude reference, expected GEO or ELL"))),
             },
-            "HFCCL" => {
-                let content = get_file_header_with_string_content(line, "HFCCLCOMPETITIONCLASS:")?;
-                Ok(FileHeader::CompetitionClass(content.to_string().into()))
+            "ALP" => match value {
+                "ISA" => Ok(FileHeaderRef::PressureAltitudeReference(PressureAltitudeReference::ISA)),
+                "MSL" => Ok(FileHeaderRef::PressureAltitudeReference(PressureAltitudeReference::MSL)),
+                _ => Err(FileHeaderInitError(format!("'{line}' is not a pressure altitude reference, expected ISA or MSL"))),
             },
-            _ => Err(FileHeaderInitError(format!("'{line}' does not have a valid file header start")))
+            // the long name carries the status, e.g. HFFRSSECURITYOK or HFFRSSECSUSPECTUSEVALIPROGRAM:text
+            "FRS" => Ok(FileHeaderRef::SecurityStatus(rest.trim())),
+            "MOP" => Ok(FileHeaderRef::MeansOfPropulsion(value)),
+            "SIT" => Ok(FileHeaderRef::Site(value)),
+            "UNT" => Ok(FileHeaderRef::Units(value)),
+            "CLB" => Ok(FileHeaderRef::Club(value)),
+            "OOI" => Ok(FileHeaderRef::OfficialObserver(value)),
+            "DB1" => Ok(FileHeaderRef::PilotBirthDate(parse_date(line, value)?)),
+            "DB2" => Ok(FileHeaderRef::SecondPilotBirthDate(parse_date(line, value)?)),
+            _ => Ok(FileHeaderRef::Unknown { code, value }),
+        }
+    }
+
+    pub fn to_owned(&self) -> FileHeader {
+        match self {
+            FileHeaderRef::Date(date) => FileHeader::Date(date.clone()),
+            FileHeaderRef::FixAccuracy(accuracy) => FileHeader::FixAccuracy(*accuracy),
+            FileHeaderRef::PilotInCharge(content) => FileHeader::PilotInCharge((*content).into()),
+            FileHeaderRef::SecondPilot(content) => FileHeader::SecondPilot((*content).into()),
+            FileHeaderRef::GliderType(content) => FileHeader::GliderType((*content).into()),
+            FileHeaderRef::GliderID(content) => FileHeader::GliderID((*content).into()),
+            FileHeaderRef::GPSDatum(content) => FileHeader::GPSDatum((*content).into()),
+            FileHeaderRef::Firmware(content) => FileHeader::Firmware((*content).into()),
+            FileHeaderRef::Hardware(content) => FileHeader::Hardware((*content).into()),
+            FileHeaderRef::LoggerType(content) => FileHeader::LoggerType((*content).into()),
+            FileHeaderRef::GPSManufacturer(content) => FileHeader::GPSManufacturer((*content).into()),
+            FileHeaderRef::PressureSensor(content) => FileHeader::PressureSensor((*content).into()),
+            FileHeaderRef::CompetitionID(content) => FileHeader::CompetitionID((*content).into()),
+            FileHeaderRef::CompetitionClass(content) => FileHeader::CompetitionClass((*content).into()),
+            FileHeaderRef::TimeZone(offset) => FileHeader::TimeZone(*offset),
+            FileHeaderRef::GPSAltitudeReference(reference) => FileHeader::GPSAltitudeReference(*reference),
+            FileHeaderRef::PressureAltitudeReference(reference) => FileHeader::PressureAltitudeReference(*reference),
+            FileHeaderRef::SecurityStatus(content) => FileHeader::SecurityStatus((*content).into()),
+            FileHeaderRef::MeansOfPropulsion(content) => FileHeader::MeansOfPropulsion((*content).into()),
+            FileHeaderRef::Site(content) => FileHeader::Site((*content).into()),
+            FileHeaderRef::Units(content) => FileHeader::Units((*content).into()),
+            FileHeaderRef::Club(content) => FileHeader::Club((*content).into()),
+            FileHeaderRef::OfficialObserver(content) => FileHeader::OfficialObserver((*content).into()),
+            FileHeaderRef::PilotBirthDate(date) => FileHeader::PilotBirthDate(date.clone()),
+            FileHeaderRef::SecondPilotBirthDate(date) => FileHeader::SecondPilotBirthDate(date.clone()),
+            FileHeaderRef::Unknown { code, value } => FileHeader::Unknown { code: (*code).into(), value: (*value).into() },
         }
     }
 }
@@ -112,21 +412,83 @@ mod tests {
 
 
     #[test]
-    fn get_file_header_with_string_content_test() {
-        let line = "HFPLTPILOTINCHARGE:John Doe";
-        let header_name = "HFPLTPILOTINCHARGE:";
-        let result = get_file_header_with_string_content(line, header_name);
-        assert_eq!(result.unwrap(), "John Doe");
-
-        // too short
-        let line = "HFPLTPILOTINCHARGE";
-        let header_name = "HFPLTPILOTINCHARGE:";
-        let result = get_file_header_with_string_content(line, header_name);
-        assert!(result.is_err());
+    fn split_header_value_test() {
+        assert_eq!(split_header_value("PLT", "PILOTINCHARGE:John Doe"), (Some("PILOTINCHARGE"), "John Doe"));
+        assert_eq!(split_header_value("PLT", "PILOT:John Doe"), (Some("PILOT"), "John Doe"));
+        assert_eq!(split_header_value("PLT", "Pilot In Charge: John Doe"), (Some("Pilot In Charge"), " John Doe"));
+        assert_eq!(split_header_value("PLT", "PILOTINCHARGE"), (Some("PILOTINCHARGE"), ""));
+        assert_eq!(split_header_value("PLT", ":John Doe"), (Some(""), "John Doe"));
+        assert_eq!(split_header_value("DTM", "100GPSDATUM:WGS-1984"), (Some("100GPSDATUM"), "WGS-1984"));
+        assert_eq!(split_header_value("GPS", "MarconiCanada: Superstar"), (None, "MarconiCanada: Superstar"));
+        assert_eq!(split_header_value("DTE", "160701"), (None, "160701"));
+        assert_eq!(split_header_value("XYZ", "ANYTHING:value"), (Some("ANYTHING"), "value"));
 
         let line = "HFFXA1AB";
-        assert!(FileHeader::parse(line).is_err());
+        assert!(FileHeaderRecord::parse(line).is_err());
         let line = "HABCD123";
-        assert!(FileHeader::parse(line).is_err());
+        assert!(FileHeaderRecord::parse(line).is_err());
+    }
+
+    #[test]
+    fn long_name_variants() {
+        let record = FileHeaderRecord::parse("HFPLTPILOT:John").unwrap();
+        assert!(matches!(record.header, FileHeader::PilotInCharge(pilot) if pilot.as_ref() == "John"));
+        let record = FileHeaderRecord::parse("HFDTMGPSDATUM:WGS84").unwrap();
+        assert!(matches!(record.header, FileHeader::GPSDatum(datum) if datum.as_ref() == "WGS84"));
+        let record = FileHeaderRecord::parse("HFGIDREGISTRATION:OY-EXD").unwrap();
+        assert!(matches!(record.header, FileHeader::GliderID(id) if id.as_ref() == "OY-EXD"));
+        let record = FileHeaderRecord::parse("HFPLTPILOTINCHARGE: LASSE EDSLEV ").unwrap();
+        assert!(matches!(record.header, FileHeader::PilotInCharge(pilot) if pilot.as_ref() == "LASSE EDSLEV"));
+        assert_eq!(record.raw_value.as_ref(), " LASSE EDSLEV ");
+        assert_eq!(record.long_name.unwrap().as_ref(), "PILOTINCHARGE");
+        let record = FileHeaderRecord::parse("HFCM2CREW2:").unwrap();
+        assert!(matches!(record.header, FileHeader::SecondPilot(crew) if crew.is_empty()));
+    }
+
+    #[test]
+    fn source_is_recorded() {
+        let record = FileHeaderRecord::parse("HFPLTPILOTINCHARGE:John Doe").unwrap();
+        assert_eq!(record.source, HeaderSource::FlightRecorder);
+        let record = FileHeaderRecord::parse("HOPLTPILOTINCHARGE:John Doe").unwrap();
+        assert_eq!(record.source, HeaderSource::OfficialObserver);
+        let record = FileHeaderRecord::parse("HPGTYGLIDERTYPE:LS 4").unwrap();
+        assert_eq!(record.source, HeaderSource::Pilot);
+        assert!(matches!(record.header, FileHeader::GliderType(glider) if glider.as_ref() == "LS 4"));
+        let record = FileHeaderRecord::parse("HSCIDCOMPETITIONID:LE").unwrap();
+        assert_eq!(record.source, HeaderSource::Unspecified);
+        assert_eq!(record.to_string(), "HSCIDCOMPETITIONID:LE");
+        assert!(FileHeaderRecord::parse("HXCIDCOMPETITIONID:LE").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fix_accuracy_uses_all_digits() {
+        assert!(matches!(FileHeaderRecord::parse("HFFXA500").unwrap().header, FileHeader::FixAccuracy(500)));
+        assert!(matches!(FileHeaderRecord::parse("HFFXA035").unwrap().header, FileHeader::FixAccuracy(35)));
+    }
+
+    #[test]
+    fn modern_headers() {
+        assert!(matches!(FileHeaderRecord::parse("HFTZNTIMEZONE:+2").unwrap().header, FileHeader::TimeZone(offset) if offset == 2.0));
+        assert!(matches!(FileHeaderRecord::parse("HFTZNTIMEZONE:5.5").unwrap().header, FileHeader::TimeZone(offset) if offset == 5.5));
+        assert!(FileHeaderRecord::parse("HFTZNTIMEZONE:abc").is_err());
+        assert!(matches!(FileHeaderRecord::parse("HFALGALTGPS:GEO").unwrap().header, FileHeader::GPSAltitudeReference(GPSAltitudeReference::Geoid)));
+        assert!(matches!(FileHeaderRecord::parse("HFALPALTPRESSURE:ISA").unwrap().header, FileHeader::PressureAltitudeReference(PressureAltitudeReference::ISA)));
+        assert!(FileHeaderRecord::parse("HFALPALTPRESSURE:XYZ").is_err());
+        assert!(matches!(FileHeaderRecord::parse("HFSITSITE:Lasham").unwrap().header, FileHeader::Site(site) if site.as_ref() == "Lasham"));
+        assert!(matches!(FileHeaderRecord::parse("HFDB1PILOTBIRTHDATE:030476").unwrap().header, FileHeader::PilotBirthDate(Date { d: 3, m: 4, y: 76 })));
+        assert!(matches!(FileHeaderRecord::parse("HFDTEDATE:060820,01").unwrap().header, FileHeader::Date(Date { d: 6, m: 8, y: 20 })));
+        assert!(matches!(FileHeaderRecord::parse("HFFRSSECURITYOK").unwrap().header, FileHeader::SecurityStatus(status) if status.as_ref() == "SECURITYOK"));
+    }
+
+    #[test]
+    fn unknown_headers() {
+        match FileHeaderRecord::parse("HFXYZSOMETHING:some value").unwrap().header {
+            FileHeader::Unknown { code, value } => {
+                assert_eq!(code.as_ref(), "XYZ");
+                assert_eq!(value.as_ref(), "some value");
+            }
+            _ => panic!("expected an unknown header"),
+        }
+        assert!(FileHeaderRecord::parse("HFXY").is_err());
+    }
+}
@@ -1,11 +1,14 @@
-use std::rc::Rc;
+use std::fmt;
 
+use crate::records::extension::Extension;
 use crate::records::util::{Coordinate, Time};
 use crate::error::IGCError::FixInitError;
 use crate::{Result, StrWrapper};
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq)]
 /// Fix
 pub struct Fix {
@@ -16,8 +19,56 @@ pub struct Fix {
     pub extension: StrWrapper,
 }
 
+/// The extension data of a B record starts at byte 36
+pub(crate) const EXTENSION_START: usize = 36;
+
 impl Fix {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(FixRef::parse(line)?.to_owned())
+    }
+
+    /// The raw text of an extension declared in the I record, e.g. `ENL`
+    /// # examples
+    /// ```rust
+    /// use igc_parser::records::Record;
+    /// let i_record = match Record::parse("I033638FXA3940SIU4143ENL").unwrap() { Record::I(i) => i, _ => unreachable!() };
+    /// let fix = match Record::parse("B1602405407121N00249342WA002800042120509950").unwrap() { Record::B(f) => f, _ => unreachable!() };
+    /// assert_eq!(fix.get_extension(&i_record, "FXA"), Some("205"));
+    /// assert_eq!(fix.get_extension_value(&i_record, "ENL"), Some(950));
+    /// ```
+    pub fn get_extension(&self, i_record: &Extension, code: &str) -> Option<&str> {
+        i_record.slice(&self.extension, EXTENSION_START, code)
+    }
+
+    /// The numeric value of an extension declared in the I record, `None` if it is missing or not a number
+    pub fn get_extension_value(&self, i_record: &Extension, code: &str) -> Option<i32> {
+        self.get_extension(i_record, code)?.trim().parse::<i32>().ok()
+    }
+}
+
+impl fmt::Display for Fix {
+    /// The B record, a fix without a valid GPS altitude is written with `V` and a GPS altitude of zero
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (validity, gps_alt) = match self.gps_alt {
+            Some(gps_alt) => ('A', gps_alt),
+            None => ('V', 0),
+        };
+        write!(f, "B{}{}{validity}{:05}{gps_alt:05}{}", self.timestamp, self.coordinates, self.pressure_alt, self.extension)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Borrowed counterpart of `Fix` where the extension references the parsed line instead of being allocated
+pub struct FixRef<'a> {
+    pub timestamp: Time,
+    pub coordinates: Coordinate,
+    pub pressure_alt: i16,
+    pub gps_alt: Option<i16>, //option because of validity flag
+    pub extension: &'a str,
+}
+
+impl<'a> FixRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.chars().count() < 35 {
             return Err(FixInitError(format!("\"{}\" is too short to be parsed as a fix", line)))
         }
@@ -39,10 +90,10 @@ impl Fix {
             Err(_) => return Err(FixInitError(format!("\"{}\" could not parse pressure altitude", line)))
         };
 
-        let extension = line[35..].to_string().into();
+        let extension = &line[35..];
 
         Ok(
-            Fix {
+            FixRef {
                 timestamp,
                 coordinates,
                 pressure_alt,
@@ -51,9 +102,45 @@ impl Fix {
             }
         )
     }
+
+    /// The raw text of an extension declared in the I record, e.g. `ENL`
+    pub fn get_extension(&self, i_record: &Extension, code: &str) -> Option<&'a str> {
+        i_record.slice(self.extension, EXTENSION_START, code)
+    }
+
+    /// The numeric value of an extension declared in the I record, `None` if it is missing or not a number
+    pub fn get_extension_value(&self, i_record: &Extension, code: &str) -> Option<i32> {
+        self.get_extension(i_record, code)?.trim().parse::<i32>().ok()
+    }
+
+    /// Converts into the owned `Fix`, this is where the extension gets allocated
+    pub fn to_owned(&self) -> Fix {
+        Fix {
+            timestamp: self.timestamp.clone(),
+            coordinates: self.coordinates.clone(),
+            pressure_alt: self.pressure_alt,
+            gps_alt: self.gps_alt,
+            extension: self.extension.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Fix {
+    /// A fix for tests `seconds` after midnight, wrapping past midnight, with the same pressure and GPS altitude
+    pub(crate) fn at(seconds: u32, latitude: f64, longitude: f64, altitude: i16) -> Self {
+        Fix {
+            timestamp: Time::from_seconds_since_midnight(seconds % 86400).unwrap(),
+            coordinates: Coordinate::from_decimal_degrees(latitude, longitude),
+            pressure_alt: altitude,
+            gps_alt: Some(altitude),
+            extension: "".into(),
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use crate::records::Record;
     use crate::records::util::{Latitude, Longitude};
@@ -92,6 +179,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fix_ref_borrows_extension() {
+        let line = "B0941395152202N00032723WA001140015000854106968064092190039002770100";
+        let fix_ref = FixRef::parse(line).unwrap();
+        assert_eq!(fix_ref.extension, "00854106968064092190039002770100");
+        assert!(std::ptr::eq(fix_ref.extension.as_ptr(), line[35..].as_ptr()));
+        assert_eq!(fix_ref.to_owned(), Fix::parse(line).unwrap());
+    }
+
     #[test]
     fn fix_too_short() {
         assert!(Fix::parse("B0941395152202N00032723WA001140015").is_err())
@@ -110,4 +206,18 @@ mod tests {
         let line = "B0941395152202N00032723WB001140015000854106968064092190039002770100";
         assert!(Fix::parse(line).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decoded_extensions() {
+        let i_record = Extension::parse("I033638FXA3940SIU4143ENL").unwrap();
+        let fix = Fix::parse("B1602405407121N00249342WA002800042120509950").unwrap();
+        assert_eq!(fix.get_extension(&i_record, "FXA"), Some("205"));
+        assert_eq!(fix.get_extension_value(&i_record, "SIU"), Some(9));
+        assert_eq!(fix.get_extension_value(&i_record, "ENL"), Some(950));
+        assert_eq!(fix.get_extension(&i_record, "RPM"), None);
+
+        // declared but the line is too short
+        let fix = Fix::parse("B1602405407121N00249342WA0028000421205").unwrap();
+        assert_eq!(fix.get_extension(&i_record, "ENL"), None);
+    }
+}
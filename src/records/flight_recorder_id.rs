@@ -1,8 +1,13 @@
+use std::fmt;
+
 use crate::{error::IGCError::FRIDInitError, StrWrapper};
+use crate::manufacturer::Manufacturer;
 use crate::Result;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct FlightRecorderID {
     pub manufacturer: StrWrapper,
@@ -12,16 +17,66 @@ pub struct FlightRecorderID {
 
 impl FlightRecorderID {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(FlightRecorderIDRef::parse(line)?.to_owned())
+    }
+
+    /// The manufacturer from the registry, `None` if the code is not registered
+    pub fn manufacturer_info(&self) -> Option<&'static Manufacturer> {
+        Manufacturer::from_a_record_code(&self.manufacturer)
+    }
+
+    /// Prefix of the manufacturer specific L records to expect in this file, e.g. `LFLA` for Flarm
+    pub fn expected_comment_prefix(&self) -> Option<String> {
+        self.manufacturer_info().map(|manufacturer| manufacturer.comment_prefix())
+    }
+}
+
+impl fmt::Display for FlightRecorderID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A{}{}{}", self.manufacturer, self.id, self.extension)
+    }
+}
+
+/// Borrowed counterpart of `FlightRecorderID`
+#[derive(Debug, Clone)]
+pub struct FlightRecorderIDRef<'a> {
+    pub manufacturer: &'a str,
+    pub id: &'a str,
+    pub extension: &'a str,
+}
+
+impl<'a> FlightRecorderIDRef<'a> {
+    /// Parses both the three letter form `AMMMNNN...` and the legacy short form `AMNNN...`
+    ///
+    /// Three letter codes only consist of letters, so the short form is used when the three characters
+    /// after `A` are not all letters while the first one is a registered single character code
+    pub fn parse(line: &'a str) -> Result<Self> {
+        let is_short_form = line.get(1..4).is_some_and(|code| !code.chars().all(|c| c.is_ascii_alphabetic()))
+            && line[1..].chars().next().and_then(Manufacturer::from_short_code).is_some();
+        if is_short_form {
+            return match (line.get(1..2), line.get(2..5), line.get(5..)) {
+                (Some(manufacturer), Some(id), Some(extension)) => Ok(FlightRecorderIDRef { manufacturer, id, extension }),
+                _ => Err(FRIDInitError(format!("'{}' is too short for a short-form A record", line))),
+            };
+        }
         if line.chars().count() < 7 { return Err(FRIDInitError(format!("'{}' is too short for an A record", line)))};
-        let manufacturer = line[1..4].to_string().into();
-        let id = line[4..7].to_string().into();
-        let extension = line[7..].to_string().into();
-        Ok(FlightRecorderID {
+        let manufacturer = &line[1..4];
+        let id = &line[4..7];
+        let extension = &line[7..];
+        Ok(FlightRecorderIDRef {
             manufacturer,
             id,
             extension,
         })
     }
+
+    pub fn to_owned(&self) -> FlightRecorderID {
+        FlightRecorderID {
+            manufacturer: self.manufacturer.into(),
+            id: self.id.into(),
+            extension: self.extension.into(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -35,6 +90,27 @@ mod tests {
         assert_eq!(frid.extension, "TEXTSTRING".into());
     }
 
+    #[test]
+    fn manufacturer_is_decoded() {
+        let frid = FlightRecorderID::parse("AFLA84X").unwrap();
+        assert_eq!(frid.manufacturer_info().unwrap().name, "Flarm");
+        assert_eq!(frid.expected_comment_prefix().unwrap(), "LFLA");
+
+        let frid = FlightRecorderID::parse("AXCT1234").unwrap();
+        assert_eq!(frid.manufacturer, "XCT".into());
+        assert!(frid.manufacturer_info().is_none());
+    }
+
+    #[test]
+    fn short_form() {
+        let frid = FlightRecorderID::parse("AL6NGFLIGHT:1").unwrap();
+        assert_eq!(frid.manufacturer, "L".into());
+        assert_eq!(frid.id, "6NG".into());
+        assert_eq!(frid.extension, "FLIGHT:1".into());
+        assert_eq!(frid.manufacturer_info().unwrap().code, "LXN");
+        assert!(FlightRecorderID::parse("AL6N").is_err());
+    }
+
     #[test]
     fn fr_id_too_short() {
         assert!(FlightRecorderID::parse("AMMMNN").is_err())
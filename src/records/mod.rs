@@ -1,11 +1,16 @@
+use std::fmt;
+
 use crate::Result;
 use crate::records::diff_gps::DiffGPS;
 use crate::error::IGCError::RecordInitError;
-use crate::records::{event::Event, file_header::FileHeader, fix::Fix, flight_recorder_id::FlightRecorderID, satellite::Satellite, security::Security, task_info::TaskInfo};
-use crate::records::comment::Comment;
-use crate::records::data_fix::DataFix;
-use crate::records::extension::Extension;
+use crate::records::{event::Event, file_header::FileHeaderRecord, fix::Fix, flight_recorder_id::FlightRecorderID, satellite::Satellite, security::Security, task_info::TaskInfo};
+use crate::records::{event::EventRef, file_header::FileHeaderRecordRef, fix::FixRef, flight_recorder_id::FlightRecorderIDRef, satellite::SatelliteRef, security::SecurityRef, task_info::TaskInfoRef};
+use crate::records::comment::{Comment, CommentRef};
+use crate::records::data_fix::{DataFix, DataFixRef};
+use crate::records::extension::{Extension, ExtensionRef};
+use crate::records::util::Time;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 
 pub mod util;
@@ -23,20 +28,35 @@ pub mod data_fix;
 
 /// Record enum for getting different record types
 /// Each element contains a struct/enum that is the result of parsing a specific line
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+///
+/// Serialised as the fields of the record with a `type` field naming the kind of record, e.g. `{"type": "fix", ...}`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(tag = "type"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub enum Record {
+    #[cfg_attr(feature = "serde", serde(rename = "flight_recorder_id"))]
     A(FlightRecorderID),
+    #[cfg_attr(feature = "serde", serde(rename = "fix"))]
     B(Fix),
+    #[cfg_attr(feature = "serde", serde(rename = "task"))]
     C(TaskInfo),
+    #[cfg_attr(feature = "serde", serde(rename = "differential_gps"))]
     D(DiffGPS),
+    #[cfg_attr(feature = "serde", serde(rename = "event"))]
     E(Event),
+    #[cfg_attr(feature = "serde", serde(rename = "satellites"))]
     F(Satellite),
+    #[cfg_attr(feature = "serde", serde(rename = "security"))]
     G(Security),
-    H(FileHeader),
+    #[cfg_attr(feature = "serde", serde(rename = "header"))]
+    H(FileHeaderRecord),
+    #[cfg_attr(feature = "serde", serde(rename = "fix_extensions"))]
     I(Extension),
+    #[cfg_attr(feature = "serde", serde(rename = "data_fix_extensions"))]
     J(Extension),
+    #[cfg_attr(feature = "serde", serde(rename = "data_fix"))]
     K(DataFix),
+    #[cfg_attr(feature = "serde", serde(rename = "comment"))]
     L(Comment),
 }
 
@@ -66,7 +86,7 @@ impl Record {
                 'E' => Ok(Record::E(Event::parse(line)?)),
                 'F' => Ok(Record::F(Satellite::parse(line)?)),
                 'G' => Ok(Record::G(Security::parse(line)?)),
-                'H' => Ok(Record::H(FileHeader::parse(line)?)),
+                'H' => Ok(Record::H(FileHeaderRecord::parse(line)?)),
                 'I' => Ok(Record::I(Extension::parse(line)?)),
                 'J' => Ok(Record::J(Extension::parse(line)?)),
                 'K' => Ok(Record::K(DataFix::parse(line)?)),
@@ -75,10 +95,139 @@ impl Record {
             }
         }
     }
+
+    /// The letter the record starts with
+    pub fn letter(&self) -> char {
+        match self {
+            Record::A(_) => 'A',
+            Record::B(_) => 'B',
+            Record::C(_) => 'C',
+            Record::D(_) => 'D',
+            Record::E(_) => 'E',
+            Record::F(_) => 'F',
+            Record::G(_) => 'G',
+            Record::H(_) => 'H',
+            Record::I(_) => 'I',
+            Record::J(_) => 'J',
+            Record::K(_) => 'K',
+            Record::L(_) => 'L',
+        }
+    }
+
+    /// The time of B, E, F and K records
+    pub fn time(&self) -> Option<&Time> {
+        match self {
+            Record::B(fix) => Some(&fix.timestamp),
+            Record::E(event) => Some(&event.time),
+            Record::F(satellite) => Some(&satellite.time),
+            Record::K(data_fix) => Some(&data_fix.time),
+            _ => None,
+        }
+    }
 }
 
-#[cfg(test)]
+impl fmt::Display for Record {
+    /// The record as a line of an IGC file without the line ending
+    /// # Examples
+    /// ```
+    /// use igc_parser::records::Record;
+    /// let line = "B1602405407121N00249342WA002800042120509950";
+    /// assert_eq!(Record::parse(line).unwrap().to_string(), line);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Record::A(flight_recorder_id) => flight_recorder_id.fmt(f),
+            Record::B(fix) => fix.fmt(f),
+            Record::C(task_info) => task_info.fmt(f),
+            Record::D(diff_gps) => diff_gps.fmt(f),
+            Record::E(event) => event.fmt(f),
+            Record::F(satellite) => satellite.fmt(f),
+            Record::G(security) => security.fmt(f),
+            Record::H(header) => header.fmt(f),
+            Record::I(extension) | Record::J(extension) => extension.fmt(f),
+            Record::K(data_fix) => data_fix.fmt(f),
+            Record::L(comment) => comment.fmt(f),
+        }
+    }
+}
 
+/// Borrowed counterpart of `Record`
+/// The string contents of each record reference the parsed line, so parsing does not allocate for them.
+/// Use `to_owned` to convert into a `Record` when the record has to outlive the input
+#[derive(Debug, Clone)]
+pub enum RecordRef<'a> {
+    A(FlightRecorderIDRef<'a>),
+    B(FixRef<'a>),
+    C(TaskInfoRef<'a>),
+    D(DiffGPS),
+    E(EventRef<'a>),
+    F(SatelliteRef<'a>),
+    G(SecurityRef<'a>),
+    H(FileHeaderRecordRef<'a>),
+    I(ExtensionRef<'a>),
+    J(ExtensionRef<'a>),
+    K(DataFixRef<'a>),
+    L(CommentRef<'a>),
+}
+
+impl<'a> RecordRef<'a> {
+    /// Returns an Ok(RecordRef) borrowing from the line that was parsed, returns Err(IGCError) if something went wrong
+    /// Should never panic!
+    /// # Arguments
+    ///
+    ///  * `line` - A string slice that holds the line to be parsed
+    /// # Examples
+    /// ```
+    /// use igc_parser::records::RecordRef;
+    /// let line = "B1602405407121N00249342WA002800042120509950";
+    /// let fix = match RecordRef::parse(line) {
+    ///     Ok(RecordRef::B(fix)) => fix,
+    ///     _ => panic!("invalid valid string slice"),
+    /// };
+    /// assert_eq!(fix.extension, "20509950");
+    /// ```
+    pub fn parse(line: &'a str) -> Result<Self> {
+        match line.chars().next() {
+            None => Err(RecordInitError(format!("'{}' could not get first character", line))),
+            Some(letter) => match letter {
+                'A' => Ok(RecordRef::A(FlightRecorderIDRef::parse(line)?)),
+                'B' => Ok(RecordRef::B(FixRef::parse(line)?)),
+                'C' => Ok(RecordRef::C(TaskInfoRef::parse(line)?)),
+                'D' => Ok(RecordRef::D(DiffGPS::parse(line)?)),
+                'E' => Ok(RecordRef::E(EventRef::parse(line)?)),
+                'F' => Ok(RecordRef::F(SatelliteRef::parse(line)?)),
+                'G' => Ok(RecordRef::G(SecurityRef::parse(line)?)),
+                'H' => Ok(RecordRef::H(FileHeaderRecordRef::parse(line)?)),
+                'I' => Ok(RecordRef::I(ExtensionRef::parse(line)?)),
+                'J' => Ok(RecordRef::J(ExtensionRef::parse(line)?)),
+                'K' => Ok(RecordRef::K(DataFixRef::parse(line)?)),
+                'L' => Ok(RecordRef::L(CommentRef::parse(line)?)),
+                _ => Err(RecordInitError(format!("'{}' does not have a valid starting letter", line))),
+            }
+        }
+    }
+
+    /// Converts into the owned `Record`
+    pub fn to_owned(&self) -> Record {
+        match self {
+            RecordRef::A(frid) => Record::A(frid.to_owned()),
+            RecordRef::B(fix) => Record::B(fix.to_owned()),
+            RecordRef::C(info) => Record::C(info.to_owned()),
+            RecordRef::D(diff_gps) => Record::D(diff_gps.clone()),
+            RecordRef::E(event) => Record::E(event.to_owned()),
+            RecordRef::F(sat) => Record::F(sat.to_owned()),
+            RecordRef::G(sec) => Record::G(sec.to_owned()),
+            RecordRef::H(header) => Record::H(header.to_owned()),
+            RecordRef::I(ext) => Record::I(ext.to_owned()),
+            RecordRef::J(ext) => Record::J(ext.to_owned()),
+            RecordRef::K(data_fix) => Record::K(data_fix.to_owned()),
+            RecordRef::L(comment) => Record::L(comment.to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panicking_unwrap)]
 mod tests {
     use super::*;
 
@@ -132,4 +281,15 @@ GK2489IERGNV3089IVJE39GO398535J3894N358954983FTGY546
 G12560DJUWT28719GTAOL5628FGWNIST78154INWTOLP7815FITN";
         content.lines().for_each(|line| { let rec = Record::parse(line); if rec.is_err() {println!("{line}") ; rec.unwrap();}})
     }
+
+    #[test]
+    fn record_ref_to_owned_matches_record() {
+        let lines = ["AXXXABC FLIGHT:1", "HFGTYGLIDERTYPE: Schleicher ASH-25", "I033638FXA3940SIU4143ENL",
+            "C5110179N00102644W Lasham Start S, Start", "F160240040609123624221821", "E160305PEVCRLF", "LXXXRURITANIAN"];
+        for line in lines {
+            let owned = format!("{:?}", Record::parse(line).unwrap());
+            let borrowed = format!("{:?}", RecordRef::parse(line).unwrap().to_owned());
+            assert_eq!(owned, borrowed);
+        }
+    }
 }
\ No newline at end of file
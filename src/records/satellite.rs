@@ -1,9 +1,74 @@
+use std::fmt;
+
 use crate::{error::IGCError::SatelliteInitError, StrWrapper};
 use crate::records::util::Time;
 use crate::Result;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
+
+/// Satellite navigation system, identified by the RINEX letter in newer F records
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GNSSSystem {
+    /// `G`, also used for the plain two digit IDs of older F records
+    GPS,
+    /// `R`
+    GLONASS,
+    /// `E`
+    Galileo,
+    /// `C`
+    BeiDou,
+    /// `J`
+    QZSS,
+    /// `S`
+    SBAS,
+}
+
+impl GNSSSystem {
+    fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'G' => Some(GNSSSystem::GPS),
+            'R' => Some(GNSSSystem::GLONASS),
+            'E' => Some(GNSSSystem::Galileo),
+            'C' => Some(GNSSSystem::BeiDou),
+            'J' => Some(GNSSSystem::QZSS),
+            'S' => Some(GNSSSystem::SBAS),
+            _ => None,
+        }
+    }
+}
+
+/// A satellite in use, identified by its system and PRN number
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SatelliteId {
+    pub system: GNSSSystem,
+    pub prn: u8,
+}
+
+impl SatelliteId {
+    /// Parses either a two digit GPS PRN such as `04` or a system prefixed ID such as `R12`
+    pub fn parse(id: &str) -> Result<Self> {
+        let mut chars = id.chars();
+        let (system, prn) = match (chars.next(), id.len()) {
+            (Some(letter), 3) if letter.is_ascii_alphabetic() => match GNSSSystem::from_letter(letter) {
+                Some(system) => (system, &id[1..]),
+                None => return Err(SatelliteInitError(format!("'{id}' does not have a known satellite system letter"))),
+            },
+            (_, 2) => (GNSSSystem::GPS, id),
+            _ => return Err(SatelliteInitError(format!("'{id}' is not a valid satellite ID"))),
+        };
+        match prn.parse::<u8>() {
+            Ok(prn) => Ok(Self { system, prn }),
+            Err(_) => Err(SatelliteInitError(format!("'{id}' does not have a valid PRN number"))),
+        }
+    }
+}
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct Satellite {
     pub time: Time,
@@ -12,17 +77,61 @@ pub struct Satellite {
 
 impl Satellite {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(SatelliteRef::parse(line)?.to_owned())
+    }
+
+    /// The typed IDs of the satellites in use
+    pub fn satellites(&self) -> Vec<SatelliteId> {
+        self.satellite_ids.iter().filter_map(|id| SatelliteId::parse(id).ok()).collect()
+    }
+}
+
+impl fmt::Display for Satellite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "F{}{}", self.time, self.satellite_ids.concat())
+    }
+}
+
+/// Borrowed counterpart of `Satellite`
+#[derive(Debug, Clone)]
+pub struct SatelliteRef<'a> {
+    pub time: Time,
+    pub satellite_ids: Vec<&'a str>,
+}
+
+impl<'a> SatelliteRef<'a> {
+    /// Older records hold two digit GPS PRNs, newer records hold three character IDs with a system letter such as `G05R12E03`
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.len() < 7 { return Err(SatelliteInitError(format!("'{line}' is too short to be parsed as a satellite record"))) }
         let time = Time::parse(&line[1..7])?;
-        let satellite_ids = line[7..]
-            .chars()
-            .collect::<Vec<char>>()
-            .chunks(2)
-            .map(|c| c.iter().collect::<String>().into())
+        let ids = line[7..].trim_end();
+        if !ids.is_ascii() { return Err(SatelliteInitError(format!("'{line}' has invalid characters in the satellite IDs"))) }
+        let id_length = if ids.chars().any(|c| c.is_ascii_alphabetic()) { 3 } else { 2 };
+        if !ids.len().is_multiple_of(id_length) {
+            return Err(SatelliteInitError(format!("'{line}' does not have a whole number of {id_length} character satellite IDs")))
+        }
+        let satellite_ids = (0..ids.len())
+            .step_by(id_length)
+            .map(|i| &ids[i..i + id_length])
             .collect::<Vec<_>>();
+        for id in &satellite_ids {
+            SatelliteId::parse(id)?;
+        }
 
         Ok(Self {time, satellite_ids})
     }
+
+    /// The typed IDs of the satellites in use
+    pub fn satellites(&self) -> Vec<SatelliteId> {
+        self.satellite_ids.iter().filter_map(|id| SatelliteId::parse(id).ok()).collect()
+    }
+
+    pub fn to_owned(&self) -> Satellite {
+        Satellite {
+            time: self.time.clone(),
+            satellite_ids: self.satellite_ids.iter().map(|&id| id.into()).collect(),
+        }
+    }
 }
 
 
@@ -41,4 +150,29 @@ mod tests {
     fn too_short() {
         assert!(Satellite::parse("F160").is_err())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn odd_length_is_rejected() {
+        assert!(Satellite::parse("F16024004060912362422182").is_err());
+    }
+
+    #[test]
+    fn typed_prns() {
+        let sat_record = Satellite::parse("F160240040609").unwrap();
+        assert_eq!(sat_record.satellites(), vec![
+            SatelliteId { system: GNSSSystem::GPS, prn: 4 },
+            SatelliteId { system: GNSSSystem::GPS, prn: 6 },
+            SatelliteId { system: GNSSSystem::GPS, prn: 9 },
+        ]);
+    }
+
+    #[test]
+    fn system_prefixes() {
+        let sat_record = Satellite::parse("F160240G05R12E03").unwrap();
+        assert_eq!(sat_record.satellite_ids.len(), 3);
+        assert_eq!(sat_record.satellites()[1], SatelliteId { system: GNSSSystem::GLONASS, prn: 12 });
+        assert_eq!(sat_record.satellites()[2].system, GNSSSystem::Galileo);
+        assert!(Satellite::parse("F160240G05X12").is_err());
+        assert!(Satellite::parse("F160240G05R1").is_err());
+    }
+}
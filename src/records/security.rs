@@ -1,8 +1,12 @@
+use std::fmt;
+
 use crate::{error::IGCError::SecurityInitError, StrWrapper};
 use crate::Result;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct Security {
     pub security_code: StrWrapper,
@@ -10,14 +14,35 @@ pub struct Security {
 
 impl Security {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(SecurityRef::parse(line)?.to_owned())
+    }
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "G{}", self.security_code)
+    }
+}
+
+/// Borrowed counterpart of `Security`
+#[derive(Debug, Clone)]
+pub struct SecurityRef<'a> {
+    pub security_code: &'a str,
+}
+
+impl<'a> SecurityRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.len() < 2 { return Err(SecurityInitError(format!("'{line}' is too short to be a security tag")))};
-        let security_code = line[1..].to_string().into();
+        let security_code = &line[1..];
         Ok(Self {security_code})
     }
+
+    pub fn to_owned(&self) -> Security {
+        Security { security_code: self.security_code.into() }
+    }
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
 
@@ -1,9 +1,14 @@
+use std::fmt;
+
 use crate::error::IGCError::TaskInfoInitError;
 use crate::records::util::{Coordinate, Date, Time};
 use crate::{Result, StrWrapper};
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+/// Serialised with a `kind` field, `task_point` or `declaration_time`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(tag = "kind", rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub enum TaskInfo {
     TaskPoint(TaskPoint),
@@ -12,16 +17,46 @@ pub enum TaskInfo {
 
 impl TaskInfo {
     pub(crate) fn parse(line: &str) -> Result<Self> {
+        Ok(TaskInfoRef::parse(line)?.to_owned())
+    }
+}
+
+impl fmt::Display for TaskInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TaskInfo::TaskPoint(task_point) => task_point.fmt(f),
+            TaskInfo::DeclarationTime(declaration_time) => declaration_time.fmt(f),
+        }
+    }
+}
+
+/// Borrowed counterpart of `TaskInfo`
+#[derive(Debug, Clone)]
+pub enum TaskInfoRef<'a> {
+    TaskPoint(TaskPointRef<'a>),
+    DeclarationTime(DeclarationTimeRef<'a>),
+}
+
+impl<'a> TaskInfoRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
         if line.len() < 18 { return Err(TaskInfoInitError(format!("'{}' is too short to be parsed as kind of task info record", line))) }
         if line[1..17].chars().all(|c| c.is_numeric()) {
-            Ok(TaskInfo::DeclarationTime(DeclarationTime::parse(line)?))
+            Ok(TaskInfoRef::DeclarationTime(DeclarationTimeRef::parse(line)?))
         } else {
-            Ok(TaskInfo::TaskPoint(TaskPoint::parse(line)?))
+            Ok(TaskInfoRef::TaskPoint(TaskPointRef::parse(line)?))
+        }
+    }
+
+    pub fn to_owned(&self) -> TaskInfo {
+        match self {
+            TaskInfoRef::TaskPoint(task_point) => TaskInfo::TaskPoint(task_point.to_owned()),
+            TaskInfoRef::DeclarationTime(declaration_time) => TaskInfo::DeclarationTime(declaration_time.to_owned()),
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct TaskPoint {
     pub coordinate: Coordinate,
@@ -30,13 +65,44 @@ pub struct TaskPoint {
 
 impl TaskPoint {
     pub fn parse(line: &str) -> Result<Self> {
+        Ok(TaskPointRef::parse(line)?.to_owned())
+    }
+}
+
+impl fmt::Display for TaskPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "C{}{}", self.coordinate, self.name.as_deref().unwrap_or(""))
+    }
+}
+
+/// Borrowed counterpart of `TaskPoint`
+#[derive(Debug, Clone)]
+pub struct TaskPointRef<'a> {
+    pub coordinate: Coordinate,
+    pub name: Option<&'a str>,
+}
+
+impl<'a> TaskPointRef<'a> {
+    pub fn parse(line: &'a str) -> Result<Self> {
+        if line.len() < 18 { return Err(TaskInfoInitError(format!("'{}' is too short to be a task point record", line))) }
         let coordinate = Coordinate::parse(&line[1..18])?;
-        let name = (line.len() != 18).then(|| line[18..].to_string().into());
+        let name = (line.len() != 18).then(|| &line[18..]);
         Ok(Self { coordinate, name })
     }
+
+    pub fn to_owned(&self) -> TaskPoint {
+        TaskPoint {
+            coordinate: self.coordinate.clone(),
+            name: self.name.map(|name| name.into()),
+        }
+    }
 }
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+/// Serialised with the extra data split into `flight_date`, `task_number`, `turn_points` and `description`
+///
+/// Extra data that does not follow the standard layout is all in `description` with the other three `null`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "DeclarationTimeFields", from = "DeclarationTimeFields"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(Debug, Clone)]
 pub struct DeclarationTime {
     pub date: Date,
@@ -45,22 +111,133 @@ pub struct DeclarationTime {
 }
 
 impl DeclarationTime {
-    fn parse(line: &str) -> Result<Self> {
+    pub fn get_extra(&self) -> StrWrapper {
+        self.extra.clone()
+    }
+
+    /// The intended date of the flight at the start of the extra data, `None` if it is missing or `000000`
+    pub fn flight_date(&self) -> Option<Date> {
+        Date::parse(self.extra.get(0..6)?).ok()
+    }
+
+    /// The four digit task number after the flight date, `None` if the extra data does not follow the standard layout
+    pub fn task_number(&self) -> Option<u16> {
+        self.numbers().map(|(task_number, _)| task_number)
+    }
+
+    /// Number of turn points declared, not counting the takeoff, start, finish and landing
+    pub fn turn_points(&self) -> Option<u8> {
+        self.numbers().map(|(_, turn_points)| turn_points)
+    }
+
+    /// The free text after the number of turn points, or all of the extra data if it does not follow the standard layout
+    pub fn description(&self) -> &str {
+        match self.numbers() {
+            Some(_) => &self.extra[12..],
+            None => &self.extra,
+        }
+    }
+
+    fn numbers(&self) -> Option<(u16, u8)> {
+        let (task_number, turn_points) = (self.extra.get(6..10)?, self.extra.get(10..12)?);
+        if !(task_number.bytes().all(|byte| byte.is_ascii_digit()) && turn_points.bytes().all(|byte| byte.is_ascii_digit())) { return None }
+        Some((task_number.parse().ok()?, turn_points.parse().ok()?))
+    }
+
+    /// Replaces the intended date of the flight, does nothing if the record has none
+    pub(crate) fn set_flight_date(&mut self, date: &Date) {
+        if self.flight_date().is_some() {
+            self.extra = format!("{date}{}", &self.extra[6..]).into();
+        }
+    }
+}
+
+/// The serialised form of `DeclarationTime`
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+struct DeclarationTimeFields {
+    date: Date,
+    time: Time,
+    /// The intended date of the flight, `null` when not given
+    flight_date: Option<Date>,
+    task_number: Option<u16>,
+    turn_points: Option<u8>,
+    description: String,
+}
+
+#[cfg(feature = "serde")]
+impl From<DeclarationTime> for DeclarationTimeFields {
+    fn from(declaration_time: DeclarationTime) -> Self {
+        // a flight date that is neither a date nor `000000` could not be written back
+        let flight_date = declaration_time.flight_date();
+        let standard = declaration_time.numbers().filter(|_| flight_date.is_some() || declaration_time.extra.starts_with("000000"));
+        Self {
+            flight_date: standard.and(flight_date),
+            task_number: standard.map(|(task_number, _)| task_number),
+            turn_points: standard.map(|(_, turn_points)| turn_points),
+            description: if standard.is_some() { declaration_time.description() } else { &declaration_time.extra }.to_string(),
+            date: declaration_time.date,
+            time: declaration_time.time,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<DeclarationTimeFields> for DeclarationTime {
+    fn from(fields: DeclarationTimeFields) -> Self {
+        let extra = match (&fields.flight_date, fields.task_number, fields.turn_points) {
+            (None, None, None) => fields.description,
+            (flight_date, task_number, turn_points) => format!(
+                "{}{:04}{:02}{}",
+                flight_date.as_ref().map_or("000000".to_string(), Date::to_string),
+                task_number.unwrap_or(0),
+                turn_points.unwrap_or(0),
+                fields.description,
+            ),
+        };
+        Self { date: fields.date, time: fields.time, extra: extra.into() }
+    }
+}
+
+impl fmt::Display for DeclarationTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "C{}{}{}", self.date, self.time, self.extra)
+    }
+}
+
+/// Borrowed counterpart of `DeclarationTime`
+#[derive(Debug, Clone)]
+pub struct DeclarationTimeRef<'a> {
+    pub date: Date,
+    pub time: Time,
+    extra: &'a str,
+}
+
+impl<'a> DeclarationTimeRef<'a> {
+    fn parse(line: &'a str) -> Result<Self> {
         if line.len() < 23 { return Err(TaskInfoInitError(format!("'{}' is too short to be a declaration time record", line))) }
         let date = Date::parse(&line[1..7])?;
         let time = Time::parse(&line[7..13])?;
-        let extra = line[13..].to_string().into();
+        let extra = &line[13..];
         Ok(Self { date, time, extra })
     }
-}
 
-impl DeclarationTime {
-    pub fn get_extra(&self) -> StrWrapper {
-        self.extra.clone().into()
+    pub fn get_extra(&self) -> &'a str {
+        self.extra
+    }
+
+    pub fn to_owned(&self) -> DeclarationTime {
+        DeclarationTime {
+            date: self.date.clone(),
+            time: self.time.clone(),
+            extra: self.extra.into(),
+        }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use crate::records::util::{Latitude, Longitude};
     use super::*;
@@ -101,4 +278,37 @@ mod tests {
             assert!(false)
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn declaration_time_serialised_with_fields() {
+        let Ok(declaration) = TaskInfo::parse("C0703232133390803230042031st task") else { panic!("expected a task info record") };
+        let serialised = serde_json::to_value(&declaration).unwrap();
+        assert_eq!(serialised, serde_json::json!({
+            "kind": "declaration_time",
+            "date": "2023-03-07",
+            "time": "21:33:39Z",
+            "flight_date": "2023-03-08",
+            "task_number": 42,
+            "turn_points": 3,
+            "description": "1st task",
+        }));
+        let TaskInfo::DeclarationTime(parsed) = serde_json::from_value(serialised).unwrap() else { panic!("expected a declaration") };
+        assert_eq!(parsed.get_extra().as_ref(), "0803230042031st task");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn declaration_time_without_standard_layout_round_trips() {
+        for line in ["C0703232133390803230042", "C070323213339999999004203", "C070323213339000000004203"] {
+            let Ok(TaskInfo::DeclarationTime(declaration)) = TaskInfo::parse(line) else { panic!("expected a declaration") };
+            let serialised = serde_json::to_value(&declaration).unwrap();
+            let parsed: DeclarationTime = serde_json::from_value(serialised.clone()).unwrap();
+            assert_eq!(parsed.to_string(), line, "{serialised}");
+        }
+        let Ok(TaskInfo::DeclarationTime(declaration)) = TaskInfo::parse("C0703232133390803230042") else { panic!("expected a declaration") };
+        let serialised = serde_json::to_value(&declaration).unwrap();
+        assert_eq!((&serialised["flight_date"], &serialised["task_number"], &serialised["turn_points"]), (&serde_json::Value::Null, &serde_json::Value::Null, &serde_json::Value::Null));
+        assert_eq!(serialised["description"], "0803230042");
+    }
 }
\ No newline at end of file
@@ -1,10 +1,15 @@
+use std::fmt;
+
 use crate::Result;
 use crate::error::IGCError::*;
 #[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")] use schemars::JsonSchema;
 
 type Seconds = u32;
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+/// UTC time of day, serialised as ISO 8601 `HH:MM:SSZ`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "String", try_from = "String"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(extend("format" = "time")))]
 #[derive(PartialEq, Clone, Debug)]
 pub struct Time {
     pub h: u8,
@@ -46,9 +51,54 @@ impl Time {
         self.h += h;
         self.h %= 24;
     }
+
+    /// Adds seconds, wrapping around midnight
+    pub fn add_seconds(&mut self, s: u32) {
+        let s = (self.seconds_since_midnight() + s % 86400) % 86400;
+        (self.h, self.m, self.s) = ((s / 3600) as u8, ((s % 3600) / 60) as u8, (s % 60) as u8);
+    }
+
+    /// `HH:MM:SSZ`
+    pub fn to_iso8601(&self) -> String {
+        format!("{:02}:{:02}:{:02}Z", self.h, self.m, self.s)
+    }
+
+    /// Reads `HH:MM:SS` with or without the `Z`
+    pub fn parse_iso8601(text: &str) -> Result<Self> {
+        let text = text.strip_suffix('Z').unwrap_or(text);
+        match text.split(':').map(|part| part.parse::<u8>().ok().filter(|_| part.len() == 2)).collect::<Vec<_>>()[..] {
+            [Some(h), Some(m), Some(s)] => Time::from_hms(h, m, s),
+            _ => Err(TimeInitError(format!("'{text}' is not an ISO 8601 time"))),
+        }
+    }
 }
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg(feature = "serde")]
+impl From<Time> for String {
+    fn from(time: Time) -> Self {
+        time.to_iso8601()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for Time {
+    type Error = crate::error::IGCError;
+
+    fn try_from(text: String) -> Result<Self> {
+        Time::parse_iso8601(&text)
+    }
+}
+
+impl fmt::Display for Time {
+    /// `HHMMSS` as written in the records
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}{:02}{:02}", self.h, self.m, self.s)
+    }
+}
+
+/// Date with a two digit year, serialised as ISO 8601 `YYYY-MM-DD`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "String", try_from = "String"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(extend("format" = "date")))]
 #[derive(PartialEq, Clone, Debug)]
 pub struct Date {
     pub d: u8,
@@ -70,9 +120,71 @@ impl Date {
             _ => Err(DateInitError(format!("'{}' can not be parsed as a number", line)))
         }
     }
+
+    /// Days since 1970-01-01, two digit years from 80 are taken as 1980 to 1999 and the rest as 2000 to 2079
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = self.full_year() as i64 - (self.m <= 2) as i64;
+        let day_of_year = (153 * ((self.m as i64 + 9) % 12) + 2) / 5 + self.d as i64 - 1;
+        let day_of_era = year % 400 * 365 + year % 400 / 4 - year % 400 / 100 + day_of_year;
+        year / 400 * 146097 + day_of_era - 719468
+    }
+
+    /// The date `days` after 1970-01-01, the year is kept as its last two digits
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let days = days + 719468;
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let (d, m) = (day_of_year - (153 * month + 2) / 5 + 1, if month < 10 { month + 3 } else { month - 9 });
+        let year = days.div_euclid(146097) * 400 + year_of_era + (m <= 2) as i64;
+        Self { d: d as u8, m: m as u8, y: year.rem_euclid(100) as u8 }
+    }
+
+    /// The year with the century, 1980 to 2079
+    pub fn full_year(&self) -> u16 {
+        self.y as u16 + if self.y >= 80 { 1900 } else { 2000 }
+    }
+
+    /// `YYYY-MM-DD`
+    pub fn to_iso8601(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.full_year(), self.m, self.d)
+    }
+
+    /// Reads `YYYY-MM-DD`, keeping the last two digits of the year
+    pub fn parse_iso8601(text: &str) -> Result<Self> {
+        match text.split('-').map(|part| part.parse::<u16>().ok()).collect::<Vec<_>>()[..] {
+            [Some(y), Some(m), Some(d)] if text.len() == 10 => Date::parse(&format!("{:02}{:02}{:02}", d, m, y % 100)),
+            _ => Err(DateInitError(format!("'{text}' is not an ISO 8601 date"))),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_iso8601()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for Date {
+    type Error = crate::error::IGCError;
+
+    fn try_from(text: String) -> Result<Self> {
+        Date::parse_iso8601(&text)
+    }
+}
+
+impl fmt::Display for Date {
+    /// `DDMMYY` as written in the records
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}{:02}{:02}", self.d, self.m, self.y)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[derive(PartialEq, Clone, Debug)]
 pub struct Coordinate {
     pub latitude: Latitude,
@@ -88,9 +200,68 @@ impl Coordinate {
         let longitude = Longitude::parse(&line[8..17])?;
         Ok(Coordinate { latitude, longitude })
     }
+
+    /// Builds a coordinate from signed decimal degrees, positive being north and east
+    pub fn from_decimal_degrees(latitude: f64, longitude: f64) -> Self {
+        Coordinate { latitude: Latitude::from_decimal_degrees(latitude), longitude: Longitude::from_decimal_degrees(longitude) }
+    }
+
+    /// Signed decimal degrees as `(latitude, longitude)`, positive being north and east
+    pub fn to_decimal_degrees(&self) -> (f64, f64) {
+        (self.latitude.to_decimal_degrees(), self.longitude.to_decimal_degrees())
+    }
+
+    /// Great circle distance in meters using the haversine formula
+    /// # examples
+    /// ```rust
+    /// use igc_parser::records::util::Coordinate;
+    /// let a = Coordinate::parse("5107126N00149300W").unwrap();
+    /// let b = Coordinate::parse("5207126N00149300W").unwrap();
+    /// assert!((a.distance_to(&b) - 111_195.).abs() < 1.);
+    /// ```
+    pub fn distance_to(&self, other: &Coordinate) -> f64 {
+        let ((lat1, lon1), (lat2, lon2)) = (self.to_radians(), other.to_radians());
+        let a = ((lat2 - lat1) / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().asin()
+    }
+
+    /// Initial great circle bearing to `other` in degrees clockwise from true north, 0 to 360
+    pub fn bearing_to(&self, other: &Coordinate) -> f64 {
+        let ((lat1, lon1), (lat2, lon2)) = (self.to_radians(), other.to_radians());
+        let y = (lon2 - lon1).sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * (lon2 - lon1).cos();
+        y.atan2(x).to_degrees().rem_euclid(360.)
+    }
+
+    /// The coordinate `distance` meters away along the great circle starting at `bearing` degrees
+    pub fn destination(&self, bearing: f64, distance: f64) -> Coordinate {
+        let (latitude, longitude) = self.to_radians();
+        let (bearing, angle) = (bearing.to_radians(), distance / EARTH_RADIUS);
+        let destination_latitude = (latitude.sin() * angle.cos() + latitude.cos() * angle.sin() * bearing.cos()).asin();
+        let destination_longitude = longitude + (bearing.sin() * angle.sin() * latitude.cos()).atan2(angle.cos() - latitude.sin() * destination_latitude.sin());
+        let destination_longitude = (destination_longitude.to_degrees() + 540.).rem_euclid(360.) - 180.;
+        Coordinate::from_decimal_degrees(destination_latitude.to_degrees(), destination_longitude)
+    }
+
+    fn to_radians(&self) -> (f64, f64) {
+        let (latitude, longitude) = self.to_decimal_degrees();
+        (latitude.to_radians(), longitude.to_radians())
+    }
 }
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+impl fmt::Display for Coordinate {
+    /// `DDMMmmmNDDDMMmmmE` as written in the records
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.latitude, self.longitude)
+    }
+}
+
+/// Mean earth radius in meters as used by the FAI for distance calculations
+pub const EARTH_RADIUS: f64 = 6_371_000.;
+
+/// Serialised as signed decimal degrees, negative in the southern hemisphere
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "f64", try_from = "f64"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(extend("minimum" = -90, "maximum" = 90)))]
 #[derive(PartialEq, Clone, Debug)]
 pub struct Latitude {
     pub degrees: u8,
@@ -116,9 +287,28 @@ impl Latitude {
             is_north,
         })
     }
+
+    pub fn from_decimal_degrees(latitude: f64) -> Self {
+        let (degrees, minutes) = split_decimal_degrees(latitude);
+        Latitude { degrees, minutes, is_north: latitude >= 0. }
+    }
+
+    /// Signed decimal degrees, negative in the southern hemisphere
+    pub fn to_decimal_degrees(&self) -> f64 {
+        let degrees = self.degrees as f64 + self.minutes as f64 / 60.;
+        if self.is_north { degrees } else { -degrees }
+    }
 }
 
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+impl fmt::Display for Latitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}{:05}{}", self.degrees, (self.minutes * 1000.).round() as u32, if self.is_north { 'N' } else { 'S' })
+    }
+}
+
+/// Serialised as signed decimal degrees, negative in the western hemisphere
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(into = "f64", try_from = "f64"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(extend("minimum" = -180, "maximum" = 180)))]
 #[derive(PartialEq, Clone, Debug)]
 pub struct Longitude {
     pub degrees: u8,
@@ -145,6 +335,69 @@ impl Longitude {
             is_east,
         })
     }
+
+    pub fn from_decimal_degrees(longitude: f64) -> Self {
+        let (degrees, minutes) = split_decimal_degrees(longitude);
+        Longitude { degrees, minutes, is_east: longitude >= 0. }
+    }
+
+    /// Signed decimal degrees, negative in the western hemisphere
+    pub fn to_decimal_degrees(&self) -> f64 {
+        let degrees = self.degrees as f64 + self.minutes as f64 / 60.;
+        if self.is_east { degrees } else { -degrees }
+    }
+}
+
+impl fmt::Display for Longitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:03}{:05}{}", self.degrees, (self.minutes * 1000.).round() as u32, if self.is_east { 'E' } else { 'W' })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Latitude> for f64 {
+    fn from(latitude: Latitude) -> Self {
+        round_decimal_degrees(latitude.to_decimal_degrees())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<f64> for Latitude {
+    type Error = crate::error::IGCError;
+
+    fn try_from(latitude: f64) -> Result<Self> {
+        if !(-90. ..=90.).contains(&latitude) { return Err(CoordinateInitError(format!("{latitude} is not a latitude in degrees"))) }
+        Ok(Latitude::from_decimal_degrees(latitude))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Longitude> for f64 {
+    fn from(longitude: Longitude) -> Self {
+        round_decimal_degrees(longitude.to_decimal_degrees())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<f64> for Longitude {
+    type Error = crate::error::IGCError;
+
+    fn try_from(longitude: f64) -> Result<Self> {
+        if !(-180. ..=180.).contains(&longitude) { return Err(CoordinateInitError(format!("{longitude} is not a longitude in degrees"))) }
+        Ok(Longitude::from_decimal_degrees(longitude))
+    }
+}
+
+/// Seven decimals are enough to get back the thousandths of a minute of the record
+#[cfg(feature = "serde")]
+fn round_decimal_degrees(decimal_degrees: f64) -> f64 {
+    (decimal_degrees * 1e7).round() / 1e7
+}
+
+/// Whole degrees and minutes rounded to the thousandths an IGC file can hold
+fn split_decimal_degrees(decimal_degrees: f64) -> (u8, f32) {
+    let thousandths = (decimal_degrees.abs() * 60_000.).round() as u32;
+    ((thousandths / 60_000) as u8, (thousandths % 60_000) as f32 / 1000.)
 }
 
 #[cfg(test)]
@@ -228,10 +481,28 @@ mod tests {
         assert_eq!(time, Time { h: 23, m: 59, s: 59 });
     }
 
+    #[test]
+    fn add_seconds() {
+        let mut time = Time { h: 23, m: 59, s: 30 };
+        time.add_seconds(29);
+        assert_eq!(time, Time { h: 23, m: 59, s: 59 });
+        time.add_seconds(3601);
+        assert_eq!(time, Time { h: 1, m: 0, s: 0 });
+        time.add_seconds(86400 * 3);
+        assert_eq!(time, Time { h: 1, m: 0, s: 0 });
+    }
+
     #[test]
     fn date() {
         let date = Date::parse("010203").unwrap();
         assert_eq!(date, Date { d: 1, m: 2, y: 3 });
+        assert_eq!(Date { d: 1, m: 1, y: 0 }.days_since_epoch(), 10957);
+        assert_eq!(Date { d: 1, m: 3, y: 24 }.days_since_epoch(), 19783);
+        assert_eq!(Date { d: 31, m: 12, y: 99 }.days_since_epoch(), 10956);
+        assert_eq!(Date::from_days_since_epoch(19783), Date { d: 1, m: 3, y: 24 });
+        assert_eq!(Date::from_days_since_epoch(19782), Date { d: 29, m: 2, y: 24 });
+        assert_eq!(Date::from_days_since_epoch(10956), Date { d: 31, m: 12, y: 99 });
+        assert_eq!(date.to_string(), "010203");
 
         let date = Date::parse("311212").unwrap();
         assert_eq!(date, Date { d: 31, m: 12, y: 12 });
@@ -242,4 +513,49 @@ mod tests {
         assert!(Date::parse("003200").is_err());
         assert!(Date::parse("0102A3").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decimal_degrees() {
+        let coordinate = Coordinate::parse("5107126N00149300W").unwrap();
+        let (latitude, longitude) = coordinate.to_decimal_degrees();
+        assert!((latitude - 51.118766).abs() < 1e-5);
+        assert!((longitude + 1.821666).abs() < 1e-5);
+        assert_eq!(Coordinate::from_decimal_degrees(latitude, longitude), coordinate);
+
+        let coordinate = Coordinate::from_decimal_degrees(-33.5, 151.25);
+        assert_eq!(coordinate.latitude, Latitude { degrees: 33, minutes: 30., is_north: false });
+        assert_eq!(coordinate.longitude, Longitude { degrees: 151, minutes: 15., is_east: true });
+        assert_eq!(coordinate.to_string(), "3330000S15115000E");
+        assert_eq!(Coordinate::parse("5107126N00149300W").unwrap().to_string(), "5107126N00149300W");
+    }
+
+    #[test]
+    fn distance_and_bearing() {
+        let a = Coordinate::from_decimal_degrees(0., 0.);
+        assert!((a.distance_to(&Coordinate::from_decimal_degrees(0., 1.)) - 111_195.).abs() < 1.);
+        assert!((a.bearing_to(&Coordinate::from_decimal_degrees(0., 1.)) - 90.).abs() < 1e-9);
+        assert!((a.bearing_to(&Coordinate::from_decimal_degrees(-1., 0.)) - 180.).abs() < 1e-9);
+        assert!((a.bearing_to(&Coordinate::from_decimal_degrees(0., -1.)) - 270.).abs() < 1e-9);
+        assert_eq!(a.distance_to(&a), 0.);
+
+        let destination = a.destination(45., 10_000.);
+        assert!((a.distance_to(&destination) - 10_000.).abs() < 5.);
+        assert!((a.bearing_to(&destination) - 45.).abs() < 0.05);
+    }
+
+    #[test]
+    fn iso8601() {
+        let time = Time { h: 9, m: 5, s: 7 };
+        assert_eq!(time.to_iso8601(), "09:05:07Z");
+        assert_eq!(Time::parse_iso8601("09:05:07Z").unwrap(), time);
+        assert_eq!(Time::parse_iso8601("09:05:07").unwrap(), time);
+        assert!(Time::parse_iso8601("9:05:07").is_err());
+        assert!(Time::parse_iso8601("24:00:00").is_err());
+
+        let date = Date { d: 6, m: 8, y: 20 };
+        assert_eq!(date.to_iso8601(), "2020-08-06");
+        assert_eq!(Date::parse_iso8601("2020-08-06").unwrap(), date);
+        assert_eq!(Date { d: 31, m: 12, y: 99 }.to_iso8601(), "1999-12-31");
+        assert!(Date::parse_iso8601("2020-8-6").is_err());
+    }
+}
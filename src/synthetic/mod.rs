@@ -0,0 +1,596 @@
+use crate::document::IGCDocument;
+use crate::records::event::Event;
+use crate::records::extension::{Extension, ExtensionType};
+use crate::records::file_header::FileHeaderRecord;
+use crate::records::fix::{Fix, EXTENSION_START};
+use crate::records::flight_recorder_id::FlightRecorderID;
+use crate::records::task_info::{TaskInfo, TaskPoint};
+use crate::records::util::{Coordinate, Date, Time};
+use crate::records::Record;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Airspeeds in km/h while launching, circling, under engine and on the approach
+const LAUNCH_SPEED: f64 = 110.;
+const CIRCLING_SPEED: f64 = 90.;
+const ENGINE_SPEED: f64 = 100.;
+const APPROACH_SPEED: f64 = 90.;
+/// Climb rates in m/s on aerotow and under engine
+const LAUNCH_CLIMB: f64 = 3.;
+const ENGINE_CLIMB: f64 = 2.;
+/// Radius in meters of the circles flown in thermals
+const CIRCLING_RADIUS: f64 = 150.;
+
+/// B record extension the generator can fill in
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExtensionChannel {
+    /// `FXA`, fix accuracy in meters from the horizontal noise
+    FXA,
+    /// `SIU`, satellites in use, 8 to 11
+    SIU,
+    /// `ENL`, environmental noise level, high while the engine runs
+    ENL,
+    /// `GSP`, ground speed in km/h
+    GSP,
+    /// `TRT`, true track in degrees
+    TRT,
+    /// `TAS`, true airspeed in km/h
+    TAS,
+}
+
+impl ExtensionChannel {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExtensionChannel::FXA => "FXA",
+            ExtensionChannel::SIU => "SIU",
+            ExtensionChannel::ENL => "ENL",
+            ExtensionChannel::GSP => "GSP",
+            ExtensionChannel::TRT => "TRT",
+            ExtensionChannel::TAS => "TAS",
+        }
+    }
+
+    /// Number of characters in the B record
+    pub fn width(&self) -> usize {
+        match self {
+            ExtensionChannel::SIU => 2,
+            _ => 3,
+        }
+    }
+}
+
+/// One step of a scripted flight
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Standing still on the field for the number of seconds
+    Ground(u32),
+    /// Aerotow into the wind up to the altitude in meters
+    Launch(f64),
+    /// Circling in a thermal and drifting with the wind up to `altitude`, climbing at `rate` m/s
+    Climb { altitude: f64, rate: f64 },
+    /// Straight glide to the waypoint at the speed and sink rate of the polar, correcting for the wind
+    Glide(Coordinate),
+    /// Straight ahead under engine power up to the altitude in meters
+    Engine(f64),
+    /// Approach to the waypoint descending steadily to the field elevation
+    Land(Coordinate),
+    /// An E record with the three letter code at the current time
+    Event(String),
+}
+
+/// What the glider was doing
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhaseKind {
+    Ground,
+    Launch,
+    Climb,
+    Glide,
+    Engine,
+    Landing,
+}
+
+/// A step of the script as it was flown
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phase {
+    pub kind: PhaseKind,
+    pub start: Time,
+    pub end: Time,
+}
+
+/// A generated flight with the ground truth it was made from
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone)]
+pub struct SyntheticFlight {
+    /// The IGC file, with noise added to its fixes
+    pub document: IGCDocument,
+    /// The fixes without noise, one for every B record of the document
+    pub truth: Vec<Fix>,
+    pub phases: Vec<Phase>,
+}
+
+/// Builder for IGC files of scripted flights with known ground truth, for tests and demos
+///
+/// The world is flat at the field elevation, the glider never goes below it, and the same settings and seed always give the same file.
+/// # examples
+/// ```rust
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::records::util::Coordinate;
+/// use igc_parser::synthetic::{ExtensionChannel, FlightGenerator};
+/// let home = Coordinate::from_decimal_degrees(51., -1.);
+/// let turn = home.destination(45., 20_000.);
+/// let flight = FlightGenerator::new(home.clone(), 150.)
+///     .with_wind(270., 15.)
+///     .with_noise(3., 2.)
+///     .with_extension(ExtensionChannel::ENL)
+///     .ground(300)
+///     .launch(600.)
+///     .climb(1500., 2.)
+///     .glide_to(turn)
+///     .climb(1500., 1.5)
+///     .land_at(home)
+///     .ground(120)
+///     .generate();
+/// let igc_file = IGCFile::parse(&flight.document.to_string()).unwrap();
+/// assert_eq!(igc_file.get_fixes().len(), flight.truth.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlightGenerator {
+    start: Coordinate,
+    elevation: f64,
+    date: Date,
+    start_time: Time,
+    interval: u32,
+    /// Direction the wind comes from in degrees and its speed in km/h
+    wind: (f64, f64),
+    /// Airspeed in km/h and sink rate in m/s when gliding
+    polar: (f64, f64),
+    /// Standard deviation of the horizontal and vertical noise in meters
+    noise: (f64, f64),
+    seed: u64,
+    extensions: Vec<ExtensionChannel>,
+    pilot: String,
+    glider: (String, String),
+    task: Vec<(Coordinate, String)>,
+    steps: Vec<Step>,
+}
+
+impl FlightGenerator {
+    /// A glider standing at `start` on a field `elevation` meters high, on 1 July 2024 at 10:00 UTC, logging every second
+    pub fn new(start: Coordinate, elevation: f64) -> Self {
+        Self {
+            start,
+            elevation,
+            date: Date { d: 1, m: 7, y: 24 },
+            start_time: Time { h: 10, m: 0, s: 0 },
+            interval: 1,
+            wind: (0., 0.),
+            polar: (100., 1.),
+            noise: (0., 0.),
+            seed: 0,
+            extensions: Vec::new(),
+            pilot: "Synthetic Pilot".into(),
+            glider: ("Synthetic".into(), "D-0000".into()),
+            task: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn with_date(mut self, date: Date) -> Self {
+        self.date = date;
+        self
+    }
+
+    pub fn with_start_time(mut self, time: Time) -> Self {
+        self.start_time = time;
+        self
+    }
+
+    /// Seconds between fixes, at least one
+    pub fn with_interval(mut self, interval: u32) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    /// Wind from `direction` degrees at `speed` km/h
+    pub fn with_wind(mut self, direction: f64, speed: f64) -> Self {
+        self.wind = (direction, speed);
+        self
+    }
+
+    /// Airspeed in km/h and sink rate in m/s when gliding
+    pub fn with_polar(mut self, airspeed: f64, sink_rate: f64) -> Self {
+        self.polar = (airspeed, sink_rate);
+        self
+    }
+
+    /// Standard deviation in meters of the noise added to positions and altitudes
+    pub fn with_noise(mut self, horizontal: f64, vertical: f64) -> Self {
+        self.noise = (horizontal, vertical);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Adds the extension to the I record and to every fix
+    pub fn with_extension(mut self, channel: ExtensionChannel) -> Self {
+        if !self.extensions.contains(&channel) { self.extensions.push(channel) }
+        self
+    }
+
+    pub fn with_pilot(mut self, pilot: &str) -> Self {
+        self.pilot = pilot.into();
+        self
+    }
+
+    pub fn with_glider(mut self, glider_type: &str, glider_id: &str) -> Self {
+        self.glider = (glider_type.into(), glider_id.into());
+        self
+    }
+
+    /// Declares a task from the start over the turn points to the finish, written with the takeoff and landing as C records
+    pub fn with_task(mut self, points: &[(Coordinate, &str)]) -> Self {
+        self.task = points.iter().map(|(coordinate, name)| (coordinate.clone(), name.to_string())).collect();
+        self
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn ground(self, seconds: u32) -> Self {
+        self.step(Step::Ground(seconds))
+    }
+
+    pub fn launch(self, altitude: f64) -> Self {
+        self.step(Step::Launch(altitude))
+    }
+
+    pub fn climb(self, altitude: f64, rate: f64) -> Self {
+        self.step(Step::Climb { altitude, rate })
+    }
+
+    pub fn glide_to(self, waypoint: Coordinate) -> Self {
+        self.step(Step::Glide(waypoint))
+    }
+
+    pub fn engine(self, altitude: f64) -> Self {
+        self.step(Step::Engine(altitude))
+    }
+
+    pub fn land_at(self, waypoint: Coordinate) -> Self {
+        self.step(Step::Land(waypoint))
+    }
+
+    pub fn event(self, code: &str) -> Self {
+        self.step(Step::Event(code.into()))
+    }
+
+    /// Flies the script and writes the IGC file
+    pub fn generate(&self) -> SyntheticFlight {
+        let mut simulation = Simulation::new(self);
+        let mut phases = Vec::new();
+        let mut events = Vec::new();
+        for step in &self.steps {
+            let start = simulation.second();
+            let kind = match step {
+                Step::Ground(seconds) => {
+                    for _ in 0..*seconds { simulation.stand() }
+                    PhaseKind::Ground
+                }
+                Step::Launch(altitude) => {
+                    let heading = if self.wind.1 > 0. { self.wind.0 } else { simulation.heading };
+                    simulation.climb_to(*altitude, LAUNCH_CLIMB, |_| heading, LAUNCH_SPEED, false);
+                    PhaseKind::Launch
+                }
+                Step::Climb { altitude, rate } => {
+                    let turn_rate = (CIRCLING_SPEED / 3.6 / CIRCLING_RADIUS).to_degrees();
+                    simulation.climb_to(*altitude, *rate, |heading| heading + turn_rate, CIRCLING_SPEED, false);
+                    PhaseKind::Climb
+                }
+                Step::Glide(waypoint) => {
+                    simulation.fly_to(waypoint, self.polar.0, Some(self.polar.1));
+                    PhaseKind::Glide
+                }
+                Step::Engine(altitude) => {
+                    let heading = simulation.heading;
+                    simulation.climb_to(*altitude, ENGINE_CLIMB, |_| heading, ENGINE_SPEED, true);
+                    PhaseKind::Engine
+                }
+                Step::Land(waypoint) => {
+                    simulation.fly_to(waypoint, APPROACH_SPEED, None);
+                    PhaseKind::Landing
+                }
+                Step::Event(code) => {
+                    events.push((start, code.clone()));
+                    continue;
+                }
+            };
+            if simulation.second() > start {
+                phases.push(Phase { kind, start: self.time_at(start), end: self.time_at(simulation.second()) });
+            }
+        }
+        let (records, truth) = self.records(&simulation.samples, &events);
+        SyntheticFlight { document: IGCDocument::from_records(records), truth, phases }
+    }
+
+    fn time_at(&self, second: usize) -> Time {
+        let mut time = self.start_time.clone();
+        time.add_seconds(second as u32);
+        time
+    }
+
+    /// The records of the file and the fixes without noise
+    fn records(&self, samples: &[Sample], events: &[(usize, String)]) -> (Vec<Record>, Vec<Fix>) {
+        let mut records = vec![Record::A(FlightRecorderID { manufacturer: "XSY".into(), id: "001".into(), extension: "FLIGHT:1".into() })];
+        let fix_accuracy = (self.noise.0 * 2.).round().max(3.) as u32;
+        let headers = [
+            format!("HFDTEDATE:{},01", self.date),
+            format!("HFFXA{fix_accuracy:03}"),
+            format!("HFPLTPILOTINCHARGE:{}", self.pilot),
+            "HFCM2CREW2:".into(),
+            format!("HFGTYGLIDERTYPE:{}", self.glider.0),
+            format!("HFGIDGLIDERID:{}", self.glider.1),
+            "HFDTMGPSDATUM:WGS84".into(),
+            "HFRFWFIRMWAREVERSION:1.0".into(),
+            "HFRHWHARDWAREVERSION:1.0".into(),
+            "HFFTYFRTYPE:igc_parser,synthetic".into(),
+            "HFGPSRECEIVER:synthetic".into(),
+            "HFPRSPRESSALTSENSOR:synthetic".into(),
+            "HFALGALTGPS:GEO".into(),
+            "HFALPALTPRESSURE:ISA".into(),
+        ];
+        records.extend(headers.iter().filter_map(|header| FileHeaderRecord::parse(header).ok()).map(Record::H));
+
+        if !self.extensions.is_empty() {
+            let mut start = EXTENSION_START as u8;
+            let extensions = self.extensions.iter().map(|channel| {
+                let end = start + channel.width() as u8 - 1;
+                let extension = (start, end, channel.code().into());
+                start = end + 1;
+                extension
+            }).collect::<Vec<_>>();
+            records.push(Record::I(Extension { extension_type: ExtensionType::I, number_of_extensions: extensions.len() as u8, extensions }));
+        }
+
+        if self.task.len() >= 2 {
+            let last = samples.last().map_or(self.start.clone(), |sample| sample.position.clone());
+            let declaration = format!("C{}{}{}0001{:02}", self.date, self.start_time, self.date, self.task.len() - 2);
+            records.extend(TaskInfo::parse(&declaration).ok().map(Record::C));
+            let points = [(self.start.clone(), "TAKEOFF".to_string())].into_iter().chain(self.task.iter().cloned()).chain([(last, "LANDING".to_string())]);
+            records.extend(points.map(|(coordinate, name)| Record::C(TaskInfo::TaskPoint(TaskPoint { coordinate, name: Some(name.into()) }))));
+        }
+
+        let mut random = Random(self.seed);
+        let mut events = events.iter().peekable();
+        let mut truth = Vec::new();
+        for (second, sample) in samples.iter().enumerate().step_by(self.interval as usize) {
+            let timestamp = self.time_at(second);
+            while let Some((_, code)) = events.next_if(|(event_second, _)| *event_second <= second) {
+                records.push(Record::E(Event { time: self.time_at(second), event_type: code.as_str().into(), extension: "".into() }));
+            }
+            let extension = self.extensions.iter().map(|channel| sample.extension(*channel, fix_accuracy, &mut random)).collect::<String>();
+            let fix = Fix {
+                timestamp,
+                coordinates: written(&sample.position),
+                pressure_alt: sample.altitude.round() as i16,
+                gps_alt: Some(sample.altitude.round() as i16),
+                extension: extension.into(),
+            };
+            let (north, east) = (random.gaussian() * self.noise.0, random.gaussian() * self.noise.0);
+            let coordinates = match north != 0. || east != 0. {
+                true => written(&sample.position.destination(east.atan2(north).to_degrees(), north.hypot(east))),
+                false => fix.coordinates.clone(),
+            };
+            let pressure_alt = (sample.altitude + random.gaussian() * self.noise.1).round() as i16;
+            let gps_alt = Some((sample.altitude + random.gaussian() * self.noise.1).round() as i16);
+            records.push(Record::B(Fix { coordinates, pressure_alt, gps_alt, ..fix.clone() }));
+            truth.push(fix);
+        }
+        let end = self.time_at(samples.len().saturating_sub(1));
+        records.extend(events.map(|(_, code)| Record::E(Event { time: end.clone(), event_type: code.as_str().into(), extension: "".into() })));
+        (records, truth)
+    }
+}
+
+/// The coordinate as it reads back from a B record, to a thousandth of a minute
+fn written(coordinate: &Coordinate) -> Coordinate {
+    Coordinate::parse(&coordinate.to_string()).unwrap_or_else(|_| coordinate.clone())
+}
+
+/// The state of the glider at the end of a second
+#[derive(Debug, Clone)]
+struct Sample {
+    position: Coordinate,
+    altitude: f64,
+    /// m/s
+    ground_speed: f64,
+    track: f64,
+    /// m/s
+    airspeed: f64,
+    engine: bool,
+}
+
+impl Sample {
+    fn extension(&self, channel: ExtensionChannel, fix_accuracy: u32, random: &mut Random) -> String {
+        let value = match channel {
+            ExtensionChannel::FXA => fix_accuracy as f64,
+            ExtensionChannel::SIU => 8. + (random.uniform() * 4.).floor(),
+            ExtensionChannel::ENL if self.engine => 800. + random.uniform() * 150.,
+            ExtensionChannel::ENL => self.airspeed * 3.6 / 5. + random.uniform() * 20.,
+            ExtensionChannel::GSP => self.ground_speed * 3.6,
+            ExtensionChannel::TRT => self.track.rem_euclid(360.),
+            ExtensionChannel::TAS => self.airspeed * 3.6,
+        };
+        let width = channel.width();
+        let max = 10f64.powi(width as i32) - 1.;
+        format!("{:0width$}", value.round().clamp(0., max) as u32)
+    }
+}
+
+/// Flies the script one second at a time
+struct Simulation<'a> {
+    generator: &'a FlightGenerator,
+    heading: f64,
+    /// East and north components of the wind in m/s
+    wind: (f64, f64),
+    samples: Vec<Sample>,
+}
+
+impl<'a> Simulation<'a> {
+    fn new(generator: &'a FlightGenerator) -> Self {
+        let (direction, speed) = generator.wind;
+        let towards = (direction + 180.).to_radians();
+        let start = Sample { position: generator.start.clone(), altitude: generator.elevation, ground_speed: 0., track: 0., airspeed: 0., engine: false };
+        Self { generator, heading: 0., wind: (towards.sin() * speed / 3.6, towards.cos() * speed / 3.6), samples: vec![start] }
+    }
+
+    fn second(&self) -> usize {
+        self.samples.len() - 1
+    }
+
+    fn last(&self) -> &Sample {
+        &self.samples[self.samples.len() - 1]
+    }
+
+    fn stand(&mut self) {
+        let sample = Sample { altitude: self.generator.elevation, ground_speed: 0., airspeed: 0., engine: false, ..self.last().clone() };
+        self.samples.push(sample);
+    }
+
+    /// Flies on the heading given by `heading` from the one before, drifting with the wind, until reaching the altitude
+    fn climb_to(&mut self, altitude: f64, rate: f64, heading: impl Fn(f64) -> f64, airspeed: f64, engine: bool) {
+        if rate <= 0. { return }
+        let airspeed = airspeed / 3.6;
+        while self.last().altitude < altitude {
+            self.heading = heading(self.heading).rem_euclid(360.);
+            let (east, north) = (self.heading.to_radians().sin() * airspeed + self.wind.0, self.heading.to_radians().cos() * airspeed + self.wind.1);
+            let track = east.atan2(north).to_degrees().rem_euclid(360.);
+            let ground_speed = east.hypot(north);
+            let last = self.last();
+            let position = last.position.destination(track, ground_speed);
+            let altitude = (last.altitude + rate).min(altitude);
+            self.samples.push(Sample { position, altitude, ground_speed, track, airspeed, engine });
+        }
+    }
+
+    /// Flies the track straight to the waypoint, sinking at `sink_rate` or steadily down to the field when it is `None`
+    fn fly_to(&mut self, waypoint: &Coordinate, airspeed: f64, sink_rate: Option<f64>) {
+        let airspeed = airspeed / 3.6;
+        loop {
+            let last = self.last();
+            let remaining = last.position.distance_to(waypoint);
+            if remaining < 1. { break }
+            let track = last.position.bearing_to(waypoint);
+            let (sin, cos) = track.to_radians().sin_cos();
+            let (along, across) = (self.wind.0 * sin + self.wind.1 * cos, self.wind.0 * cos - self.wind.1 * sin);
+            let correction = (-across / airspeed).clamp(-1., 1.).asin();
+            let ground_speed = (airspeed * correction.cos() + along).max(1.);
+            let distance = ground_speed.min(remaining);
+            let position = if distance == remaining { waypoint.clone() } else { last.position.destination(track, distance) };
+            let sink = sink_rate.unwrap_or((last.altitude - self.generator.elevation) * distance / remaining);
+            let altitude = (last.altitude - sink).max(self.generator.elevation);
+            self.heading = (track + correction.to_degrees()).rem_euclid(360.);
+            self.samples.push(Sample { position, altitude, ground_speed: distance, track, airspeed, engine: false });
+        }
+    }
+}
+
+/// SplitMix64, so a seed always gives the same noise
+struct Random(u64);
+
+impl Random {
+    /// Uniform in [0, 1)
+    fn uniform(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        (z ^ (z >> 31)) as f64 / (u64::MAX as f64 + 1.)
+    }
+
+    /// Standard normal from the Box-Muller transform
+    fn gaussian(&mut self) -> f64 {
+        let (u, v) = (1. - self.uniform(), self.uniform());
+        (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::badges::takeoff_and_landing;
+    use crate::igc_file::IGCFile;
+
+    fn home() -> Coordinate {
+        Coordinate::from_decimal_degrees(51., -1.)
+    }
+
+    fn generator() -> FlightGenerator {
+        let turn = home().destination(90., 15_000.);
+        FlightGenerator::new(home(), 100.)
+            .with_wind(270., 20.)
+            .with_interval(4)
+            .with_task(&[(home(), "START"), (turn.clone(), "EAST"), (home(), "FINISH")])
+            .with_extension(ExtensionChannel::FXA)
+            .with_extension(ExtensionChannel::SIU)
+            .with_extension(ExtensionChannel::ENL)
+            .ground(600)
+            .launch(600.)
+            .event("PEV")
+            .climb(1200., 2.)
+            .glide_to(turn)
+            .engine(1000.)
+            .glide_to(home())
+            .land_at(home().destination(180., 2000.))
+            .ground(300)
+    }
+
+    #[test]
+    fn scripted_flight() {
+        let flight = generator().generate();
+        let kinds = flight.phases.iter().map(|phase| phase.kind).collect::<Vec<_>>();
+        use PhaseKind::*;
+        assert_eq!(kinds, [Ground, Launch, Climb, Glide, Engine, Glide, Landing, Ground]);
+        assert_eq!(flight.phases[1].start, Time::from_hms(10, 10, 0).unwrap());
+        assert!(flight.truth.iter().all(|fix| fix.pressure_alt <= 1200 && fix.pressure_alt >= 100));
+
+        let igc_file = IGCFile::parse(&flight.document.to_string()).unwrap();
+        let fixes = igc_file.get_fixes().iter().map(|fix| fix.as_ref().unwrap().clone()).collect::<Vec<_>>();
+        assert_eq!(fixes, flight.truth);
+        assert!(fixes.windows(2).all(|pair| pair[1].timestamp.seconds_since_midnight() - pair[0].timestamp.seconds_since_midnight() == 4));
+        assert_eq!(igc_file.get_task_info().len(), 6);
+        assert_eq!(igc_file.get_events().len(), 1);
+        assert_eq!(fixes[0].extension.len(), 8);
+        assert!(fixes.last().unwrap().coordinates.distance_to(&home().destination(180., 2000.)) < 5.);
+        assert_eq!(takeoff_and_landing(&fixes).map(|(takeoff, _)| takeoff), Some(150));
+    }
+
+    #[test]
+    fn wind_drifts_climbs() {
+        let flight = FlightGenerator::new(home(), 0.).with_wind(270., 36.).climb(1000., 2.).generate();
+        assert_eq!(flight.truth.len(), 501);
+        // 500 seconds drifting east at 10 m/s
+        let drift = home().distance_to(&flight.truth[500].coordinates);
+        assert!((drift - 5000.).abs() < 2. * CIRCLING_RADIUS, "{drift}");
+        assert!(home().bearing_to(&flight.truth[500].coordinates) > 45. && home().bearing_to(&flight.truth[500].coordinates) < 135.);
+    }
+
+    #[test]
+    fn seeded_noise() {
+        let noisy = generator().with_noise(5., 3.).with_seed(7);
+        assert_eq!(noisy.generate().document.to_string(), noisy.generate().document.to_string());
+        assert_ne!(noisy.generate().document.to_string(), noisy.clone().with_seed(8).generate().document.to_string());
+        let flight = noisy.generate();
+        let fixes = flight.document.fixes().collect::<Vec<_>>();
+        assert!(fixes.iter().zip(&flight.truth).any(|(fix, truth)| fix.coordinates != truth.coordinates));
+        assert!(fixes.iter().zip(&flight.truth).all(|(fix, truth)| fix.coordinates.distance_to(&truth.coordinates) < 50.));
+    }
+}
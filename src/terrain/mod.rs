@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::airspace::{AltitudeSource, Terrain};
+use crate::error::IGCError::TerrainInitError;
+use crate::records::fix::Fix;
+use crate::records::util::{Coordinate, Time};
+use crate::Result;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// Value of an SRTM sample without data
+const VOID: i16 = -32768;
+
+/// A one by one degree SRTM tile, row 0 is the northern edge
+#[derive(Debug, Clone)]
+struct Tile {
+    size: usize,
+    samples: Vec<i16>,
+}
+
+impl Tile {
+    /// Tiles are square grids of big endian 16 bit samples, 1201 wide for SRTM3 and 3601 wide for SRTM1
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let samples = bytes.len() / 2;
+        let size = (samples as f64).sqrt().round() as usize;
+        if size < 2 || size * size != samples || !bytes.len().is_multiple_of(2) { return None }
+        let samples = bytes.chunks_exact(2).map(|sample| i16::from_be_bytes([sample[0], sample[1]])).collect();
+        Some(Self { size, samples })
+    }
+
+    /// Bilinear interpolation of the samples around the position, voids are left out
+    fn elevation(&self, latitude_offset: f64, longitude_offset: f64) -> Option<f64> {
+        let last = (self.size - 1) as f64;
+        let row = ((1. - latitude_offset) * last).clamp(0., last);
+        let column = (longitude_offset * last).clamp(0., last);
+        let (top, left) = ((row.floor() as usize).min(self.size - 2), (column.floor() as usize).min(self.size - 2));
+        let (dy, dx) = (row - top as f64, column - left as f64);
+        let corners = [
+            (top, left, (1. - dy) * (1. - dx)),
+            (top, left + 1, (1. - dy) * dx),
+            (top + 1, left, dy * (1. - dx)),
+            (top + 1, left + 1, dy * dx),
+        ];
+        let (sum, weight) = corners
+            .iter()
+            .map(|&(row, column, weight)| (self.samples[row * self.size + column], weight))
+            .filter(|&(sample, _)| sample != VOID)
+            .fold((0., 0.), |(sum, total), (sample, weight)| (sum + sample as f64 * weight, total + weight));
+        (weight > 0.).then(|| sum / weight)
+    }
+}
+
+/// Ground elevation and height above ground of a fix
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundClearance {
+    /// Elevation in meters above mean sea level, `None` if no tile covers the fix
+    pub ground: Option<f64>,
+    /// Height in meters above ground level
+    pub agl: Option<f64>,
+}
+
+/// A period where the height above ground stayed below a threshold
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowSegment {
+    pub start_index: usize,
+    pub end_index: usize,
+    pub start: Time,
+    pub end: Time,
+    /// Index of the fix with the lowest height above ground
+    pub min_index: usize,
+    pub min_agl: f64,
+}
+
+/// Ground elevation from SRTM `.hgt` tiles on disk, named after their south west corner like `N51W002.hgt`
+/// # examples
+/// ```rust,no_run
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::airspace::AltitudeSource;
+/// use igc_parser::terrain::SrtmTerrain;
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let fixes = file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+/// let terrain = SrtmTerrain::for_fixes("./srtm", &fixes).unwrap();
+/// for segment in terrain.low_segments(&fixes, &AltitudeSource::Gps, 150.) {
+///     println!("{:.0}m above ground at {:?}", segment.min_agl, fixes[segment.min_index].timestamp);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SrtmTerrain {
+    directory: PathBuf,
+    tiles: HashMap<(i16, i16), Tile>,
+}
+
+impl SrtmTerrain {
+    /// Terrain reading tiles from `directory`, no tiles are loaded until requested
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self { directory: directory.as_ref().to_path_buf(), tiles: HashMap::new() }
+    }
+
+    /// Terrain with every tile under the fixes loaded, tiles missing from the directory are skipped
+    pub fn for_fixes(directory: impl AsRef<Path>, fixes: &[Fix]) -> Result<Self> {
+        let mut terrain = Self::new(directory);
+        for fix in fixes {
+            let (latitude, longitude) = fix.coordinates.to_decimal_degrees();
+            terrain.load_tile(latitude.floor() as i16, longitude.floor() as i16)?;
+        }
+        Ok(terrain)
+    }
+
+    /// File name of the tile with the south west corner at the given whole degrees, e.g. `N51W002.hgt`
+    pub fn tile_name(latitude: i16, longitude: i16) -> String {
+        let north_south = if latitude >= 0 { 'N' } else { 'S' };
+        let east_west = if longitude >= 0 { 'E' } else { 'W' };
+        format!("{north_south}{:02}{east_west}{:03}.hgt", latitude.unsigned_abs(), longitude.unsigned_abs())
+    }
+
+    /// Loads the tile with the south west corner at the given whole degrees, `false` if there is no such file
+    pub fn load_tile(&mut self, latitude: i16, longitude: i16) -> Result<bool> {
+        if self.tiles.contains_key(&(latitude, longitude)) { return Ok(true) }
+        let name = Self::tile_name(latitude, longitude);
+        let Some(path) = [name.clone(), name.to_lowercase()].into_iter().map(|name| self.directory.join(name)).find(|path| path.is_file()) else {
+            return Ok(false)
+        };
+        match fs::read(&path) {
+            Ok(bytes) => self.insert_tile(latitude, longitude, &bytes).map(|_| true),
+            Err(error) => Err(TerrainInitError(format!("could not read '{}': {error}", path.display()))),
+        }
+    }
+
+    /// Adds a tile from the contents of a `.hgt` file
+    pub fn insert_tile(&mut self, latitude: i16, longitude: i16, bytes: &[u8]) -> Result<()> {
+        let Some(tile) = Tile::parse(bytes) else {
+            return Err(TerrainInitError(format!("{} is not a square grid of 16 bit samples", Self::tile_name(latitude, longitude))))
+        };
+        self.tiles.insert((latitude, longitude), tile);
+        Ok(())
+    }
+
+    /// Ground elevation in meters above mean sea level, `None` if the tile is not loaded or has no data there
+    pub fn elevation(&self, coordinate: &Coordinate) -> Option<f64> {
+        let (latitude, longitude) = coordinate.to_decimal_degrees();
+        let (south, west) = (latitude.floor(), longitude.floor());
+        self.tiles.get(&(south as i16, west as i16))?.elevation(latitude - south, longitude - west)
+    }
+
+    /// Ground elevation and height above ground of each fix, using `altitude_source` for the altitude of the fix
+    pub fn ground_clearance(&self, fixes: &[Fix], altitude_source: &AltitudeSource) -> Vec<GroundClearance> {
+        fixes
+            .iter()
+            .map(|fix| {
+                let ground = self.elevation(&fix.coordinates);
+                GroundClearance { ground, agl: ground.map(|ground| altitude_source.altitude(fix) - ground) }
+            })
+            .collect()
+    }
+
+    /// Periods where the height above ground was below `threshold` meters, fixes without ground elevation end a period
+    pub fn low_segments(&self, fixes: &[Fix], altitude_source: &AltitudeSource, threshold: f64) -> Vec<LowSegment> {
+        let mut segments = Vec::new();
+        let mut current: Option<LowSegment> = None;
+        for (i, (fix, clearance)) in fixes.iter().zip(self.ground_clearance(fixes, altitude_source)).enumerate() {
+            match (clearance.agl.filter(|agl| *agl < threshold), current.as_mut()) {
+                (Some(agl), Some(segment)) => {
+                    segment.end_index = i;
+                    segment.end = fix.timestamp.clone();
+                    if agl < segment.min_agl {
+                        segment.min_index = i;
+                        segment.min_agl = agl;
+                    }
+                }
+                (Some(agl), None) => current = Some(LowSegment {
+                    start_index: i,
+                    end_index: i,
+                    start: fix.timestamp.clone(),
+                    end: fix.timestamp.clone(),
+                    min_index: i,
+                    min_agl: agl,
+                }),
+                (None, _) => segments.extend(current.take()),
+            }
+        }
+        segments.extend(current);
+        segments
+    }
+}
+
+impl Terrain for SrtmTerrain {
+    fn elevation(&self, coordinate: &Coordinate) -> Option<f64> {
+        SrtmTerrain::elevation(self, coordinate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3 by 3 tile rising 100m per sample towards the east with a void in the north east corner
+    fn tile_bytes() -> Vec<u8> {
+        [0i16, 100, VOID, 0, 100, 200, 0, 100, 200].iter().flat_map(|sample| sample.to_be_bytes()).collect()
+    }
+
+    #[test]
+    fn tile_names() {
+        assert_eq!(SrtmTerrain::tile_name(51, -2), "N51W002.hgt");
+        assert_eq!(SrtmTerrain::tile_name(-34, 151), "S34E151.hgt");
+        assert_eq!(SrtmTerrain::tile_name(0, 0), "N00E000.hgt");
+    }
+
+    #[test]
+    fn bilinear_elevation() {
+        let mut terrain = SrtmTerrain::new(".");
+        terrain.insert_tile(51, -2, &tile_bytes()).unwrap();
+        assert!(terrain.insert_tile(52, -2, &[0, 0, 0]).is_err());
+
+        let elevation = |latitude: f64, longitude: f64| terrain.elevation(&Coordinate::from_decimal_degrees(latitude, longitude)).unwrap();
+        assert!((elevation(51.5, -2.) - 0.).abs() < 0.1);
+        assert!((elevation(51.5, -1.75) - 50.).abs() < 0.5);
+        assert!((elevation(51.25, -1.25) - 150.).abs() < 0.5);
+        // the void in the north east corner is left out
+        assert!((elevation(51.75, -1.25) - 133.3).abs() < 0.5);
+        assert!(terrain.elevation(&Coordinate::from_decimal_degrees(50.5, -1.5)).is_none());
+    }
+
+    #[test]
+    fn low_segments_from_directory() {
+        let directory = std::env::temp_dir().join(format!("igc_parser_srtm_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("N51W002.hgt"), tile_bytes()).unwrap();
+
+        let fixes = [
+            Fix::at(43200, 51.5, -1.9, 400),
+            Fix::at(43210, 51.5, -1.5, 150),
+            Fix::at(43220, 51.5, -1.4, 200),
+            Fix::at(43230, 51.5, -1.2, 500),
+            Fix::at(43240, 50.5, -1.2, 100),
+        ];
+        let terrain = SrtmTerrain::for_fixes(&directory, &fixes).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let clearance = terrain.ground_clearance(&fixes, &AltitudeSource::Gps);
+        assert!((clearance[1].ground.unwrap() - 100.).abs() < 0.5);
+        assert!((clearance[1].agl.unwrap() - 50.).abs() < 0.5);
+        assert_eq!(clearance[4], GroundClearance { ground: None, agl: None });
+
+        let segments = terrain.low_segments(&fixes, &AltitudeSource::Gps, 100.);
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_index, segments[0].end_index, segments[0].min_index), (1, 2, 1));
+        assert_eq!(segments[0].end, Time::from_hms(12, 0, 20).unwrap());
+        assert!((segments[0].min_agl - 50.).abs() < 0.5);
+    }
+}
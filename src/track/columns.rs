@@ -0,0 +1,183 @@
+use crate::analysis::Day;
+use crate::igc_file::IGCFile;
+use crate::metadata::FlightMetadata;
+use crate::records::extension::Extension;
+use crate::records::file_header::FileHeaderRef;
+use crate::records::fix::{Fix, FixRef};
+use crate::records::util::Date;
+use crate::StrWrapper;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// The valid fixes of a flight as one contiguous vector per field, for per column statistics and handing over to Arrow
+///
+/// Every vector has one element per fix. Timestamps are Unix timestamps in seconds when the date is known and otherwise
+/// seconds from midnight before the first fix, a fix more than 12 hours before the one before it is taken to be on the next day.
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::track::columns::FixColumns;
+/// let columns = FixColumns::parse(&fs::read_to_string("./examples/example.igc").unwrap());
+/// let highest = columns.gps_altitudes.iter().zip(&columns.gps_valid).filter(|(_, valid)| **valid).map(|(altitude, _)| altitude).max();
+/// println!("{} fixes, highest at {highest:?} m", columns.len());
+/// assert!(columns.extension("FXA").is_some());
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FixColumns {
+    pub timestamps: Vec<i64>,
+    /// Signed decimal degrees, negative in the southern hemisphere
+    pub latitudes: Vec<f64>,
+    /// Signed decimal degrees, negative in the western hemisphere
+    pub longitudes: Vec<f64>,
+    /// Meters
+    pub pressure_altitudes: Vec<i32>,
+    /// Meters, zero where `gps_valid` is false
+    pub gps_altitudes: Vec<i32>,
+    pub gps_valid: Vec<bool>,
+    /// The numeric extensions declared in the I record, in the order of the I record
+    pub extensions: Vec<ExtensionColumn>,
+    pub date: Option<Date>,
+}
+
+/// The values of one extension of the B records, e.g. `ENL`
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionColumn {
+    pub code: StrWrapper,
+    /// Zero where `valid` is false
+    pub values: Vec<i32>,
+    /// False where the extension is missing or not a number
+    pub valid: Vec<bool>,
+}
+
+impl FixColumns {
+    /// Reads the columns straight from the text of an IGC file without allocating a `Fix` for every B record
+    ///
+    /// B records that fail to parse are left out, use `IGCFile` to see the errors
+    pub fn parse(content: &str) -> Self {
+        let mut columns = Self::default();
+        let mut i_record = None;
+        let mut day = Day::default();
+        for line in content.lines() {
+            match line.as_bytes().first() {
+                Some(b'B') => if let Ok(fix) = FixRef::parse(line) { columns.push(&fix, i_record.as_ref(), &mut day) },
+                Some(b'I') if i_record.is_none() => if let Ok(extension) = Extension::parse(line) {
+                    columns.declare(&extension);
+                    i_record = Some(extension);
+                },
+                Some(b'H') if columns.date.is_none() && line.get(2..5) == Some("DTE") => {
+                    if let Ok(FileHeaderRef::Date(date)) = FileHeaderRef::parse(line) { columns.date = Some(date) }
+                }
+                _ => {}
+            }
+        }
+        columns.finish()
+    }
+
+    /// Columns of fixes with the extensions declared in `i_record`
+    pub fn from_fixes<'a>(fixes: impl IntoIterator<Item = &'a Fix>, i_record: Option<&Extension>, date: Option<Date>) -> Self {
+        let mut columns = Self { date, ..Self::default() };
+        if let Some(i_record) = i_record { columns.declare(i_record) }
+        let mut day = Day::default();
+        for fix in fixes {
+            let fix = FixRef {
+                timestamp: fix.timestamp.clone(),
+                coordinates: fix.coordinates.clone(),
+                pressure_alt: fix.pressure_alt,
+                gps_alt: fix.gps_alt,
+                extension: &fix.extension,
+            };
+            columns.push(&fix, i_record, &mut day);
+        }
+        columns.finish()
+    }
+
+    /// Columns of the parsed fixes of a file using its first I record and the date from the `HFDTE` header
+    pub fn from_igc_file(igc_file: &IGCFile) -> Self {
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok());
+        let i_record = igc_file.get_i_extension_vec().iter().find_map(|extension| extension.as_ref().ok());
+        Self::from_fixes(fixes, i_record, FlightMetadata::from_igc_file(igc_file).date)
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// The column of the extension with the three letter code, `None` if the I record does not declare it
+    pub fn extension(&self, code: &str) -> Option<&ExtensionColumn> {
+        self.extensions.iter().find(|column| column.code.as_ref() == code)
+    }
+
+    fn declare(&mut self, i_record: &Extension) {
+        self.extensions = i_record.extensions.iter().map(|(_, _, code)| ExtensionColumn {
+            code: code.clone(),
+            values: vec![0; self.len()],
+            valid: vec![false; self.len()],
+        }).collect();
+    }
+
+    fn push(&mut self, fix: &FixRef, i_record: Option<&Extension>, day: &mut Day) {
+        self.timestamps.push(day.seconds(fix.timestamp.seconds_since_midnight() as i64));
+        let (latitude, longitude) = fix.coordinates.to_decimal_degrees();
+        self.latitudes.push(latitude);
+        self.longitudes.push(longitude);
+        self.pressure_altitudes.push(fix.pressure_alt as i32);
+        self.gps_altitudes.push(fix.gps_alt.unwrap_or(0) as i32);
+        self.gps_valid.push(fix.gps_alt.is_some());
+        let Some(i_record) = i_record else { return };
+        for column in &mut self.extensions {
+            let value = fix.get_extension_value(i_record, &column.code);
+            column.values.push(value.unwrap_or(0));
+            column.valid.push(value.is_some());
+        }
+    }
+
+    /// Moves the timestamps from seconds since midnight to Unix timestamps once the date is known
+    fn finish(mut self) -> Self {
+        if let Some(date) = &self.date {
+            let midnight = date.days_since_epoch() * 86400;
+            self.timestamps.iter_mut().for_each(|timestamp| *timestamp += midnight);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::track::indexed::IndexedTrack;
+
+    #[test]
+    fn parsed_like_igc_file() {
+        let content = fs::read_to_string("./examples/example.igc").unwrap();
+        let columns = FixColumns::parse(&content);
+        let igc_file = IGCFile::parse(&content).unwrap();
+        assert_eq!(columns, FixColumns::from_igc_file(&igc_file));
+        assert_eq!(columns.len(), 5825);
+        let track = IndexedTrack::from_igc_file(&igc_file);
+        assert_eq!(columns.timestamps[100], track.timestamp(100).unwrap());
+        assert_eq!(columns.extensions.iter().map(|column| column.code.as_ref()).collect::<Vec<_>>(), ["FXA", "SIU"]);
+        let fix = &track.fixes()[100];
+        assert_eq!((columns.latitudes[100], columns.longitudes[100]), fix.coordinates.to_decimal_degrees());
+        assert_eq!(columns.extension("SIU").unwrap().values[100], fix.extension[3..5].parse::<i32>().unwrap());
+    }
+
+    #[test]
+    fn over_midnight_without_date() {
+        let content = "I013638FXA\nB2359595100000N00100000WV0010000000\nB0000095100000N00100000WA0010000200\nB0000195100000N00100000WA0010000200042";
+        let columns = FixColumns::parse(content);
+        assert_eq!(columns.timestamps, [86399, 86409, 86419]);
+        assert_eq!(columns.gps_valid, [false, true, true]);
+        assert_eq!(columns.gps_altitudes, [0, 200, 200]);
+        let fix_accuracy = columns.extension("FXA").unwrap();
+        assert_eq!(fix_accuracy.values, [0, 0, 42]);
+        assert_eq!(fix_accuracy.valid, [false, false, true]);
+        assert!(columns.extension("ENL").is_none());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::analysis::Day;
+use crate::igc_file::IGCFile;
+use crate::metadata::FlightMetadata;
+use crate::records::fix::Fix;
+use crate::records::util::{Date, Time};
+use crate::track::interpolate;
+
+/// Fixes indexed by time for looking up where the glider was at a given time
+///
+/// Times are taken to be on the day of the first fix, or the day after for times before it, so flights over midnight work
+/// as long as they last less than 24 hours. Absolute timestamps are Unix timestamps in seconds and need the date of the flight.
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::track::indexed::IndexedTrack;
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let track = IndexedTrack::from_igc_file(&file);
+/// for event in file.get_events().iter().filter_map(|event| event.as_ref().ok()) {
+///     if let Some(fix) = track.position_at(&event.time) {
+///         println!("{} at {:?}", event.event_type, fix.coordinates.to_decimal_degrees());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTrack {
+    fixes: Vec<Fix>,
+    /// Seconds from the first fix, never decreasing
+    offsets: Vec<i64>,
+    date: Option<Date>,
+}
+
+impl IndexedTrack {
+    /// Indexes fixes in time order, a fix more than 12 hours before the one before it is taken to be on the next day
+    pub fn new(fixes: Vec<Fix>) -> Self {
+        let mut day = Day::default();
+        let offsets = fixes.iter().map(|fix| day.seconds(fix.timestamp.seconds_since_midnight() as i64)).collect::<Vec<_>>();
+        let start = offsets.first().copied().unwrap_or(0);
+        // fixes running slightly backwards in time keep the offsets sorted for the binary search
+        let offsets = offsets.iter().scan(0, |latest, offset| {
+            *latest = (offset - start).max(*latest);
+            Some(*latest)
+        }).collect();
+        Self { fixes, offsets, date: None }
+    }
+
+    /// Indexes the parsed fixes of a file using the date from the `HFDTE` header
+    pub fn from_igc_file(igc_file: &IGCFile) -> Self {
+        let fixes = igc_file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect();
+        let track = Self::new(fixes);
+        match FlightMetadata::from_igc_file(igc_file).date {
+            Some(date) => track.with_date(date),
+            None => track,
+        }
+    }
+
+    /// Sets the date of the first fix, needed for absolute timestamps
+    pub fn with_date(mut self, date: Date) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    pub fn date(&self) -> Option<&Date> {
+        self.date.as_ref()
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+
+    pub fn len(&self) -> usize {
+        self.fixes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty()
+    }
+
+    /// Unix timestamp of the fix at `index`, `None` without a date or if there is no such fix
+    pub fn timestamp(&self, index: usize) -> Option<i64> {
+        Some(self.start_timestamp()? + self.offsets.get(index)?)
+    }
+
+    /// Index of the fix closest in time, the earlier one on a tie
+    pub fn nearest(&self, time: &Time) -> Option<usize> {
+        self.nearest_offset(self.offset_of(time)?)
+    }
+
+    /// Index of the fix closest to a Unix timestamp, `None` without a date
+    pub fn nearest_to_timestamp(&self, timestamp: i64) -> Option<usize> {
+        self.nearest_offset(timestamp - self.start_timestamp()?)
+    }
+
+    /// Position and altitudes at `time` interpolated between the fixes around it, `None` outside the track
+    pub fn position_at(&self, time: &Time) -> Option<Fix> {
+        self.position_at_offset(self.offset_of(time)?)
+    }
+
+    /// Position and altitudes at a Unix timestamp, `None` outside the track or without a date
+    pub fn position_at_timestamp(&self, timestamp: i64) -> Option<Fix> {
+        self.position_at_offset(timestamp - self.start_timestamp()?)
+    }
+
+    /// Fixes from `from` up to and including `to`, empty if the times are in the wrong order or outside the track
+    pub fn slice(&self, from: &Time, to: &Time) -> &[Fix] {
+        match (self.offset_of(from), self.offset_of(to)) {
+            (Some(from), Some(to)) => self.slice_offsets(from, to),
+            _ => &[],
+        }
+    }
+
+    /// Fixes between two Unix timestamps, both inclusive
+    pub fn slice_timestamps(&self, from: i64, to: i64) -> &[Fix] {
+        match self.start_timestamp() {
+            Some(start) => self.slice_offsets(from - start, to - start),
+            None => &[],
+        }
+    }
+
+    fn start_timestamp(&self) -> Option<i64> {
+        let (date, first) = (self.date.as_ref()?, self.fixes.first()?);
+        Some(date.days_since_epoch() * 86400 + first.timestamp.seconds_since_midnight() as i64)
+    }
+
+    /// Seconds from the first fix, a time before it is on the next day unless it is closer to the start than to the end
+    fn offset_of(&self, time: &Time) -> Option<i64> {
+        let first = self.fixes.first()?;
+        let offset = (time.seconds_since_midnight() as i64 - first.timestamp.seconds_since_midnight() as i64).rem_euclid(86400);
+        let end = *self.offsets.last()?;
+        Some(if offset > end && offset - end > 86400 - offset { offset - 86400 } else { offset })
+    }
+
+    fn nearest_offset(&self, offset: i64) -> Option<usize> {
+        if self.fixes.is_empty() { return None }
+        let after = self.offsets.partition_point(|&fix_offset| fix_offset < offset);
+        if after == 0 { return Some(0) }
+        if after == self.offsets.len() { return Some(after - 1) }
+        Some(if self.offsets[after] - offset < offset - self.offsets[after - 1] { after } else { after - 1 })
+    }
+
+    fn position_at_offset(&self, offset: i64) -> Option<Fix> {
+        if offset < 0 || offset > *self.offsets.last()? { return None }
+        let after = self.offsets.partition_point(|&fix_offset| fix_offset < offset);
+        let mut time = self.fixes[0].timestamp.clone();
+        time.add_seconds(offset as u32);
+        if self.offsets[after] == offset { return Some(Fix { timestamp: time, ..self.fixes[after].clone() }) }
+        Some(interpolate(&self.fixes[after - 1], &self.fixes[after], &time))
+    }
+
+    fn slice_offsets(&self, from: i64, to: i64) -> &[Fix] {
+        let start = self.offsets.partition_point(|&offset| offset < from);
+        let end = self.offsets.partition_point(|&offset| offset <= to);
+        if start >= end { return &[] }
+        &self.fixes[start..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixes every 10 seconds from 23:59:30 to 00:00:30
+    fn track() -> IndexedTrack {
+        IndexedTrack::new((0..=6).map(|i| Fix::at(86370 + i * 10, 50. + i as f64 * 0.01, 10., 1000 + i as i16 * 100)).collect())
+    }
+
+    #[test]
+    fn nearest_fix() {
+        let track = track();
+        assert_eq!(track.nearest(&Time::from_hms(23, 59, 30).unwrap()), Some(0));
+        assert_eq!(track.nearest(&Time::from_hms(23, 59, 54).unwrap()), Some(2));
+        assert_eq!(track.nearest(&Time::from_hms(23, 59, 55).unwrap()), Some(2));
+        assert_eq!(track.nearest(&Time::from_hms(0, 0, 6).unwrap()), Some(4));
+        assert_eq!(track.nearest(&Time::from_hms(12, 0, 0).unwrap()), Some(6));
+        assert_eq!(track.nearest(&Time::from_hms(23, 0, 0).unwrap()), Some(0));
+        assert_eq!(IndexedTrack::new(Vec::new()).nearest(&Time::from_hms(12, 0, 0).unwrap()), None);
+    }
+
+    #[test]
+    fn interpolated_position() {
+        let track = track();
+        let fix = track.position_at(&Time::from_hms(0, 0, 5).unwrap()).unwrap();
+        assert_eq!(fix.timestamp, Time::from_hms(0, 0, 5).unwrap());
+        assert_eq!(fix.pressure_alt, 1350);
+        assert!((fix.coordinates.to_decimal_degrees().0 - 50.035).abs() < 0.0001);
+        assert_eq!(track.position_at(&Time::from_hms(0, 0, 30).unwrap()).unwrap().pressure_alt, 1600);
+        assert!(track.position_at(&Time::from_hms(0, 0, 31).unwrap()).is_none());
+        assert!(track.position_at(&Time::from_hms(23, 59, 29).unwrap()).is_none());
+    }
+
+    #[test]
+    fn absolute_timestamps() {
+        assert_eq!(track().timestamp(0), None);
+        let track = track().with_date(Date { d: 31, m: 12, y: 23 });
+        // 2023-12-31 23:59:30 UTC
+        assert_eq!(track.timestamp(0), Some(1704067170));
+        assert_eq!(track.timestamp(6), Some(1704067230));
+        assert_eq!(track.nearest_to_timestamp(1704067202), Some(3));
+        assert_eq!(track.position_at_timestamp(1704067205).unwrap().pressure_alt, 1350);
+        assert_eq!(track.slice_timestamps(1704067190, 1704067210).len(), 3);
+    }
+
+    #[test]
+    fn range_slicing() {
+        let track = track();
+        let slice = track.slice(&Time::from_hms(23, 59, 45).unwrap(), &Time::from_hms(0, 0, 10).unwrap());
+        assert_eq!(slice.iter().map(|fix| fix.pressure_alt).collect::<Vec<_>>(), [1200, 1300, 1400]);
+        assert!(track.slice(&Time::from_hms(0, 0, 10).unwrap(), &Time::from_hms(23, 59, 45).unwrap()).is_empty());
+    }
+}
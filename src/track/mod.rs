@@ -0,0 +1,82 @@
+use crate::analysis::seconds_since;
+use crate::records::fix::Fix;
+use crate::records::util::{Coordinate, Time, EARTH_RADIUS};
+
+/// Douglas-Peucker and Visvalingam simplification of fix sequences
+pub mod simplify;
+/// Fixed interval resampling and gap filling of fix sequences
+pub mod resample;
+/// Lookup of fixes and interpolated positions by time
+pub mod indexed;
+/// Fixes as one vector per field for vectorised analytics
+pub mod columns;
+
+/// Linear interpolation of position and altitudes between two fixes at `time`
+///
+/// The GPS altitude is only interpolated if both fixes have one, and the extension data is taken from the fix closest in time.
+/// A `time` before `from` or after `to` gets the position of the closer of the two.
+/// # examples
+/// ```rust
+/// use igc_parser::records::{fix::Fix, util::Time, Record};
+/// use igc_parser::track::interpolate;
+/// let from = match Record::parse("B1000005100000N00100000WA0010000200").unwrap() { Record::B(f) => f, _ => unreachable!() };
+/// let to = match Record::parse("B1000105100100N00100000WA0020000300").unwrap() { Record::B(f) => f, _ => unreachable!() };
+/// let fix = interpolate(&from, &to, &Time::from_hms(10, 0, 5).unwrap());
+/// assert_eq!(fix.pressure_alt, 150);
+/// assert_eq!(fix.gps_alt, Some(250));
+/// ```
+pub fn interpolate(from: &Fix, to: &Fix, time: &Time) -> Fix {
+    let duration = seconds_since(&from.timestamp, &to.timestamp);
+    let elapsed = seconds_since(&from.timestamp, time);
+    let fraction = match elapsed {
+        _ if duration == 0 => 0.,
+        // outside the two fixes, `elapsed` counts from `from` to a `time` before it through the next day
+        elapsed if elapsed > duration => if 86400 - elapsed < elapsed - duration { 0. } else { 1. },
+        elapsed => elapsed as f64 / duration as f64,
+    };
+    if fraction == 0. { return Fix { timestamp: time.clone(), ..from.clone() } }
+    if fraction == 1. { return Fix { timestamp: time.clone(), ..to.clone() } }
+    let (from_latitude, from_longitude) = from.coordinates.to_decimal_degrees();
+    let (to_latitude, to_longitude) = to.coordinates.to_decimal_degrees();
+    // the shorter way around when crossing the antimeridian
+    let longitude_change = (to_longitude - from_longitude + 540.) % 360. - 180.;
+    let longitude = (from_longitude + longitude_change * fraction + 540.) % 360. - 180.;
+    let lerp = |from: i16, to: i16| (from as f64 + (to as f64 - from as f64) * fraction).round() as i16;
+    Fix {
+        timestamp: time.clone(),
+        coordinates: Coordinate::from_decimal_degrees(from_latitude + (to_latitude - from_latitude) * fraction, longitude),
+        pressure_alt: lerp(from.pressure_alt, to.pressure_alt),
+        gps_alt: from.gps_alt.zip(to.gps_alt).map(|(from, to)| lerp(from, to)),
+        extension: if fraction < 0.5 { from.extension.clone() } else { to.extension.clone() },
+    }
+}
+
+/// Position of a fix in meters east and north on a plane touching the earth at `reference_latitude`
+pub(crate) fn project(coordinate: &Coordinate, reference_latitude: f64) -> (f64, f64) {
+    let (latitude, longitude) = coordinate.to_decimal_degrees();
+    let scale = EARTH_RADIUS * std::f64::consts::PI / 180.;
+    (longitude * scale * reference_latitude.to_radians().cos(), latitude * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_outside_the_fixes() {
+        let (from, to) = (Fix::at(36000, 51., 1., 1000), Fix::at(36010, 51.01, 1., 2000));
+        let fix = interpolate(&from, &to, &Time::from_hms(10, 0, 5).unwrap());
+        assert_eq!(fix.pressure_alt, 1500);
+
+        let before = interpolate(&from, &to, &Time::from_hms(9, 59, 59).unwrap());
+        assert_eq!((before.coordinates, before.pressure_alt), (from.coordinates.clone(), 1000));
+        assert_eq!(before.timestamp, Time::from_hms(9, 59, 59).unwrap());
+        let after = interpolate(&from, &to, &Time::from_hms(10, 0, 11).unwrap());
+        assert_eq!((after.coordinates, after.pressure_alt), (to.coordinates.clone(), 2000));
+
+        // over midnight
+        let (from, to) = (Fix::at(86395, 51., 1., 1000), Fix::at(86405, 51.01, 1., 2000));
+        assert_eq!(interpolate(&from, &to, &Time::from_hms(0, 0, 0).unwrap()).pressure_alt, 1500);
+        assert_eq!(interpolate(&from, &to, &Time::from_hms(23, 59, 50).unwrap()).pressure_alt, 1000);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::analysis::seconds_since;
+use crate::records::fix::Fix;
+use crate::track::interpolate;
+
+#[cfg(feature = "serde")] use serde::{Deserialize, Serialize};
+
+/// What to do with gaps between fixes longer than the maximum gap
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapFill {
+    /// Interpolate across the gap like between any other fixes
+    Interpolate,
+    /// Repeat the fix before the gap, as if the glider stood still
+    Hold,
+    /// Leave the gap without fixes
+    Skip,
+}
+
+/// Resampling of fixes to a fixed interval
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::track::resample::{GapFill, Resampler};
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let fixes = file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+/// let resampler = Resampler { interval: 10, gap_fill: GapFill::Skip, ..Resampler::default() };
+/// for fix in resampler.resample(&fixes).iter().take(3) {
+///     println!("{:?} {}m", fix.timestamp, fix.pressure_alt);
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resampler {
+    /// Seconds between resampled fixes
+    pub interval: u32,
+    /// Gaps between fixes longer than this in seconds are filled according to `gap_fill`
+    pub max_gap: u32,
+    pub gap_fill: GapFill,
+}
+
+impl Default for Resampler {
+    fn default() -> Self {
+        Self { interval: 1, max_gap: 10, gap_fill: GapFill::Interpolate }
+    }
+}
+
+impl Resampler {
+    pub fn new(interval: u32) -> Self {
+        Self { interval, ..Self::default() }
+    }
+
+    /// Fixes every `interval` seconds from the first fix, interpolated linearly between the original fixes
+    ///
+    /// The fixes should be in time order and span less than 24 hours.
+    pub fn resample(&self, fixes: &[Fix]) -> Vec<Fix> {
+        let Some(first) = fixes.first() else { return Vec::new() };
+        let offsets = fixes.iter().map(|fix| seconds_since(&first.timestamp, &fix.timestamp)).collect::<Vec<_>>();
+        let end = offsets.iter().copied().max().unwrap_or(0);
+        let mut resampled = Vec::new();
+        let mut segment = 0;
+        for offset in (0..=end).step_by(self.interval.max(1) as usize) {
+            while segment + 1 < fixes.len() && offsets[segment + 1] < offset { segment += 1 }
+            let mut time = first.timestamp.clone();
+            time.add_seconds(offset);
+            match fixes.get(segment + 1) {
+                Some(next) => resampled.extend(self.between(&fixes[segment], next, offset - offsets[segment], offsets[segment + 1] - offsets[segment])),
+                None => resampled.push(Fix { timestamp: time, ..fixes[segment].clone() }),
+            }
+        }
+        resampled
+    }
+
+    /// The original fixes with fixes every `interval` seconds added in gaps longer than `max_gap`
+    pub fn fill_gaps(&self, fixes: &[Fix]) -> Vec<Fix> {
+        let mut filled = Vec::with_capacity(fixes.len());
+        for pair in fixes.windows(2) {
+            filled.push(pair[0].clone());
+            let gap = seconds_since(&pair[0].timestamp, &pair[1].timestamp);
+            if gap <= self.max_gap { continue }
+            let interval = self.interval.max(1) as usize;
+            filled.extend((interval..gap as usize).step_by(interval).filter_map(|offset| self.between(&pair[0], &pair[1], offset as u32, gap)));
+        }
+        filled.extend(fixes.last().cloned());
+        filled
+    }
+
+    /// The fix `offset` seconds after `from` on the way to `to`, which comes `gap` seconds after it
+    fn between(&self, from: &Fix, to: &Fix, offset: u32, gap: u32) -> Option<Fix> {
+        let mut time = from.timestamp.clone();
+        time.add_seconds(offset);
+        if gap <= self.max_gap || offset == 0 || offset == gap {
+            return Some(interpolate(from, to, &time))
+        }
+        match self.gap_fill {
+            GapFill::Interpolate => Some(interpolate(from, to, &time)),
+            GapFill::Hold => Some(Fix { timestamp: time, ..from.clone() }),
+            GapFill::Skip => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::util::Time;
+
+    /// Fixes every 4 seconds across midnight with a 20 second gap between 8 and 28 seconds and no GPS altitude at 4 seconds
+    fn fixes() -> Vec<Fix> {
+        [0, 4, 8, 28, 32]
+            .iter()
+            .map(|&seconds| {
+                let fix = Fix::at(86390 + seconds, 51. + seconds as f64 * 0.001, 0., 1000 + seconds as i16 * 10);
+                Fix { gps_alt: fix.gps_alt.filter(|_| seconds != 4), extension: format!("{seconds:03}").into(), ..fix }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resample_interpolates() {
+        let resampled = Resampler::new(2).resample(&fixes());
+        assert_eq!(resampled.len(), 17);
+        assert_eq!(resampled[1].timestamp, Time::from_hms(23, 59, 52).unwrap());
+        assert_eq!(resampled[1].pressure_alt, 1020);
+        assert_eq!(resampled[1].gps_alt, None);
+        assert_eq!(resampled[3].gps_alt, None);
+        assert_eq!(resampled[4].gps_alt, Some(1080));
+        assert_eq!(resampled[10].timestamp, Time::from_hms(0, 0, 10).unwrap());
+        assert_eq!(resampled[10].pressure_alt, 1200);
+        assert!((resampled[10].coordinates.to_decimal_degrees().0 - 51.02).abs() < 0.0001);
+        // extension data comes from the closest original fix
+        assert_eq!(resampled[1].extension.as_ref(), "004");
+        assert_eq!(resampled[16].extension.as_ref(), "032");
+    }
+
+    #[test]
+    fn gap_fill() {
+        let hold = Resampler { interval: 2, gap_fill: GapFill::Hold, ..Resampler::default() }.resample(&fixes());
+        assert_eq!(hold.len(), 17);
+        assert_eq!(hold[10].pressure_alt, 1080);
+        assert_eq!(hold[10].extension.as_ref(), "008");
+
+        let skip = Resampler { interval: 2, gap_fill: GapFill::Skip, ..Resampler::default() }.resample(&fixes());
+        assert_eq!(skip.len(), 8);
+        assert_eq!(skip[5].timestamp, Time::from_hms(0, 0, 18).unwrap());
+
+        let filled = Resampler { interval: 5, ..Resampler::default() }.fill_gaps(&fixes());
+        assert_eq!(filled.iter().map(|fix| fix.pressure_alt).collect::<Vec<_>>(), [1000, 1040, 1080, 1130, 1180, 1230, 1280, 1320]);
+        assert_eq!(Resampler { gap_fill: GapFill::Skip, ..Resampler::default() }.fill_gaps(&fixes()), fixes());
+        assert!(Resampler::default().resample(&[]).is_empty());
+    }
+}
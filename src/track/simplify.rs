@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::analysis::seconds_since;
+use crate::records::fix::Fix;
+use crate::track::project;
+
+/// Douglas-Peucker simplification in three dimensions, keeping fixes further than `tolerance` meters from the simplified track
+///
+/// Distances are synchronized, a fix is compared with where the simplified track is at the time of the fix rather than with
+/// the closest point of the line, so changes in speed and stops are kept. Altitudes are pressure altitudes.
+/// # examples
+/// ```rust
+/// use std::fs;
+/// use igc_parser::igc_file::IGCFile;
+/// use igc_parser::track::simplify::douglas_peucker;
+/// let file = IGCFile::parse(&fs::read_to_string("./examples/example.igc").unwrap()).unwrap();
+/// let fixes = file.get_fixes().iter().filter_map(|fix| fix.as_ref().ok().cloned()).collect::<Vec<_>>();
+/// let simplified = douglas_peucker(&fixes, 50.);
+/// assert!(simplified.len() < fixes.len());
+/// assert_eq!(simplified.first(), fixes.first());
+/// assert_eq!(simplified.last(), fixes.last());
+/// ```
+pub fn douglas_peucker(fixes: &[Fix], tolerance: f64) -> Vec<Fix> {
+    if fixes.len() < 3 { return fixes.to_vec() }
+    let reference_latitude = fixes[0].coordinates.to_decimal_degrees().0;
+    let points = fixes
+        .iter()
+        .map(|fix| {
+            let (x, y) = project(&fix.coordinates, reference_latitude);
+            (x, y, fix.pressure_alt as f64, seconds_since(&fixes[0].timestamp, &fix.timestamp) as f64)
+        })
+        .collect::<Vec<_>>();
+
+    let mut keep = vec![false; fixes.len()];
+    (keep[0], keep[fixes.len() - 1]) = (true, true);
+    let mut ranges = vec![(0, fixes.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let (start, end) = (points[first], points[last]);
+        let furthest = (first + 1..last)
+            .map(|i| {
+                let (x, y, z, t) = points[i];
+                let fraction = if end.3 > start.3 { ((t - start.3) / (end.3 - start.3)).clamp(0., 1.) } else { 0. };
+                let at = |from: f64, to: f64| from + (to - from) * fraction;
+                let distance = ((x - at(start.0, end.0)).powi(2) + (y - at(start.1, end.1)).powi(2) + (z - at(start.2, end.2)).powi(2)).sqrt();
+                (i, distance)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = furthest {
+            if distance > tolerance {
+                keep[i] = true;
+                ranges.push((first, i));
+                ranges.push((i, last));
+            }
+        }
+    }
+    fixes.iter().zip(keep).filter(|(_, keep)| *keep).map(|(fix, _)| fix.clone()).collect()
+}
+
+/// A fix in the Visvalingam heap, ordered so the smallest area is popped first
+#[derive(Debug, PartialEq)]
+struct Candidate {
+    area: f64,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.area.total_cmp(&self.area).then(other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Visvalingam-Whyatt simplification, removing the fix spanning the smallest triangle with its neighbours until `max_points` are left
+///
+/// The first and last fix are always kept, so at least two fixes are returned.
+pub fn visvalingam(fixes: &[Fix], max_points: usize) -> Vec<Fix> {
+    if fixes.len() <= max_points.max(2) { return fixes.to_vec() }
+    let reference_latitude = fixes[0].coordinates.to_decimal_degrees().0;
+    let points = fixes.iter().map(|fix| project(&fix.coordinates, reference_latitude)).collect::<Vec<_>>();
+    let area = |a: usize, b: usize, c: usize| {
+        let ((ax, ay), (bx, by), (cx, cy)) = (points[a], points[b], points[c]);
+        ((bx - ax) * (cy - ay) - (cx - ax) * (by - ay)).abs() / 2.
+    };
+
+    let mut previous = (0..fixes.len()).map(|i| i.wrapping_sub(1)).collect::<Vec<_>>();
+    let mut next = (1..=fixes.len()).collect::<Vec<_>>();
+    let initial_area = |i: usize| if i == 0 || i == fixes.len() - 1 { f64::INFINITY } else { area(i - 1, i, i + 1) };
+    let mut areas = (0..fixes.len()).map(initial_area).collect::<Vec<_>>();
+    let mut heap = (1..fixes.len() - 1).map(|index| Candidate { area: areas[index], index }).collect::<BinaryHeap<_>>();
+
+    let mut remaining = fixes.len();
+    let mut removed = vec![false; fixes.len()];
+    while remaining > max_points.max(2) {
+        let Some(Candidate { area: smallest, index }) = heap.pop() else { break };
+        // entries are left in the heap when the area of a fix changes, only the latest one counts
+        if removed[index] || smallest != areas[index] { continue }
+        removed[index] = true;
+        remaining -= 1;
+        let (before, after) = (previous[index], next[index]);
+        next[before] = after;
+        previous[after] = before;
+        for neighbour in [before, after] {
+            if neighbour == 0 || neighbour == fixes.len() - 1 { continue }
+            // never below the area of the removed fix, so the order of removal matches the importance of the fixes
+            areas[neighbour] = area(previous[neighbour], neighbour, next[neighbour]).max(smallest);
+            heap.push(Candidate { area: areas[neighbour], index: neighbour });
+        }
+    }
+    fixes.iter().zip(removed).filter(|(_, removed)| !removed).map(|(fix, _)| fix.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixes one second apart labelled with their index in the extension
+    fn track_of(position: impl Fn(u32) -> (f64, f64)) -> Vec<Fix> {
+        (0..=100)
+            .map(|i| {
+                let (latitude, longitude) = position(i);
+                Fix { extension: format!("{i:03}").into(), ..Fix::at(43200 + i, latitude, longitude, 1000) }
+            })
+            .collect()
+    }
+
+    /// Straight north at constant speed with a bump east at 40 seconds, about 11m per second
+    fn track() -> Vec<Fix> {
+        track_of(|i| (51. + i as f64 * 0.0001, if i == 40 { 0.001 } else { 0. }))
+    }
+
+    #[test]
+    fn douglas_peucker_keeps_corners() {
+        let simplified = douglas_peucker(&track(), 20.);
+        assert_eq!(simplified.iter().map(|fix| fix.extension.as_ref()).collect::<Vec<_>>(), ["000", "039", "040", "041", "100"]);
+
+        // a climb is a change in the third dimension
+        let mut fixes = track_of(|i| (51. + i as f64 * 0.0001, 0.));
+        fixes[60].pressure_alt = 1100;
+        assert_eq!(douglas_peucker(&fixes, 20.).len(), 5);
+        assert_eq!(douglas_peucker(&fixes, 200.).len(), 2);
+    }
+
+    #[test]
+    fn douglas_peucker_is_time_aware() {
+        // stopping halfway leaves the fixes on the line but far from where constant speed would put them
+        let fixes = track_of(|i| (51. + i.clamp(25, 75) as f64 * 0.0002 - 0.005, 0.));
+        let simplified = douglas_peucker(&fixes, 20.);
+        assert_eq!(simplified.iter().map(|fix| fix.extension.as_ref()).collect::<Vec<_>>(), ["000", "025", "075", "100"]);
+    }
+
+    #[test]
+    fn visvalingam_removes_smallest_areas() {
+        let simplified = visvalingam(&track(), 5);
+        assert_eq!(simplified.iter().map(|fix| fix.extension.as_ref()).collect::<Vec<_>>(), ["000", "039", "040", "041", "100"]);
+        // the narrow bump spans a smaller triangle than the long legs on either side of it
+        let simplified = visvalingam(&track(), 4);
+        assert_eq!(simplified.len(), 4);
+        assert!(!simplified.iter().any(|fix| fix.extension.as_ref() == "040"));
+        assert_eq!(visvalingam(&track(), 0).len(), 2);
+        assert_eq!(visvalingam(&track()[..2], 1).len(), 2);
+    }
+}